@@ -409,7 +409,7 @@ export async function buildProd() {
     fs.mkdirSync(actionsOut, { recursive: true });
 
     // verify bundled actions exist
-    const bundles = fs.readdirSync(actionsOut).filter(f => f.endsWith(".jsbundle"));
+    const bundles = fs.readdirSync(actionsOut).filter(f => f.endsWith(".mjs") || f.endsWith(".jsbundle"));
     if (bundles.length === 0) {
         const rustActionsDir = path.join(serverDir, "src", "actions_rust");
         const hasRustActions = fs.existsSync(rustActionsDir) &&
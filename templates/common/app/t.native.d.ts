@@ -91,6 +91,23 @@ export interface TitanRequest {
      */
     headers: Record<string, string | undefined>;
 
+    /**
+     * Cookies sent by the client, parsed natively from the `Cookie` header.
+     *
+     * Values are percent-decoded. If a name appears more than once, the
+     * first occurrence wins. Empty object when no cookies were sent.
+     *
+     * @example
+     * ```js
+     * export function me(req) {
+     *   const sid = req.cookies.sid;
+     *   if (!sid) return t.response.json({ error: "Not logged in" }, { status: 401 });
+     *   return { sid };
+     * }
+     * ```
+     */
+    cookies: Record<string, string>;
+
     /**
      * Dynamic route parameters extracted from the URL path.
      *
@@ -130,6 +147,77 @@ export interface TitanRequest {
      * ```
      */
     query: Record<string, string>;
+
+    /**
+     * Error context, only present when the action runs as an error handler
+     * configured in `__config.errors` (404, 405, 500 or 504).
+     *
+     * @example
+     * ```js
+     * // app.js: t.config({ errors: { "500": "serverError" } });
+     * export function serverError(req) {
+     *   return t.response.json(
+     *     { error: "Something went wrong", ref: req.error.correlationId },
+     *     { status: req.error.status }
+     *   );
+     * }
+     * ```
+     */
+    error?: {
+        status: number;
+        code: "NOT_FOUND" | "METHOD_NOT_ALLOWED" | "INTERNAL_ERROR" | "GATEWAY_TIMEOUT";
+        /** Raw error message. Do not expose it to clients in production. */
+        message: string;
+        correlationId: string;
+        /** The action that failed (500 / 504) */
+        action: string | null;
+        /** Allowed methods for the path (405) */
+        allow: string[];
+    };
+
+    /**
+     * Schedule context, only present when the action runs from a `__cron`
+     * job (`t.cron(...)` in app.js). `method` is then `"CRON"`.
+     *
+     * @example
+     * ```js
+     * // app.js: t.cron("0 3 * * *", "nightlyCleanup");
+     * export function nightlyCleanup(req) {
+     *   t.log("cleanup", `run scheduled for ${req.cron.scheduledAt}`);
+     *   return { ok: true };
+     * }
+     * ```
+     */
+    cron?: {
+        schedule: string;
+        /** RFC 3339 time of the occurrence, in the job's timezone */
+        scheduledAt: string;
+        timezone: string;
+    };
+
+    /**
+     * Attempt info, only present when the action runs as a background job
+     * (`t.jobs.enqueue(...)`). `method` is then `"JOB"` and `body` is the
+     * enqueued payload.
+     */
+    job?: {
+        id: string;
+        /** 1 on the first run, 2 on the first retry, ... */
+        attempt: number;
+        retries: number;
+        /** Unix milliseconds */
+        enqueuedAt: number;
+    };
+
+    /**
+     * Call info, only present when the action runs through `t.call(...)`.
+     */
+    call?: {
+        /** 1 for a direct call, 2 for a call made by a callee, ... */
+        depth: number;
+        /** Action names from the outermost caller down to this action */
+        chain: string[];
+    };
 }
 
 /**
@@ -365,17 +453,50 @@ declare global {
      * This model is conceptually similar to **Algebraic Effects** — your code
      * reads as synchronous while the runtime handles concurrency under the hood.
      *
+     * ## Async actions (no replay)
+     *
+     * Inside an `async` action, `drift()` returns a real `Promise`. `await` it
+     * and the action continues exactly where it left off — nothing before the
+     * `await` runs twice, and many drifts cost no more than one.
+     *
+     * ```js
+     * export async function checkout(req) {
+     *   t.log("checkout started");            // runs once
+     *   const user  = await drift(t.fetch("https://api.example.com/user/1"));
+     *   const order = await drift(t.fetch("https://api.example.com/order/99"));
+     *   return { user: JSON.parse(user.body), order: JSON.parse(order.body) };
+     * }
+     * ```
+     *
+     * Functions that are not declared `async` but return a promise (e.g.
+     * transpiled to a generator helper) are replayed until their first
+     * request settles, then switch to this mode; a warning is logged.
+     *
      * ## Important notes
      *
      * - `drift` is the **only** way to await promises in Titan actions.
-     * - The action function may be re-executed (replayed) — avoid side effects
-     *   before the `drift` call that shouldn't be repeated.
+     * - In sync actions the function may be re-executed (replayed) — avoid
+     *   side effects before the `drift` call that shouldn't be repeated, or
+     *   make the action `async` and `await` the drift instead.
      * - Can be used with any Titan API that returns a `Promise` (e.g.,
      *   `t.fetch`, `t.db.connect`, `t.password.hash`, `t.fs.readFile`, etc.).
+     * - Every op has a timeout (`__config.drift_timeout_ms`, default 30s);
+     *   override it per call with `drift(op, { timeout: ms })`. A timed-out
+     *   op resolves to `{ error: "Drift operation timed out after ...ms" }`.
+     * - An array of ops runs concurrently (all at once, or at most
+     *   `concurrency` at a time; default `__config.drift_concurrency`).
+     *   Results keep input order and a failed op only yields its own
+     *   `{ error }` entry, like `Promise.allSettled`.
+     * - If the client disconnects (or `request_timeout_ms` expires, 60 s by
+     *   default), the request's outstanding ops and timers are cancelled, a
+     *   script still running for it is terminated and it never resumes.
      *
      * @typeParam T - The resolved type of the promise.
      * @param promise - The promise or expression to drift (suspend and resolve).
-     * @returns The synchronously resolved value of the input promise.
+     * @param options - `timeout` in milliseconds (per op, also inside a
+     *   batch) and `concurrency` limit for an array of ops.
+     * @returns The resolved value (sync actions), or a `Promise` of it
+     *   (`async` actions).
      *
      * @example
      * ```js
@@ -398,6 +519,18 @@ declare global {
      *
      * @example
      * ```js
+     * // Batch: both requests run concurrently
+     * export function dashboard(req) {
+     *   const [user, feed] = drift([
+     *     t.fetch("https://api.example.com/user/1"),
+     *     t.fetch("https://api.example.com/feed"),
+     *   ], { concurrency: 2 });
+     *   return { user: user.error ? null : JSON.parse(user.body), feed };
+     * }
+     * ```
+     *
+     * @example
+     * ```js
      * // Drift with database operations
      * export function getUsers(req) {
      *   const conn  = drift(t.db.connect(process.env.DATABASE_URL));
@@ -409,7 +542,7 @@ declare global {
      * @see https://titan-docs-ez.vercel.app/docs/14-drift — Drift documentation
      * @see https://titan-docs-ez.vercel.app/docs/runtime-architecture — Gravity Runtime
      */
-    var drift: <T>(promise: Promise<T> | T) => T;
+    var drift: <T>(promise: Promise<T> | T, options?: { timeout?: number; concurrency?: number }) => T;
 
 
     // -----------------------------------------------------------------------
@@ -629,6 +762,35 @@ declare global {
             error?: string;
         }>;
 
+        /**
+         * Call another action in-process (no HTTP round trip). Must be
+         * consumed with `drift()`.
+         *
+         * The callee runs on the worker pool as a `CALL /__call/<action>`
+         * request (override with `method` / `path`) and sees `req.call`.
+         * Resolves to whatever it returns: plain JSON, its `t.response.*`
+         * object (`_isResponse`), or `{ error }` when it throws. Binary bodies
+         * come back base64-encoded with `bodyEncoding: "base64"`.
+         *
+         * Calls nest at most 8 deep; deeper chains resolve to `{ error }`.
+         *
+         * @example
+         * ```js
+         * export function profile(req) {
+         *   const user = drift(t.call("getUser", { params: { id: req.params.id } }));
+         *   return { user };
+         * }
+         * ```
+         */
+        call(action: string, request?: {
+            method?: string;
+            path?: string;
+            body?: any;
+            headers?: Record<string, string>;
+            params?: Record<string, string>;
+            query?: Record<string, string>;
+        }): Promise<any>;
+
 
         // -------------------------------------------------------------------
         //  Authentication & Security
@@ -1014,6 +1176,67 @@ declare global {
         proc: TitanCore.Process;
 
 
+        // -------------------------------------------------------------------
+        //  Responses
+        // -------------------------------------------------------------------
+
+        /**
+         * Response builders for full control over status, headers and body.
+         *
+         * Every builder accepts either an options object (`{ status, headers }`,
+         * preferred — also understood by the fast-path scanner) or the legacy
+         * positional form `(data, status, headers)`.
+         *
+         * @see {@link TitanCore.ResponseModule} for method signatures.
+         */
+        response: TitanCore.ResponseModule;
+
+        /**
+         * Base class for errors that map to an HTTP status.
+         *
+         * Throwing one (or a subclass below) from an action answers with that
+         * status and an RFC 9457 `application/problem+json` body instead of a
+         * generic 500. For server errors (5xx) in production, `detail` and
+         * `details` are left out of the body (the detail is still logged).
+         *
+         * @example
+         * ```js
+         * export function getUser(req) {
+         *   const user = findUser(req.params.id);
+         *   if (!user) throw new t.NotFound("User not found", { details: { id: req.params.id } });
+         *   return user;
+         * }
+         * // → 404 {"type":"about:blank","title":"Not Found","status":404,
+         * //        "detail":"User not found","instance":"/user/7","code":"NOT_FOUND","details":{"id":"7"}}
+         * ```
+         */
+        HttpError: typeof TitanCore.HttpError;
+        /** `400` — see {@link TitanCore.HttpError}. */
+        BadRequest: TitanCore.HttpErrorSubclass;
+        /** `401` — see {@link TitanCore.HttpError}. */
+        Unauthorized: TitanCore.HttpErrorSubclass;
+        /** `403` — see {@link TitanCore.HttpError}. */
+        Forbidden: TitanCore.HttpErrorSubclass;
+        /** `404` — see {@link TitanCore.HttpError}. */
+        NotFound: TitanCore.HttpErrorSubclass;
+        /** `405` — see {@link TitanCore.HttpError}. */
+        MethodNotAllowed: TitanCore.HttpErrorSubclass;
+        /** `409` — see {@link TitanCore.HttpError}. */
+        Conflict: TitanCore.HttpErrorSubclass;
+        /** `410` — see {@link TitanCore.HttpError}. */
+        Gone: TitanCore.HttpErrorSubclass;
+        /** `422` — see {@link TitanCore.HttpError}. */
+        UnprocessableEntity: TitanCore.HttpErrorSubclass;
+        /** `429` — see {@link TitanCore.HttpError}. */
+        TooManyRequests: TitanCore.HttpErrorSubclass;
+        /** `500` — see {@link TitanCore.HttpError}. */
+        InternalServerError: TitanCore.HttpErrorSubclass;
+        /** `501` — see {@link TitanCore.HttpError}. */
+        NotImplemented: TitanCore.HttpErrorSubclass;
+        /** `503` — see {@link TitanCore.HttpError}. */
+        ServiceUnavailable: TitanCore.HttpErrorSubclass;
+
+
         // -------------------------------------------------------------------
         //  Utilities
         // -------------------------------------------------------------------
@@ -1036,6 +1259,45 @@ declare global {
          */
         url: TitanCore.URLModule;
 
+        /**
+         * Background jobs. `enqueue` returns immediately with the job ID; the
+         * action runs later on the worker pool with `payload` as `req.body`.
+         *
+         * A job whose action throws (or times out) is retried up to
+         * `retries` times; after that it lands in the dead-letter list
+         * (`GET /__titan/admin/jobs`). Concurrency and persistence are set
+         * with `__config.jobs_concurrency` and `__config.jobs_store`.
+         *
+         * Calling it before a `drift()` is safe: the replay that follows the
+         * drift gets the same job ID back instead of enqueueing a duplicate.
+         *
+         * @example
+         * ```js
+         * export function signup(req) {
+         *   t.jobs.enqueue("sendWelcomeEmail", { email: req.body.email }, {
+         *     delay: 5000,
+         *     retries: 3,
+         *     backoff: { type: "exponential", delay: 1000 },
+         *   });
+         *   return { ok: true };
+         * }
+         * ```
+         */
+        jobs: {
+            enqueue(
+                action: string,
+                payload?: any,
+                options?: {
+                    /** Milliseconds before the first run (default 0, at most 24h) */
+                    delay?: number;
+                    /** Extra attempts after a failure (default 0) */
+                    retries?: number;
+                    /** A number is a fixed delay in ms; default exponential from 1000ms */
+                    backoff?: number | { type?: "fixed" | "exponential"; delay?: number };
+                }
+            ): string;
+        };
+
         /**
 
         /**
@@ -1108,6 +1370,99 @@ declare global {
         interface TitanResponse {
             readonly __titan_response: true;
         }
+
+        /** Options for `t.HttpError` and its subclasses. */
+        interface HttpErrorOptions {
+            /** Machine-readable code. Defaults to the title in UPPER_SNAKE_CASE. */
+            code?: string;
+            /** Extra data, sent as `details` in the problem+json body. */
+            details?: any;
+            /** Problem type URI. Defaults to `about:blank`. */
+            type?: string;
+            /** Extra response headers (e.g. `WWW-Authenticate`, `Retry-After`). */
+            headers?: Record<string, string | string[]>;
+        }
+
+        /** An error that maps to an HTTP status (`400`–`599`, otherwise `500`). */
+        class HttpError extends Error {
+            constructor(status?: number, message?: string, options?: HttpErrorOptions);
+            status: number;
+            title: string;
+            code: string;
+            details?: any;
+            type?: string;
+            headers?: Record<string, string | string[]>;
+        }
+
+        /** A preset `HttpError` with a fixed status, e.g. `new t.NotFound("No such user")`. */
+        type HttpErrorSubclass = new (message?: string, options?: HttpErrorOptions) => HttpError;
+
+        /** Options accepted by every `t.response.*` builder. */
+        interface ResponseOptions {
+            /** HTTP status code. Defaults to `200` (`302` for redirects). */
+            status?: number;
+            /** Extra response headers. An array value is sent as repeated headers. */
+            headers?: Record<string, string | string[]>;
+            /** Cookies to set, each serialized into its own `Set-Cookie` header. */
+            cookies?: Array<CookieOptions & { name: string; value: string }>;
+        }
+
+        /** Attributes for a `Set-Cookie` header. `Path` defaults to `/`. */
+        interface CookieOptions {
+            maxAge?: number;
+            expires?: Date | string | number;
+            domain?: string;
+            path?: string;
+            httpOnly?: boolean;
+            secure?: boolean;
+            /** `"None"` requires `secure: true` in all modern browsers. */
+            sameSite?: "Strict" | "Lax" | "None" | "strict" | "lax" | "none";
+        }
+
+        /**
+         * Response builders available as `t.response`.
+         *
+         * @example
+         * ```js
+         * export function pixel(req) {
+         *   const gif = t.buffer.fromBase64("R0lGODlhAQABAAAAACw=");
+         *   return t.response.binary(gif, { contentType: "image/gif" });
+         * }
+         * ```
+         */
+        interface ResponseModule {
+            /** JSON body, `Content-Type: application/json`. */
+            json(data: any, options?: ResponseOptions | number, headers?: Record<string, string>): TitanResponse;
+            /** Plain text body, `Content-Type: text/plain`. */
+            text(data: string, options?: ResponseOptions | number, headers?: Record<string, string>): TitanResponse;
+            /** HTML body, `Content-Type: text/html`. */
+            html(data: string, options?: ResponseOptions | number, headers?: Record<string, string>): TitanResponse;
+            /**
+             * Binary body sent as-is (images, PDFs, protobuf, ...).
+             *
+             * The bytes are copied when the action returns, so the buffer may
+             * be reused or mutated afterwards.
+             * `contentType` defaults to `application/octet-stream`.
+             */
+            binary(
+                data: Uint8Array | ArrayBuffer | ArrayBufferView,
+                options?: ResponseOptions & { contentType?: string }
+            ): TitanResponse;
+            /**
+             * Append a `Set-Cookie` header to an existing response and return it.
+             *
+             * @example
+             * ```js
+             * const res = t.response.json({ ok: true });
+             * return t.response.setCookie(res, "sid", token, { httpOnly: true, secure: true, sameSite: "Lax", maxAge: 3600 });
+             * ```
+             */
+            setCookie<R extends TitanResponse>(res: R, name: string, value: string, options?: CookieOptions): R;
+            /** Expire a cookie on the client (`Max-Age=0`). */
+            clearCookie<R extends TitanResponse>(res: R, name: string, options?: CookieOptions): R;
+            /** Redirect (`302` unless a 3xx status is given). */
+            redirect(url: string, options?: ResponseOptions | number, headers?: Record<string, string>): TitanResponse;
+        }
         /**
          * Asynchronous file system operations.
          *
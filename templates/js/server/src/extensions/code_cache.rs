@@ -0,0 +1,187 @@
+//! Persistent V8 Code Cache for action bundles
+//!
+//! After an action bundle (or an ES module it imports) is compiled and run
+//! for the first time, V8's code cache for it is written next to the file
+//! (`<bundle>.v8cache`, through a `<bundle>.<pid>.v8cache.tmp` file). The file
+//! starts with a hash of the source and V8 version; other workers and later
+//! boots consume it instead of compiling from scratch. A mismatched or
+//! rejected cache is simply replaced.
+//!
+//! Caches are also kept in memory, so isolates rebuilt later in the process
+//! (recycle, respawn, hot reload) skip the disk read.
+
+use std::collections::HashMap;
+use std::ffi::OsString;
+use std::fs;
+use std::hash::{Hash, Hasher};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, OnceLock};
+
+pub const EXTENSION: &str = "v8cache";
+
+static MEMORY: OnceLock<Mutex<HashMap<PathBuf, (u64, Arc<Vec<u8>>)>>> = OnceLock::new();
+
+fn memory() -> &'static Mutex<HashMap<PathBuf, (u64, Arc<Vec<u8>>)>> {
+    MEMORY.get_or_init(|| Mutex::new(HashMap::new()))
+}
+
+fn cache_path(bundle: &Path) -> PathBuf {
+    let mut name = OsString::from(bundle.as_os_str());
+    name.push(".");
+    name.push(EXTENSION);
+    PathBuf::from(name)
+}
+
+fn tmp_path(bundle: &Path) -> PathBuf {
+    let mut name = OsString::from(bundle.as_os_str());
+    name.push(format!(".{}.{}.tmp", std::process::id(), EXTENSION));
+    PathBuf::from(name)
+}
+
+/// Cache files and their temporaries, which file watchers should ignore.
+pub fn is_cache_file(path: &Path) -> bool {
+    let name = path.file_name().unwrap_or_default().to_string_lossy();
+    name.ends_with(&format!(".{}", EXTENSION)) || name.ends_with(&format!(".{}.tmp", EXTENSION))
+}
+
+/// Key for a compiled source: V8 rejects caches from other versions anyway,
+/// this just avoids handing it one.
+pub fn hash(source: &str) -> u64 {
+    let mut hasher = std::collections::hash_map::DefaultHasher::new();
+    v8::V8::get_version().hash(&mut hasher);
+    source.hash(&mut hasher);
+    hasher.finish()
+}
+
+/// Cached data for `bundle` compiled from a source with this `hash`.
+pub fn get(bundle: &Path, hash: u64) -> Option<Arc<Vec<u8>>> {
+    if let Some((h, data)) = memory().lock().unwrap().get(bundle) {
+        if *h == hash {
+            return Some(data.clone());
+        }
+    }
+
+    let mut data = fs::read(cache_path(bundle)).ok()?;
+    if data.len() <= 8 || data[..8] != hash.to_le_bytes() {
+        return None;
+    }
+    let data = Arc::new(data.split_off(8));
+    memory()
+        .lock()
+        .unwrap()
+        .insert(bundle.to_path_buf(), (hash, data.clone()));
+    Some(data)
+}
+
+/// Compile `source` (named by `origin`), consuming `cached` when given.
+/// Returns the script and whether V8 rejected the cache.
+pub fn compile<'s>(
+    scope: &mut v8::HandleScope<'s>,
+    source: v8::Local<v8::String>,
+    origin: &v8::ScriptOrigin,
+    cached: Option<&[u8]>,
+) -> (Option<v8::Local<'s, v8::Script>>, bool) {
+    use v8::script_compiler::{CompileOptions, NoCacheReason, Source};
+
+    match cached {
+        Some(bytes) => {
+            let mut src =
+                Source::new_with_cached_data(source, Some(origin), v8::CachedData::new(bytes));
+            let script = v8::script_compiler::compile(
+                scope,
+                &mut src,
+                CompileOptions::ConsumeCodeCache,
+                NoCacheReason::NoReason,
+            );
+            let rejected = src.get_cached_data().is_some_and(|c| c.rejected());
+            (script, rejected)
+        }
+        None => {
+            let mut src = Source::new(source, Some(origin));
+            let script = v8::script_compiler::compile(
+                scope,
+                &mut src,
+                CompileOptions::NoCompileOptions,
+                NoCacheReason::NoReason,
+            );
+            (script, false)
+        }
+    }
+}
+
+/// Module counterpart of `compile`: `source` is compiled as an ES module.
+pub fn compile_module<'s>(
+    scope: &mut v8::HandleScope<'s>,
+    source: v8::Local<v8::String>,
+    origin: &v8::ScriptOrigin,
+    cached: Option<&[u8]>,
+) -> (Option<v8::Local<'s, v8::Module>>, bool) {
+    use v8::script_compiler::{CompileOptions, NoCacheReason, Source};
+
+    match cached {
+        Some(bytes) => {
+            let mut src =
+                Source::new_with_cached_data(source, Some(origin), v8::CachedData::new(bytes));
+            let module = v8::script_compiler::compile_module2(
+                scope,
+                &mut src,
+                CompileOptions::ConsumeCodeCache,
+                NoCacheReason::NoReason,
+            );
+            let rejected = src.get_cached_data().is_some_and(|c| c.rejected());
+            (module, rejected)
+        }
+        None => {
+            let mut src = Source::new(source, Some(origin));
+            (v8::script_compiler::compile_module(scope, &mut src), false)
+        }
+    }
+}
+
+/// Forget a cache V8 rejected, so `store` replaces it.
+pub fn reject(bundle: &Path) {
+    memory().lock().unwrap().remove(bundle);
+}
+
+/// Produce the code cache for a script that has already run (so functions
+/// compiled lazily during the run are included) and persist it.
+pub fn store(scope: &mut v8::HandleScope, bundle: &Path, hash: u64, script: v8::Local<v8::Script>) {
+    if let Some(cache) = script.get_unbound_script(scope).create_code_cache() {
+        persist(bundle, hash, &cache);
+    }
+}
+
+/// `store` for an ES module that has been evaluated.
+pub fn store_module(
+    scope: &mut v8::HandleScope,
+    file: &Path,
+    hash: u64,
+    module: v8::Local<v8::Module>,
+) {
+    if let Some(cache) = module.get_unbound_module_script(scope).create_code_cache() {
+        persist(file, hash, &cache);
+    }
+}
+
+fn persist(bundle: &Path, hash: u64, cache: &[u8]) {
+    let data = Arc::new(cache.to_vec());
+
+    let mut file = hash.to_le_bytes().to_vec();
+    file.extend_from_slice(&data);
+    {
+        // Workers booting together all miss; the first one persists
+        let mut mem = memory().lock().unwrap();
+        if mem.get(bundle).is_some_and(|(h, _)| *h == hash) {
+            return;
+        }
+        mem.insert(bundle.to_path_buf(), (hash, data));
+    }
+
+    // Best effort (read-only deployments just keep the in-memory copy).
+    // Write then rename, so a worker never reads a partial file.
+    let path = cache_path(bundle);
+    let tmp = tmp_path(bundle);
+    if fs::write(&tmp, &file).is_ok() && fs::rename(&tmp, &path).is_err() {
+        let _ = fs::remove_file(&tmp);
+    }
+}
@@ -1,6 +1,8 @@
 //! External native extension loading and FFI.
 //! 
 //! Supports loading `.dll` / `.so` extensions defined in `titan.json` files.
+//! A `main` ending in `.mjs` is evaluated as an ES module; its default
+//! export, when a function, is called with `t`.
 
 use v8;
 use std::path::PathBuf;
@@ -26,6 +28,7 @@ pub struct Registry {
 pub struct ModuleDef {
     pub name: String,
     pub js: String,
+    pub main: PathBuf,
     pub native_indices: HashMap<String, usize>,
 }
 
@@ -150,7 +153,7 @@ pub fn load_project_extensions(root: PathBuf) {
                      }
                 }
                 let js_path = dir.join(&config.main);
-                modules.push(ModuleDef { name: config.name.clone(), js: fs::read_to_string(js_path).unwrap_or_default(), native_indices: mod_natives_map });
+                modules.push(ModuleDef { name: config.name.clone(), js: fs::read_to_string(&js_path).unwrap_or_default(), main: js_path, native_indices: mod_natives_map });
                 println!("{} {} {}", blue("[Titan]"), green("Extension loaded:"), config.name);
             }
         }
@@ -197,6 +200,11 @@ pub fn inject_external_extensions(scope: &mut v8::HandleScope, global: v8::Local
          let act_key = v8_str(scope, "__titan_action");
          let act_val = v8_str(scope, &module.name);
          global.set(scope, act_key.into(), act_val.into());
+
+         if super::modules::is_module(&module.main) {
+             inject_module_extension(scope, &module, t_obj);
+             continue;
+         }
          
          let wrapped_js = format!("(function(t) {{ {} }})", module.js);
          let wrapped_js_str = v8_str(scope, &wrapped_js);
@@ -213,6 +221,48 @@ pub fn inject_external_extensions(scope: &mut v8::HandleScope, global: v8::Local
     }
 }
 
+/// Evaluate an ES module extension and hand `t` to its default export.
+fn inject_module_extension(scope: &mut v8::HandleScope, module: &ModuleDef, t_obj: v8::Local<v8::Object>) {
+    let namespace = match super::modules::evaluate(scope, &module.main) {
+        Ok(namespace) => namespace,
+        Err(e) => {
+            println!("{} {} {} -> {}", blue("[Titan]"), red("Extension module failed:"), module.name, e);
+            return;
+        }
+    };
+    let default_key = v8_str(scope, "default");
+    let Some(func) = namespace.get(scope, default_key.into()).and_then(|v| v8::Local::<v8::Function>::try_from(v).ok()) else {
+        return;
+    };
+    let tc = &mut v8::TryCatch::new(scope);
+    let receiver = v8::undefined(tc).into();
+    if func.call(tc, receiver, &[t_obj.into()]).is_none() {
+        let msg = tc.message().map(|m| m.get(tc).to_rust_string_lossy(tc)).unwrap_or_default();
+        println!("{} {} {} -> {}", blue("[Titan]"), red("Extension module failed:"), module.name, msg);
+    }
+}
+
+/// Native callbacks installed by `inject_external_extensions` (startup snapshot).
+pub fn external_references() -> Vec<v8::ExternalReference<'static>> {
+    use v8::MapFnTo;
+    vec![v8::ExternalReference { function: native_invoke_extension.map_fn_to() }]
+}
+
+/// Stable description of the loaded extensions (names, JS and native slots),
+/// part of the startup snapshot fingerprint.
+pub fn fingerprint(hasher: &mut impl std::hash::Hasher) {
+    use std::hash::Hash;
+    if let Ok(guard) = REGISTRY.lock() {
+        for module in guard.as_ref().map(|r| r.modules.as_slice()).unwrap_or_default() {
+            module.name.hash(hasher);
+            module.js.hash(hasher);
+            let mut natives: Vec<_> = module.native_indices.iter().collect();
+            natives.sort();
+            natives.hash(hasher);
+        }
+    }
+}
+
 fn native_invoke_extension(scope: &mut v8::HandleScope, args: v8::FunctionCallbackArguments, mut retval: v8::ReturnValue) {
     let fn_idx = args.get(0).to_integer(scope).unwrap().value() as usize;
     let js_args_val = args.get(1);
@@ -0,0 +1,638 @@
+//! V8 Inspector (Chrome DevTools) for one worker isolate — dev mode only
+//!
+//! `TITAN_DEV=1 TITAN_INSPECT=9229` (or `1` for the default port, or a full
+//! `host:port`) serves the DevTools protocol for one worker, by default
+//! worker 0 (`TITAN_INSPECT_WORKER` picks another).
+//!
+//! 1. Discovery on the usual endpoints (`/json`, `/json/list`,
+//!    `/json/version`), so `chrome://inspect` finds the target on localhost.
+//! 2. One DevTools session at a time over a WebSocket, bridged to the
+//!    worker thread through a channel the worker loop selects on.
+//! 3. While paused on a breakpoint the worker only serves DevTools messages
+//!    (stepping, evaluation); its queued requests wait.
+//! 4. While a session is attached every request is pinned to the inspected
+//!    worker, so breakpoints are hit on every call.
+//! 5. Every context of the worker is reported, including per-request
+//!    `Isolation::Context` ones, and `console.*` calls show up in DevTools.
+//!
+//! Any worker's `Inspector` also hosts the in-process CPU profiler session
+//! of the admin endpoints (see `diagnostics`).
+//!
+//! The inspected worker boots from source, not from the startup snapshot,
+//! so that every action script is known to the debugger.
+
+use std::cell::{Cell, RefCell};
+use std::collections::VecDeque;
+use std::net::SocketAddr;
+use std::rc::Rc;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Mutex, OnceLock};
+
+use axum::{
+    Json, Router,
+    extract::{
+        Path, WebSocketUpgrade,
+        ws::{Message, WebSocket},
+    },
+    http::StatusCode,
+    response::{IntoResponse, Response},
+    routing::get,
+};
+use crossbeam::channel::{Receiver, Sender};
+use serde_json::{Value, json};
+use tokio::sync::mpsc;
+use v8::inspector::{
+    ChannelBase, ChannelImpl, StringBuffer, StringView, V8Inspector, V8InspectorClientBase,
+    V8InspectorClientImpl, V8InspectorClientTrustLevel, V8InspectorSession,
+};
+
+use super::{PROJECT_ROOT, TitanRuntime};
+use crate::utils::{blue, gray, green, red};
+
+const DEFAULT_PORT: u16 = 9229;
+/// All contexts of the worker share one context group
+const GROUP: i32 = 1;
+
+static HUB: OnceLock<Hub> = OnceLock::new();
+
+/// Bridge between the WebSocket server (tokio) and the inspected worker.
+struct Hub {
+    addr: SocketAddr,
+    worker: usize,
+    /// Target ID in discovery and in the WebSocket path
+    target: String,
+    inbox_tx: Sender<Inbound>,
+    inbox_rx: Receiver<Inbound>,
+    /// Sink of the attached session, tagged with its generation
+    frontend: Mutex<Option<(u64, mpsc::UnboundedSender<String>)>>,
+    generation: AtomicU64,
+    attached: AtomicBool,
+}
+
+/// What the WebSocket side sends to the worker.
+pub enum Inbound {
+    Connect,
+    Message(String),
+    Disconnect,
+}
+
+/// Read `TITAN_INSPECT`. Must run before the worker pool starts; returns
+/// whether the inspector is enabled.
+pub fn configure(production_mode: bool, workers: usize) -> bool {
+    let spec = match std::env::var("TITAN_INSPECT") {
+        Ok(spec) if !spec.is_empty() && spec != "0" => spec,
+        _ => return false,
+    };
+    if production_mode {
+        println!(
+            "{} {}",
+            blue("[Titan]"),
+            red("TITAN_INSPECT is ignored outside dev mode (TITAN_DEV=1)")
+        );
+        return false;
+    }
+
+    let addr = match spec.as_str() {
+        "1" | "true" => Some(SocketAddr::from(([127, 0, 0, 1], DEFAULT_PORT))),
+        port => port
+            .parse::<u16>()
+            .map(|port| SocketAddr::from(([127, 0, 0, 1], port)))
+            .or_else(|_| port.parse::<SocketAddr>())
+            .ok(),
+    };
+    let Some(addr) = addr else {
+        println!(
+            "{} {} '{}' (expected a port or host:port)",
+            blue("[Titan]"),
+            red("Invalid TITAN_INSPECT"),
+            spec
+        );
+        return false;
+    };
+
+    let worker = std::env::var("TITAN_INSPECT_WORKER")
+        .ok()
+        .and_then(|w| w.parse::<usize>().ok())
+        .unwrap_or(0);
+    let worker = if worker < workers {
+        worker
+    } else {
+        println!(
+            "{} {} {} (only {} worker(s)), inspecting worker 0",
+            blue("[Titan]"),
+            red("TITAN_INSPECT_WORKER out of range:"),
+            worker,
+            workers
+        );
+        0
+    };
+
+    let (inbox_tx, inbox_rx) = crossbeam::channel::unbounded();
+    HUB.set(Hub {
+        addr,
+        worker,
+        target: format!("titan-{}-worker-{}", std::process::id(), worker),
+        inbox_tx,
+        inbox_rx,
+        frontend: Mutex::new(None),
+        generation: AtomicU64::new(0),
+        attached: AtomicBool::new(false),
+    })
+    .is_ok()
+}
+
+/// Worker `id` is the inspected one (it boots without the snapshot).
+pub fn inspects(id: usize) -> bool {
+    HUB.get().is_some_and(|hub| hub.worker == id)
+}
+
+/// Worker every request must go to: the inspected one while a DevTools
+/// session is attached.
+#[inline(always)]
+pub fn pinned() -> Option<usize> {
+    HUB.get()
+        .filter(|hub| hub.attached.load(Ordering::Relaxed))
+        .map(|hub| hub.worker)
+}
+
+/// Inspector messages for worker `id` (never ready for the others).
+pub fn inbox(id: usize) -> Receiver<Inbound> {
+    match HUB.get() {
+        Some(hub) if hub.worker == id => hub.inbox_rx.clone(),
+        _ => crossbeam::channel::never(),
+    }
+}
+
+// WORKER SIDE
+
+/// Per-isolate inspector. Must be dropped before its isolate.
+pub struct Inspector {
+    shared: Rc<Shared>,
+    /// In-process session of a running CPU profile (admin endpoints)
+    profiler: Option<Profiler>,
+    // Drop order: the V8 inspector before the client it points to
+    v8: v8::UniqueRef<V8Inspector>,
+    _client: Box<Client>,
+}
+
+/// State reachable both from the worker loop and from V8's pause callbacks.
+struct Shared {
+    inbox: Receiver<Inbound>,
+    v8: Cell<*mut V8Inspector>,
+    session: RefCell<Option<Session>>,
+    /// Nesting of protocol dispatches (a dispatch can pause, and the pause
+    /// loop dispatches more)
+    depth: Cell<usize>,
+    /// Connects / disconnects that arrived during a dispatch
+    deferred: RefCell<VecDeque<Inbound>>,
+    paused: Cell<bool>,
+}
+
+struct Session {
+    // Drop order: the session before its channel
+    session: v8::UniqueRef<V8InspectorSession>,
+    _channel: Box<Channel>,
+}
+
+/// A session driven from Rust: commands are answered synchronously.
+struct Profiler {
+    session: Session,
+    replies: mpsc::UnboundedReceiver<String>,
+    next_id: u64,
+}
+
+struct Client {
+    base: V8InspectorClientBase,
+    shared: Rc<Shared>,
+}
+
+struct Channel {
+    base: ChannelBase,
+    frontend: mpsc::UnboundedSender<String>,
+}
+
+/// Inspector for worker `id`'s isolate, if it is the inspected one.
+pub fn attach(id: usize, isolate: &mut v8::Isolate) -> Option<Inspector> {
+    let hub = HUB.get().filter(|hub| hub.worker == id)?;
+
+    // A rebuilt isolate cannot continue the old one's session: close it so
+    // DevTools offers to reconnect
+    if hub.frontend.lock().unwrap().take().is_some() {
+        hub.attached.store(false, Ordering::Relaxed);
+    }
+
+    Some(Inspector::new(isolate, hub.inbox_rx.clone()))
+}
+
+impl Inspector {
+    /// `inbox` feeds the pause loop: DevTools messages, or `never()` for an
+    /// inspector only used for profiling.
+    pub fn new(isolate: &mut v8::Isolate, inbox: Receiver<Inbound>) -> Self {
+        let shared = Rc::new(Shared {
+            inbox,
+            v8: Cell::new(std::ptr::null_mut()),
+            session: RefCell::new(None),
+            depth: Cell::new(0),
+            deferred: RefCell::new(VecDeque::new()),
+            paused: Cell::new(false),
+        });
+        let mut client = Box::new(Client {
+            base: V8InspectorClientBase::new::<Client>(),
+            shared: shared.clone(),
+        });
+        let mut inspector = V8Inspector::create(isolate, &mut *client);
+        shared.v8.set(&mut *inspector);
+
+        Self {
+            shared,
+            profiler: None,
+            v8: inspector,
+            _client: client,
+        }
+    }
+
+    /// Report a new context; `default` marks the worker's shared one.
+    pub fn context_created(&mut self, context: v8::Local<v8::Context>, name: &str, default: bool) {
+        let aux = if default {
+            r#"{"isDefault":true}"#
+        } else {
+            r#"{"isDefault":false}"#
+        };
+        self.v8.context_created(
+            context,
+            GROUP,
+            StringView::from(name.as_bytes()),
+            StringView::from(aux.as_bytes()),
+        );
+    }
+
+    pub fn context_destroyed(&mut self, context: v8::Local<v8::Context>) {
+        self.v8.context_destroyed(context);
+    }
+
+    /// Start the CPU profiler in a session of its own (beside any DevTools
+    /// one). `interval_us` is the sampling interval.
+    pub fn start_profiling(&mut self, interval_us: Option<u32>) -> Result<(), String> {
+        if self.profiler.is_some() {
+            return Err("CPU profiler already running".to_string());
+        }
+        let (frontend, replies) = mpsc::unbounded_channel();
+        let mut profiler = Profiler {
+            session: Session::open(&mut self.v8, frontend),
+            replies,
+            next_id: 0,
+        };
+        profiler.call("Profiler.enable", json!({}))?;
+        if let Some(interval) = interval_us {
+            profiler.call(
+                "Profiler.setSamplingInterval",
+                json!({ "interval": interval }),
+            )?;
+        }
+        profiler.call("Profiler.start", json!({}))?;
+        self.profiler = Some(profiler);
+        Ok(())
+    }
+
+    /// Stop the CPU profiler; returns the profile (`.cpuprofile` format).
+    pub fn stop_profiling(&mut self) -> Result<Value, String> {
+        let mut profiler = self
+            .profiler
+            .take()
+            .ok_or_else(|| "CPU profiler not running".to_string())?;
+        let mut result = profiler.call("Profiler.stop", json!({}))?;
+        Ok(result["profile"].take())
+    }
+}
+
+impl Drop for Inspector {
+    fn drop(&mut self) {
+        self.profiler.take();
+        self.shared.session.borrow_mut().take();
+    }
+}
+
+impl Session {
+    fn open(inspector: &mut V8Inspector, frontend: mpsc::UnboundedSender<String>) -> Self {
+        let mut channel = Box::new(Channel {
+            base: ChannelBase::new::<Channel>(),
+            frontend,
+        });
+        let session = inspector.connect(
+            GROUP,
+            &mut *channel,
+            StringView::empty(),
+            V8InspectorClientTrustLevel::FullyTrusted,
+        );
+        Session {
+            session,
+            _channel: channel,
+        }
+    }
+}
+
+/// Dispatch a protocol message (UTF-8 JSON; 8-bit string views are Latin-1).
+fn dispatch_text(session: &mut V8InspectorSession, text: &str) {
+    let utf16: Vec<u16>;
+    let message = if text.is_ascii() {
+        StringView::from(text.as_bytes())
+    } else {
+        utf16 = text.encode_utf16().collect();
+        StringView::from(&utf16[..])
+    };
+    session.dispatch_protocol_message(message);
+}
+
+impl Profiler {
+    /// Run one protocol command and return its result.
+    fn call(&mut self, method: &str, params: Value) -> Result<Value, String> {
+        self.next_id += 1;
+        let id = self.next_id;
+        let message = json!({ "id": id, "method": method, "params": params }).to_string();
+        dispatch_text(&mut self.session.session, &message);
+
+        while let Ok(text) = self.replies.try_recv() {
+            let mut reply: Value = serde_json::from_str(&text).map_err(|e| e.to_string())?;
+            if reply["id"].as_u64() != Some(id) {
+                continue;
+            }
+            if let Some(message) = reply["error"]["message"].as_str() {
+                return Err(format!("{}: {}", method, message));
+            }
+            return Ok(reply["result"].take());
+        }
+        Err(format!("{}: no reply", method))
+    }
+}
+
+/// Handle one inspector message on the worker loop.
+pub fn handle(runtime: &mut TitanRuntime, msg: Inbound) {
+    let Some(inspector) = runtime.inspector.as_ref() else {
+        return;
+    };
+    let _scope = &mut v8::HandleScope::new(&mut runtime.isolate);
+    inspector.shared.handle(msg);
+}
+
+impl Shared {
+    fn handle(&self, msg: Inbound) {
+        match msg {
+            Inbound::Message(text) => self.dispatch(&text),
+            // The session cannot change under a running dispatch: resume and
+            // apply once it returns
+            other if self.depth.get() > 0 => {
+                if let Inbound::Disconnect = other {
+                    self.paused.set(false);
+                }
+                self.deferred.borrow_mut().push_back(other);
+            }
+            Inbound::Connect => self.connect(),
+            Inbound::Disconnect => {
+                self.session.borrow_mut().take();
+                self.paused.set(false);
+            }
+        }
+    }
+
+    fn connect(&self) {
+        let Some((_, frontend)) = HUB
+            .get()
+            .and_then(|hub| hub.frontend.lock().unwrap().clone())
+        else {
+            return;
+        };
+        // SAFETY: set in `Inspector::new`; the V8 inspector outlives
+        // `Shared`'s use by the worker (dropped with the `Inspector` holding both)
+        let inspector = unsafe { &mut *self.v8.get() };
+        *self.session.borrow_mut() = Some(Session::open(inspector, frontend));
+    }
+
+    fn dispatch(&self, text: &str) {
+        let session = self
+            .session
+            .borrow_mut()
+            .as_mut()
+            .map(|s| &mut *s.session as *mut V8InspectorSession);
+        let Some(session) = session else {
+            return;
+        };
+
+        self.depth.set(self.depth.get() + 1);
+        // SAFETY: sessions are only replaced or dropped at depth 0 (see
+        // `handle`), so the pointer stays valid through nested dispatches
+        dispatch_text(unsafe { &mut *session }, text);
+        self.depth.set(self.depth.get() - 1);
+
+        if self.depth.get() == 0 {
+            let deferred: Vec<Inbound> = self.deferred.borrow_mut().drain(..).collect();
+            for msg in deferred {
+                self.handle(msg);
+            }
+        }
+    }
+}
+
+impl V8InspectorClientImpl for Client {
+    fn base(&self) -> &V8InspectorClientBase {
+        &self.base
+    }
+
+    fn base_mut(&mut self) -> &mut V8InspectorClientBase {
+        &mut self.base
+    }
+
+    unsafe fn base_ptr(this: *const Self) -> *const V8InspectorClientBase
+    where
+        Self: Sized,
+    {
+        // SAFETY: `this` points to a live `Client`
+        unsafe { std::ptr::addr_of!((*this).base) }
+    }
+
+    /// Paused on a breakpoint: serve DevTools until it resumes.
+    fn run_message_loop_on_pause(&mut self, _context_group_id: i32) {
+        let shared = self.shared.clone();
+        shared.paused.set(true);
+        while shared.paused.get() {
+            match shared.inbox.recv() {
+                Ok(msg) => shared.handle(msg),
+                Err(_) => break,
+            }
+        }
+    }
+
+    fn quit_message_loop_on_pause(&mut self) {
+        self.shared.paused.set(false);
+    }
+
+    fn run_if_waiting_for_debugger(&mut self, _context_group_id: i32) {}
+}
+
+impl Channel {
+    fn send(&mut self, message: v8::UniquePtr<StringBuffer>) {
+        if let Some(message) = message.into_option() {
+            let _ = self.frontend.send(message.string().to_string());
+        }
+    }
+}
+
+impl ChannelImpl for Channel {
+    fn base(&self) -> &ChannelBase {
+        &self.base
+    }
+
+    fn base_mut(&mut self) -> &mut ChannelBase {
+        &mut self.base
+    }
+
+    unsafe fn base_ptr(this: *const Self) -> *const ChannelBase
+    where
+        Self: Sized,
+    {
+        // SAFETY: `this` points to a live `Channel`
+        unsafe { std::ptr::addr_of!((*this).base) }
+    }
+
+    fn send_response(&mut self, _call_id: i32, message: v8::UniquePtr<StringBuffer>) {
+        self.send(message);
+    }
+
+    fn send_notification(&mut self, message: v8::UniquePtr<StringBuffer>) {
+        self.send(message);
+    }
+
+    fn flush_protocol_notifications(&mut self) {}
+}
+
+// DEVTOOLS SERVER
+
+/// Serve discovery and the DevTools WebSocket (spawn on the tokio runtime).
+pub async fn serve() {
+    let Some(hub) = HUB.get() else {
+        return;
+    };
+    let listener = match tokio::net::TcpListener::bind(hub.addr).await {
+        Ok(listener) => listener,
+        Err(e) => {
+            println!(
+                "{} {} {} ({})",
+                blue("[Titan]"),
+                red("Inspector failed to listen on"),
+                hub.addr,
+                e
+            );
+            return;
+        }
+    };
+
+    let app = Router::new()
+        .route("/json", get(list))
+        .route("/json/list", get(list))
+        .route("/json/version", get(version))
+        .route("/{target}", get(session));
+
+    println!(
+        "{} {} ws://{}/{} {}",
+        blue("[Titan]"),
+        green("Inspector listening on"),
+        hub.addr,
+        hub.target,
+        gray(&format!("(worker {}, open chrome://inspect)", hub.worker))
+    );
+    let _ = axum::serve(listener, app).await;
+}
+
+async fn list() -> Json<Value> {
+    let hub = HUB.get().unwrap();
+    let socket = format!("{}/{}", hub.addr, hub.target);
+    let root = PROJECT_ROOT
+        .get()
+        .map(|p| p.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    Json(json!([{
+        "description": "Titan worker isolate",
+        "devtoolsFrontendUrl": format!(
+            "devtools://devtools/bundled/js_app.html?experiments=true&v8only=true&ws={}",
+            socket
+        ),
+        "id": hub.target,
+        "title": format!("titan worker {}", hub.worker),
+        "type": "node",
+        "url": format!("file://{}", root),
+        "webSocketDebuggerUrl": format!("ws://{}", socket),
+    }]))
+}
+
+async fn version() -> Json<Value> {
+    Json(json!({
+        "Browser": format!("Titan/{}", env!("CARGO_PKG_VERSION")),
+        "Protocol-Version": "1.3",
+        "V8-Version": v8::V8::get_version(),
+    }))
+}
+
+async fn session(Path(target): Path<String>, ws: WebSocketUpgrade) -> Response {
+    let hub = HUB.get().unwrap();
+    if target != hub.target {
+        return (StatusCode::NOT_FOUND, "Unknown inspector target").into_response();
+    }
+    if hub.attached.load(Ordering::Relaxed) {
+        return (
+            StatusCode::CONFLICT,
+            "A DevTools session is already attached",
+        )
+            .into_response();
+    }
+    ws.on_upgrade(run_session)
+}
+
+/// Bridge one DevTools WebSocket to the worker until either side closes.
+async fn run_session(mut socket: WebSocket) {
+    let hub = HUB.get().unwrap();
+    let (tx, mut rx) = mpsc::unbounded_channel();
+    let generation = hub.generation.fetch_add(1, Ordering::Relaxed);
+    {
+        let mut frontend = hub.frontend.lock().unwrap();
+        if frontend.is_some() {
+            return;
+        }
+        *frontend = Some((generation, tx));
+    }
+    hub.attached.store(true, Ordering::Relaxed);
+    let _ = hub.inbox_tx.send(Inbound::Connect);
+    println!(
+        "{} {} {}",
+        blue("[Titan]"),
+        green("DevTools attached"),
+        gray(&format!("(requests pinned to worker {})", hub.worker))
+    );
+
+    loop {
+        tokio::select! {
+            incoming = socket.recv() => match incoming {
+                Some(Ok(Message::Text(text))) => {
+                    let _ = hub.inbox_tx.send(Inbound::Message(text.as_str().to_string()));
+                }
+                Some(Ok(Message::Close(_))) | Some(Err(_)) | None => break,
+                Some(Ok(_)) => {}
+            },
+            // Closed when the worker's isolate is rebuilt
+            outgoing = rx.recv() => match outgoing {
+                Some(text) => {
+                    if socket.send(Message::Text(text.into())).await.is_err() {
+                        break;
+                    }
+                }
+                None => break,
+            },
+        }
+    }
+
+    let mut frontend = hub.frontend.lock().unwrap();
+    if frontend.as_ref().is_some_and(|(g, _)| *g == generation) {
+        *frontend = None;
+        hub.attached.store(false, Ordering::Relaxed);
+        let _ = hub.inbox_tx.send(Inbound::Disconnect);
+    }
+    println!("{} {}", blue("[Titan]"), gray("DevTools detached"));
+}
@@ -0,0 +1,616 @@
+//! ES Module Loader
+//!
+//! Action bundles ending in `.mjs` and extensions whose `main` is an `.mjs`
+//! file are evaluated as real ES modules instead of being wrapped in a
+//! function.
+//!
+//! 1. Relative specifiers (`./x`, `../x`, `/x`) resolve against the importing
+//!    file; `.mjs`, `.js` and `index.*` are tried like Node does.
+//! 2. Bare specifiers are looked up in `node_modules` walking up from the
+//!    importing file, then the project root. `package.json` `exports`
+//!    (`import` / `module` / `default` conditions), `module` and `main` pick
+//!    the entry. CommonJS entries (`.cjs`, or `.js` in a package whose
+//!    `"type"` is not `"module"`) are rejected: they have to be bundled.
+//! 3. Top-level await is supported: microtasks are drained after evaluation.
+//!    Only promises that settle without I/O can be awaited at load time.
+//! 4. An action is the module's `default` export, else (legacy bundles) its
+//!    export named after the action, wrapped by `defineAction`. It is also
+//!    published as `globalThis[name]`, like classic bundles, so snapshots and
+//!    isolated contexts find it.
+//! 5. Compiled modules go through the code cache like classic bundles.
+//!
+//! Modules are shared within one load pass (a context's extensions and
+//! actions); hot reload starts a new pass, so edits are picked up.
+//! Dynamic `import()` is not supported.
+
+use std::cell::RefCell;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use serde_json::Value;
+
+use super::{PROJECT_ROOT, code_cache, v8_str};
+
+thread_local! {
+    static GRAPH: RefCell<Graph> = RefCell::new(Graph::default());
+}
+
+#[derive(Default)]
+struct Graph {
+    depth: usize,
+    modules: Vec<Entry>,
+}
+
+struct Entry {
+    path: PathBuf,
+    module: v8::Global<v8::Module>,
+    /// Source hash, when the code cache has to be written after evaluation
+    store: Option<u64>,
+}
+
+/// Keeps the module graph alive; nested passes share the outermost one.
+pub struct Pass(());
+
+impl Pass {
+    pub fn enter() -> Self {
+        GRAPH.with(|g| g.borrow_mut().depth += 1);
+        Pass(())
+    }
+}
+
+impl Drop for Pass {
+    fn drop(&mut self) {
+        GRAPH.with(|g| {
+            let mut graph = g.borrow_mut();
+            graph.depth -= 1;
+            if graph.depth == 0 {
+                graph.modules.clear();
+            }
+        });
+    }
+}
+
+pub fn is_module(path: &Path) -> bool {
+    path.extension().is_some_and(|ext| ext == "mjs")
+}
+
+/// Evaluate an action module and return its action function.
+pub fn load_action(
+    scope: &mut v8::HandleScope,
+    name: &str,
+    path: &Path,
+) -> Result<v8::Global<v8::Function>, String> {
+    let namespace = evaluate(scope, path)?;
+
+    let func = ["default", name].into_iter().find_map(|export| {
+        let key = v8_str(scope, export);
+        namespace
+            .get(scope, key.into())
+            .and_then(|v| v8::Local::<v8::Function>::try_from(v).ok())
+    });
+    let Some(func) = func else {
+        return Err(format!(
+            "neither the default export nor an export named '{}' is a function",
+            name
+        ));
+    };
+
+    // Same wrapping classic bundles get (body parsing, request completion)
+    let global = scope.get_current_context().global(scope);
+    let define_key = v8_str(scope, "defineAction");
+    let define = global
+        .get(scope, define_key.into())
+        .and_then(|v| v8::Local::<v8::Function>::try_from(v).ok())
+        .ok_or("defineAction is not installed")?;
+    let tc = &mut v8::TryCatch::new(scope);
+    let wrapped = define
+        .call(tc, global.into(), &[func.into()])
+        .and_then(|v| v8::Local::<v8::Function>::try_from(v).ok());
+    let Some(wrapped) = wrapped else {
+        return Err(caught(tc).unwrap_or_else(|| "defineAction failed".to_string()));
+    };
+
+    let key = v8_str(tc, name);
+    global.set(tc, key.into(), wrapped.into());
+    Ok(v8::Global::new(tc, wrapped))
+}
+
+/// Link and evaluate the module at `path` (with its imports) in the current
+/// context, settling top-level await. Returns the module namespace.
+pub fn evaluate<'s>(
+    scope: &mut v8::HandleScope<'s>,
+    path: &Path,
+) -> Result<v8::Local<'s, v8::Object>, String> {
+    let _pass = Pass::enter();
+    let tc = &mut v8::TryCatch::new(scope);
+
+    // Imports resolve to canonical paths; the entry must match them
+    let path = path.canonicalize().unwrap_or_else(|_| path.to_path_buf());
+    let module = match module_for(tc, &path) {
+        Ok(module) => module,
+        Err(e) => return Err(caught(tc).unwrap_or(e)),
+    };
+    if module.instantiate_module(tc, resolve).is_none() {
+        return Err(caught(tc).unwrap_or_else(|| "failed to link imports".to_string()));
+    }
+    let Some(result) = module.evaluate(tc) else {
+        return Err(caught(tc).unwrap_or_else(|| "evaluation failed".to_string()));
+    };
+
+    tc.perform_microtask_checkpoint();
+    if let Ok(promise) = v8::Local::<v8::Promise>::try_from(result) {
+        match promise.state() {
+            v8::PromiseState::Fulfilled => {}
+            v8::PromiseState::Rejected => {
+                let reason = promise.result(tc);
+                return Err(describe(tc, reason));
+            }
+            v8::PromiseState::Pending => {
+                return Err("top-level await did not settle during load \
+                     (I/O cannot be awaited at load time)"
+                    .to_string());
+            }
+        }
+    }
+
+    store_caches(tc);
+    v8::Local::<v8::Object>::try_from(module.get_module_namespace())
+        .map_err(|_| "module has no namespace".to_string())
+}
+
+/// The module for `path`, compiled on first use in this pass.
+fn module_for<'s>(
+    scope: &mut v8::HandleScope<'s>,
+    path: &Path,
+) -> Result<v8::Local<'s, v8::Module>, String> {
+    let known = GRAPH.with(|g| {
+        g.borrow()
+            .modules
+            .iter()
+            .find(|e| e.path == path)
+            .map(|e| e.module.clone())
+    });
+    if let Some(module) = known {
+        return Ok(v8::Local::new(scope, module));
+    }
+
+    super::snapshot::record_input(path);
+    let code = fs::read_to_string(path).map_err(|e| format!("{}: {}", path.display(), e))?;
+    let source = v8_str(scope, &code);
+    let name = v8_str(scope, &super::source_maps::script_name(path));
+    let origin = v8::ScriptOrigin::new(
+        scope,
+        name.into(),
+        0,
+        0,
+        false,
+        0,
+        None,
+        false,
+        false,
+        true,
+        None,
+    );
+
+    let hash = code_cache::hash(&code);
+    let cached = code_cache::get(path, hash);
+    let (module, rejected) = code_cache::compile_module(
+        scope,
+        source,
+        &origin,
+        cached.as_deref().map(|c| c.as_slice()),
+    );
+    let module = module.ok_or_else(|| format!("failed to compile {}", path.display()))?;
+    if rejected {
+        code_cache::reject(path);
+    }
+
+    let entry = Entry {
+        path: path.to_path_buf(),
+        module: v8::Global::new(scope, module),
+        store: (cached.is_none() || rejected).then_some(hash),
+    };
+    GRAPH.with(|g| g.borrow_mut().modules.push(entry));
+    Ok(module)
+}
+
+/// V8 resolve callback: specifier + referrer → compiled module.
+fn resolve<'a>(
+    context: v8::Local<'a, v8::Context>,
+    specifier: v8::Local<'a, v8::String>,
+    _attributes: v8::Local<'a, v8::FixedArray>,
+    referrer: v8::Local<'a, v8::Module>,
+) -> Option<v8::Local<'a, v8::Module>> {
+    let scope = &mut unsafe { v8::CallbackScope::new(context) };
+    let tc = &mut v8::TryCatch::new(scope);
+    let specifier = specifier.to_rust_string_lossy(tc);
+
+    let from = GRAPH.with(|g| {
+        g.borrow()
+            .modules
+            .iter()
+            .find(|e| v8::Local::new(tc, &e.module) == referrer)
+            .map(|e| e.path.clone())
+    });
+    let result = from
+        .ok_or_else(|| format!("cannot resolve '{}': unknown importer", specifier))
+        .and_then(|from| resolve_path(&specifier, &from))
+        .and_then(|path| module_for(tc, &path));
+
+    match result {
+        Ok(module) => Some(module),
+        Err(_) if tc.has_caught() => {
+            tc.rethrow();
+            None
+        }
+        Err(msg) => {
+            let msg = v8_str(tc, &msg);
+            let exception = v8::Exception::error(tc, msg);
+            tc.throw_exception(exception);
+            None
+        }
+    }
+}
+
+fn resolve_path(specifier: &str, from: &Path) -> Result<PathBuf, String> {
+    let base = from.parent().unwrap_or(Path::new("."));
+    let spec = specifier.strip_prefix("file://").unwrap_or(specifier);
+    let not_found = || {
+        format!(
+            "cannot find module '{}' imported from {}",
+            specifier,
+            from.display()
+        )
+    };
+
+    if spec.starts_with("./") || spec.starts_with("../") || spec.starts_with('/') {
+        return resolve_file(&base.join(spec)).ok_or_else(not_found);
+    }
+    if spec.starts_with("node:") {
+        return Err(format!(
+            "Node builtin '{}' is not available (bundle the action to use the @titanpl/node shims)",
+            specifier
+        ));
+    }
+    let (path, esm) = resolve_package(spec, base).ok_or_else(not_found)?;
+    if !esm && is_commonjs(&path) {
+        return Err(format!(
+            "'{}' resolves to CommonJS ({}), which cannot be imported as an ES module \
+             (bundle the action, or use a package with an ESM build)",
+            specifier,
+            path.display()
+        ));
+    }
+    Ok(path)
+}
+
+/// Exact file, then with `.mjs` / `.js`, then as a directory.
+fn resolve_file(path: &Path) -> Option<PathBuf> {
+    if path.is_file() {
+        return path.canonicalize().ok();
+    }
+    for ext in ["mjs", "js"] {
+        let mut with_ext = path.as_os_str().to_owned();
+        with_ext.push(".");
+        with_ext.push(ext);
+        let candidate = PathBuf::from(with_ext);
+        if candidate.is_file() {
+            return candidate.canonicalize().ok();
+        }
+    }
+    if path.is_dir() {
+        if let Some((entry, _)) = package_entry(path, ".") {
+            return Some(entry);
+        }
+        return ["index.mjs", "index.js"]
+            .iter()
+            .map(|index| path.join(index))
+            .find(|p| p.is_file())
+            .and_then(|p| p.canonicalize().ok());
+    }
+    None
+}
+
+/// `pkg`, `pkg/sub/path`, `@scope/pkg` or `@scope/pkg/sub/path`, and whether
+/// the package declares that file an ES module.
+fn resolve_package(spec: &str, from: &Path) -> Option<(PathBuf, bool)> {
+    let mut parts = spec.splitn(if spec.starts_with('@') { 3 } else { 2 }, '/');
+    let name = if spec.starts_with('@') {
+        format!("{}/{}", parts.next()?, parts.next()?)
+    } else {
+        parts.next()?.to_string()
+    };
+    let subpath = parts.next().filter(|s| !s.is_empty());
+
+    let project = PROJECT_ROOT.get().map(|root| root.join("node_modules"));
+    let candidates = from
+        .ancestors()
+        .map(|dir| dir.join("node_modules"))
+        .chain(project);
+    for node_modules in candidates {
+        let dir = node_modules.join(&name);
+        if !dir.is_dir() {
+            continue;
+        }
+        let export = subpath.map_or(".".to_string(), |s| format!("./{}", s));
+        return package_entry(&dir, &export).or_else(|| {
+            resolve_file(&subpath.map_or(dir.clone(), |s| dir.join(s))).map(|path| (path, false))
+        });
+    }
+    None
+}
+
+/// Entry for `subpath` ("." or "./x") from package.json `exports`, else
+/// `module` / `main` for the package root. The flag is set when the entry
+/// came from an `import` / `module` condition or the `module` field.
+fn package_entry(dir: &Path, subpath: &str) -> Option<(PathBuf, bool)> {
+    let manifest = fs::read_to_string(dir.join("package.json")).ok()?;
+    let pkg: Value = serde_json::from_str(&manifest).ok()?;
+
+    let target = match &pkg["exports"] {
+        Value::Object(map) if map.keys().any(|k| k.starts_with('.')) => {
+            map.get(subpath).and_then(|v| condition(v, false))
+        }
+        exports @ (Value::String(_) | Value::Object(_) | Value::Array(_)) if subpath == "." => {
+            condition(exports, false)
+        }
+        _ => None,
+    };
+    let (target, esm) = target.or_else(|| {
+        (subpath == ".")
+            .then(|| {
+                let module = pkg["module"].as_str().map(|m| (m, true));
+                module.or(pkg["main"].as_str().map(|m| (m, false)))
+            })
+            .flatten()
+            .map(|(target, esm)| (target.to_string(), esm))
+    })?;
+    resolve_file(&dir.join(target)).map(|path| (path, esm))
+}
+
+/// Target of an `exports` entry, preferring the ESM conditions; `require`
+/// is never picked. `esm` tracks whether an ESM condition was taken.
+fn condition(value: &Value, esm: bool) -> Option<(String, bool)> {
+    match value {
+        Value::String(target) => Some((target.clone(), esm)),
+        Value::Object(map) => [("import", true), ("module", true), ("default", esm)]
+            .iter()
+            .find_map(|(key, esm)| map.get(*key).and_then(|v| condition(v, *esm))),
+        Value::Array(list) => list.iter().find_map(|v| condition(v, esm)),
+        _ => None,
+    }
+}
+
+/// `.cjs`, or `.js` whose nearest package.json does not say `"type": "module"`
+/// (Node's rule).
+fn is_commonjs(path: &Path) -> bool {
+    match path.extension().and_then(|ext| ext.to_str()) {
+        Some("cjs") => true,
+        Some("js") => {
+            let manifest = path
+                .ancestors()
+                .skip(1)
+                .map(|dir| dir.join("package.json"))
+                .find(|p| p.is_file())
+                .and_then(|p| fs::read_to_string(p).ok());
+            let kind = manifest
+                .and_then(|m| serde_json::from_str::<Value>(&m).ok())
+                .and_then(|pkg| pkg["type"].as_str().map(str::to_string));
+            kind.as_deref() != Some("module")
+        }
+        _ => false,
+    }
+}
+
+/// Write code caches for modules compiled (and now evaluated) in this pass.
+fn store_caches(scope: &mut v8::HandleScope) {
+    let pending: Vec<(PathBuf, u64, v8::Global<v8::Module>)> = GRAPH.with(|g| {
+        g.borrow_mut()
+            .modules
+            .iter_mut()
+            .filter_map(|e| {
+                e.store
+                    .take()
+                    .map(|hash| (e.path.clone(), hash, e.module.clone()))
+            })
+            .collect()
+    });
+    for (path, hash, module) in pending {
+        let module = v8::Local::new(scope, module);
+        code_cache::store_module(scope, &path, hash, module);
+    }
+}
+
+fn caught(tc: &mut v8::TryCatch<v8::HandleScope>) -> Option<String> {
+    let exception = tc.exception()?;
+    Some(describe(tc, exception))
+}
+
+/// Stack trace when the value has one, else its string form.
+fn describe(scope: &mut v8::HandleScope, value: v8::Local<v8::Value>) -> String {
+    if let Ok(obj) = v8::Local::<v8::Object>::try_from(value) {
+        let key = v8_str(scope, "stack");
+        if let Some(stack) = obj.get(scope, key.into()).filter(|s| s.is_string()) {
+            return super::source_maps::remap_stack(&stack.to_rust_string_lossy(scope));
+        }
+    }
+    value.to_rust_string_lossy(scope)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Fresh directory with `files` (path, contents) written into it.
+    fn fixture(name: &str, files: &[(&str, &str)]) -> PathBuf {
+        let dir =
+            std::env::temp_dir().join(format!("titan-modules-{}-{}", std::process::id(), name));
+        let _ = fs::remove_dir_all(&dir);
+        for (path, contents) in files {
+            let path = dir.join(path);
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(path, contents).unwrap();
+        }
+        dir.canonicalize().unwrap()
+    }
+
+    #[test]
+    fn relative_specifiers_try_extensions_and_index() {
+        let dir = fixture(
+            "relative",
+            &[
+                ("app/action.mjs", ""),
+                ("app/util.mjs", ""),
+                ("app/lib/index.js", ""),
+                ("shared.js", ""),
+            ],
+        );
+        let from = dir.join("app/action.mjs");
+        assert_eq!(
+            resolve_path("./util", &from).unwrap(),
+            dir.join("app/util.mjs")
+        );
+        assert_eq!(
+            resolve_path("./util.mjs", &from).unwrap(),
+            dir.join("app/util.mjs")
+        );
+        assert_eq!(
+            resolve_path("./lib", &from).unwrap(),
+            dir.join("app/lib/index.js")
+        );
+        assert_eq!(
+            resolve_path("../shared", &from).unwrap(),
+            dir.join("shared.js")
+        );
+        assert!(
+            resolve_path("./missing", &from)
+                .unwrap_err()
+                .contains("cannot find module")
+        );
+        assert!(
+            resolve_path("node:fs", &from)
+                .unwrap_err()
+                .contains("node:fs")
+        );
+    }
+
+    #[test]
+    fn bare_specifiers_use_package_exports() {
+        let dir = fixture(
+            "exports",
+            &[
+                ("app/action.mjs", ""),
+                (
+                    "node_modules/pkg/package.json",
+                    r#"{ "exports": { ".": { "require": "./cjs.js", "import": "./esm.mjs" }, "./sub": "./sub.mjs" } }"#,
+                ),
+                ("node_modules/pkg/cjs.js", ""),
+                ("node_modules/pkg/esm.mjs", ""),
+                ("node_modules/pkg/sub.mjs", ""),
+                (
+                    "node_modules/@scope/lib/package.json",
+                    r#"{ "module": "./dist/index.mjs", "main": "./index.js" }"#,
+                ),
+                ("node_modules/@scope/lib/dist/index.mjs", ""),
+                ("node_modules/@scope/lib/index.js", ""),
+            ],
+        );
+        let from = dir.join("app/action.mjs");
+        let pkg = dir.join("node_modules/pkg");
+        assert_eq!(resolve_path("pkg", &from).unwrap(), pkg.join("esm.mjs"));
+        assert_eq!(resolve_path("pkg/sub", &from).unwrap(), pkg.join("sub.mjs"));
+        assert_eq!(
+            resolve_path("@scope/lib", &from).unwrap(),
+            dir.join("node_modules/@scope/lib/dist/index.mjs")
+        );
+        assert!(resolve_path("other", &from).is_err());
+    }
+
+    #[test]
+    fn package_entry_falls_back_to_main() {
+        let dir = fixture(
+            "main",
+            &[
+                ("package.json", r#"{ "main": "lib/entry" }"#),
+                ("lib/entry.js", ""),
+            ],
+        );
+        assert_eq!(
+            package_entry(&dir, "."),
+            Some((dir.join("lib/entry.js"), false))
+        );
+        assert_eq!(package_entry(&dir, "./other"), None);
+    }
+
+    #[test]
+    fn package_entry_reads_string_and_array_exports() {
+        let dir = fixture(
+            "string-exports",
+            &[
+                (
+                    "package.json",
+                    r#"{ "exports": "./index.mjs", "main": "./index.js" }"#,
+                ),
+                ("index.mjs", ""),
+            ],
+        );
+        assert_eq!(
+            package_entry(&dir, "."),
+            Some((dir.join("index.mjs"), false))
+        );
+
+        let dir = fixture(
+            "array-exports",
+            &[
+                (
+                    "package.json",
+                    r#"{ "exports": [{ "import": "./a.mjs" }, "./b.js"] }"#,
+                ),
+                ("a.mjs", ""),
+            ],
+        );
+        assert_eq!(package_entry(&dir, "."), Some((dir.join("a.mjs"), true)));
+    }
+
+    #[test]
+    fn commonjs_packages_are_rejected() {
+        let dir = fixture(
+            "commonjs",
+            &[
+                ("app/action.mjs", ""),
+                ("node_modules/cjs/package.json", r#"{ "main": "index.js" }"#),
+                ("node_modules/cjs/index.js", "module.exports = {};"),
+                ("node_modules/cjs/lib/util.js", ""),
+                (
+                    "node_modules/req/package.json",
+                    r#"{ "exports": { "require": "./index.cjs" }, "main": "./index.cjs" }"#,
+                ),
+                ("node_modules/req/index.cjs", ""),
+                (
+                    "node_modules/esm/package.json",
+                    r#"{ "type": "module", "main": "index.js" }"#,
+                ),
+                ("node_modules/esm/index.js", ""),
+                (
+                    "node_modules/dual/package.json",
+                    r#"{ "module": "./dist/index.esm.js", "main": "./dist/index.js" }"#,
+                ),
+                ("node_modules/dual/dist/index.esm.js", ""),
+                ("node_modules/dual/dist/index.js", ""),
+            ],
+        );
+        let from = dir.join("app/action.mjs");
+        for spec in ["cjs", "cjs/lib/util", "req"] {
+            let err = resolve_path(spec, &from).unwrap_err();
+            assert!(err.contains("resolves to CommonJS"), "{}: {}", spec, err);
+        }
+        assert_eq!(
+            resolve_path("esm", &from).unwrap(),
+            dir.join("node_modules/esm/index.js")
+        );
+        assert_eq!(
+            resolve_path("dual", &from).unwrap(),
+            dir.join("node_modules/dual/dist/index.esm.js")
+        );
+    }
+}
@@ -0,0 +1,284 @@
+//! V8 Startup Snapshot
+//!
+//! The bootstrapped context (Titan APIs, titan_core.js, extension JS and every
+//! action bundle, compiled code included) is serialized once and cached on
+//! disk. Workers deserialize it instead of re-evaluating everything.
+//!
+//! 1. `prepare` runs at boot: it loads the cache when its fingerprint (server
+//!    binary, project root, action bundles, extensions, and every other file
+//!    read while bootstrapping: imported modules, `t.readSync` at load time)
+//!    matches, otherwise it builds a new one in a child process
+//!    (`--build-snapshot`), so a V8 abort on unsupported state cannot take
+//!    the server down.
+//! 2. `--build-snapshot` can also be run at build time (e.g. in a Dockerfile).
+//! 3. Any failure falls back to the regular boot. After a hot reload the
+//!    snapshot is stale, so isolates rebuilt from then on boot from source.
+//! 4. The environment is never baked in (it would write secrets to disk):
+//!    `t.env` is read lazily on each worker. Code that reads it at load time
+//!    makes the build fail, and the server boots from source.
+//!
+//! Cache: `.titan/snapshot.bin` under the project root (`TITAN_SNAPSHOT_PATH`
+//! overrides). Disable with `__config.snapshot: false`.
+
+use std::cell::RefCell;
+use std::collections::{BTreeSet, HashMap};
+use std::fs;
+use std::hash::{Hash, Hasher};
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Instant;
+
+use super::{builtin, external, init_v8, web};
+use crate::action_management::scan_actions;
+use crate::utils::{blue, gray, green, red};
+
+static EXTERNAL_REFS: OnceLock<&'static v8::ExternalReferences> = OnceLock::new();
+static SNAPSHOT: OnceLock<Vec<u8>> = OnceLock::new();
+static STALE: AtomicBool = AtomicBool::new(false);
+static BUILDING: AtomicBool = AtomicBool::new(false);
+static ENV_READ: AtomicBool = AtomicBool::new(false);
+
+thread_local! {
+    /// Files read while the snapshot is built, besides the action bundles
+    static INPUTS: RefCell<BTreeSet<PathBuf>> = RefCell::new(BTreeSet::new());
+}
+
+/// Native callbacks reachable from a snapshot. Passed to every isolate.
+pub fn external_references() -> &'static v8::ExternalReferences {
+    EXTERNAL_REFS.get_or_init(|| {
+        let mut refs = builtin::external_references();
+        refs.extend(web::external_references());
+        refs.extend(external::external_references());
+        Box::leak(Box::new(v8::ExternalReferences::new(&refs)))
+    })
+}
+
+/// The snapshot new isolates should boot from, if any.
+pub fn blob() -> Option<&'static [u8]> {
+    if STALE.load(Ordering::Relaxed) {
+        return None;
+    }
+    SNAPSHOT.get().map(|b| b.as_slice())
+}
+
+/// Actions changed on disk (hot reload): stop booting from the snapshot.
+pub fn invalidate() {
+    STALE.store(true, Ordering::Relaxed);
+}
+
+/// True in the `--build-snapshot` process while the context is bootstrapped.
+pub fn building() -> bool {
+    BUILDING.load(Ordering::Relaxed)
+}
+
+/// The environment was requested during the build; the snapshot is refused.
+pub fn env_read() {
+    ENV_READ.store(true, Ordering::Relaxed);
+}
+
+/// A file read during the build: its changes must invalidate the cache.
+pub fn record_input(path: &Path) {
+    if building() {
+        INPUTS.with(|i| i.borrow_mut().insert(path.to_path_buf()));
+    }
+}
+
+fn cache_path(root: &Path) -> PathBuf {
+    match std::env::var("TITAN_SNAPSHOT_PATH") {
+        Ok(p) if !p.is_empty() => PathBuf::from(p),
+        _ => root.join(".titan").join("snapshot.bin"),
+    }
+}
+
+/// Hash of everything baked into the snapshot that is known before building
+/// it. Files read during the build are checked separately (`read_cache`).
+fn fingerprint(root: &Path) -> u64 {
+    let mut hasher = std::collections::hash_map::DefaultHasher::new();
+
+    let mut files: Vec<PathBuf> = scan_actions(&root.to_path_buf()).into_values().collect();
+    if let Ok(exe) = std::env::current_exe() {
+        files.push(exe);
+    }
+    files.sort();
+    hash_files(&files, &mut hasher);
+
+    root.hash(&mut hasher);
+    external::fingerprint(&mut hasher);
+    hasher.finish()
+}
+
+fn hash_files<'a>(files: impl IntoIterator<Item = &'a PathBuf>, hasher: &mut impl Hasher) {
+    for path in files {
+        path.hash(hasher);
+        if let Ok(meta) = fs::metadata(path) {
+            meta.len().hash(hasher);
+            meta.modified().ok().hash(hasher);
+        }
+    }
+}
+
+fn inputs_hash(inputs: &[PathBuf]) -> u64 {
+    let mut hasher = std::collections::hash_map::DefaultHasher::new();
+    hash_files(inputs, &mut hasher);
+    hasher.finish()
+}
+
+/// Load or build the snapshot before the worker pool starts.
+pub fn prepare(root: &Path) {
+    let start = Instant::now();
+    let path = cache_path(root);
+    let key = fingerprint(root);
+
+    let blob = match read_cache(&path, key) {
+        Some(blob) => Some(blob),
+        None => {
+            let built = std::env::current_exe().and_then(|exe| {
+                std::process::Command::new(exe)
+                    .arg("--build-snapshot")
+                    .status()
+            });
+            match built {
+                Ok(status) if status.success() => read_cache(&path, key),
+                Ok(status) => {
+                    println!(
+                        "{} {} ({})",
+                        blue("[Titan]"),
+                        red("Startup snapshot build failed, booting from source"),
+                        status
+                    );
+                    None
+                }
+                Err(e) => {
+                    println!(
+                        "{} {} ({})",
+                        blue("[Titan]"),
+                        red("Startup snapshot build failed, booting from source"),
+                        e
+                    );
+                    None
+                }
+            }
+        }
+    };
+
+    if let Some(blob) = blob {
+        println!(
+            "{} {} {}",
+            blue("[Titan]"),
+            green("Startup snapshot ready"),
+            gray(&format!(
+                "({} KB in {:.2?})",
+                blob.len() / 1024,
+                start.elapsed()
+            ))
+        );
+        let _ = SNAPSHOT.set(blob);
+    }
+}
+
+/// `--build-snapshot`: bootstrap a context, serialize it and write the cache.
+pub fn build_command(root: &Path, stack_size: usize) -> Result<(), String> {
+    let owned_root = root.to_path_buf();
+    let (blob, inputs) = std::thread::Builder::new()
+        .name("titan-snapshot".into())
+        .stack_size(stack_size)
+        .spawn(move || create(&owned_root))
+        .map_err(|e| e.to_string())?
+        .join()
+        .map_err(|_| "snapshot builder panicked".to_string())??;
+
+    let path = cache_path(root);
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir).map_err(|e| format!("{}: {}", dir.display(), e))?;
+    }
+    // Layout: fingerprint, input file list (length-prefixed, one path per
+    // line), hash of the inputs, blob
+    let list = inputs
+        .iter()
+        .map(|p| p.to_string_lossy())
+        .collect::<Vec<_>>()
+        .join("\n");
+    let mut data = fingerprint(root).to_le_bytes().to_vec();
+    data.extend_from_slice(&(list.len() as u32).to_le_bytes());
+    data.extend_from_slice(list.as_bytes());
+    data.extend_from_slice(&inputs_hash(&inputs).to_le_bytes());
+    data.extend_from_slice(&blob);
+
+    // Write then rename, so a concurrent boot never reads a partial file
+    let tmp = path.with_extension("tmp");
+    fs::write(&tmp, &data).map_err(|e| format!("{}: {}", tmp.display(), e))?;
+    fs::rename(&tmp, &path).map_err(|e| format!("{}: {}", path.display(), e))?;
+    Ok(())
+}
+
+/// Serialized context plus the files read while bootstrapping it.
+fn create(root: &PathBuf) -> Result<(Vec<u8>, Vec<PathBuf>), String> {
+    init_v8();
+    BUILDING.store(true, Ordering::Relaxed);
+    let mut isolate = v8::Isolate::snapshot_creator(Some(external_references()), None);
+    {
+        let handle_scope = &mut v8::HandleScope::new(&mut isolate);
+        let context = v8::Context::new(handle_scope, v8::ContextOptions::default());
+        {
+            let scope = &mut v8::ContextScope::new(handle_scope, context);
+            // Action handles are looked up again after deserialization
+            drop(super::bootstrap(scope, root, 0));
+        }
+        handle_scope.set_default_context(context);
+    }
+    BUILDING.store(false, Ordering::Relaxed);
+    if ENV_READ.load(Ordering::Relaxed) {
+        return Err("t.env is read at load time; it is not baked into snapshots".into());
+    }
+    let blob = isolate
+        .create_blob(v8::FunctionCodeHandling::Keep)
+        .ok_or("V8 could not serialize the context")?;
+    let inputs = INPUTS.with(|i| i.take().into_iter().collect());
+    Ok((blob.to_vec(), inputs))
+}
+
+/// Cached blob, if present, built for this exact fingerprint and none of the
+/// files read while building it changed since.
+fn read_cache(path: &Path, key: u64) -> Option<Vec<u8>> {
+    let mut data = fs::read(path).ok()?;
+    if data.len() <= 12 || data[..8] != key.to_le_bytes() {
+        return None;
+    }
+    let list_len = u32::from_le_bytes(data[8..12].try_into().ok()?) as usize;
+    let list_end = 12 + list_len;
+    let blob_start = list_end + 8;
+    if data.len() <= blob_start {
+        return None;
+    }
+    let inputs: Vec<PathBuf> = std::str::from_utf8(&data[12..list_end])
+        .ok()?
+        .lines()
+        .map(PathBuf::from)
+        .collect();
+    if data[list_end..blob_start] != inputs_hash(&inputs).to_le_bytes() {
+        return None;
+    }
+    Some(data.split_off(blob_start))
+}
+
+/// Collect action functions from a context deserialized from the snapshot.
+pub fn restore_actions(
+    scope: &mut v8::HandleScope,
+    root: &PathBuf,
+) -> HashMap<String, v8::Global<v8::Function>> {
+    let context = scope.get_current_context();
+    let global = context.global(scope);
+
+    let mut map = HashMap::new();
+    for name in scan_actions(root).into_keys() {
+        let key = super::v8_str(scope, &name);
+        if let Some(func) = global
+            .get(scope, key.into())
+            .and_then(|v| v8::Local::<v8::Function>::try_from(v).ok())
+        {
+            map.insert(name, v8::Global::new(scope, func));
+        }
+    }
+    map
+}
@@ -0,0 +1,425 @@
+// Web-standard globals (natives in web.rs)
+// Evaluates to a function; web.rs calls it once with the native bindings and
+// whether this is the worker a DevTools session attaches to.
+(function (native, inspected) {
+    class DOMException extends Error {
+        constructor(message = "", name = "Error") {
+            super(message);
+            this.name = name;
+        }
+    }
+
+    function toBytes(input, what) {
+        if (input instanceof ArrayBuffer) return new Uint8Array(input);
+        if (ArrayBuffer.isView(input)) {
+            return new Uint8Array(input.buffer, input.byteOffset, input.byteLength);
+        }
+        throw new TypeError(`${what} requires an ArrayBuffer or ArrayBufferView`);
+    }
+
+    // --- TextEncoder / TextDecoder ---
+
+    class TextEncoder {
+        get encoding() {
+            return "utf-8";
+        }
+
+        encode(input = "") {
+            return native.encode(String(input));
+        }
+
+        encodeInto(source, destination) {
+            if (!(destination instanceof Uint8Array)) {
+                throw new TypeError("encodeInto() destination must be a Uint8Array");
+            }
+            const [read, written] = native.encodeInto(String(source), destination);
+            return { read, written };
+        }
+    }
+
+    const ENCODING_LABELS = {
+        "utf-8": ["unicode-1-1-utf-8", "unicode11utf8", "unicode20utf8", "utf-8", "utf8", "x-unicode20utf8"],
+        "utf-16le": ["csunicode", "iso-10646-ucs-2", "ucs-2", "unicode", "unicodefeff", "utf-16", "utf-16le"],
+        "windows-1252": [
+            "ansi_x3.4-1968", "ascii", "cp1252", "cp819", "csisolatin1", "ibm819", "iso-8859-1",
+            "iso-ir-100", "iso8859-1", "iso88591", "iso_8859-1", "iso_8859-1:1987", "l1", "latin1",
+            "us-ascii", "windows-1252", "x-cp1252"
+        ]
+    };
+    const ENCODINGS = new Map();
+    for (const [name, labels] of Object.entries(ENCODING_LABELS)) {
+        for (const label of labels) ENCODINGS.set(label, name);
+    }
+
+    class TextDecoder {
+        #encoding;
+        #fatal;
+        #ignoreBOM;
+        #pending = null;
+        #started = false;
+
+        constructor(label = "utf-8", options = {}) {
+            const encoding = ENCODINGS.get(String(label).trim().toLowerCase());
+            if (!encoding) {
+                throw new RangeError(`TextDecoder: unsupported encoding "${label}"`);
+            }
+            this.#encoding = encoding;
+            this.#fatal = !!(options && options.fatal);
+            this.#ignoreBOM = !!(options && options.ignoreBOM);
+        }
+
+        get encoding() {
+            return this.#encoding;
+        }
+
+        get fatal() {
+            return this.#fatal;
+        }
+
+        get ignoreBOM() {
+            return this.#ignoreBOM;
+        }
+
+        decode(input, options = {}) {
+            let bytes = input === undefined ? new Uint8Array(0) : toBytes(input, "TextDecoder.decode()");
+            if (this.#pending) {
+                const joined = new Uint8Array(this.#pending.length + bytes.length);
+                joined.set(this.#pending);
+                joined.set(bytes, this.#pending.length);
+                bytes = joined;
+                this.#pending = null;
+            }
+
+            const stream = !!(options && options.stream);
+            const stripBOM = !this.#ignoreBOM && !this.#started;
+            const result = native.decode(bytes, this.#encoding, this.#fatal, stripBOM, stream);
+            if (result === undefined) {
+                this.#started = false;
+                throw new TypeError(`TextDecoder: invalid ${this.#encoding} data`);
+            }
+
+            const [text, tail] = result;
+            if (tail) this.#pending = bytes.slice(bytes.length - tail);
+            // A stream's BOM is only looked for at its very start
+            this.#started = stream && (this.#started || bytes.length > tail);
+            return text;
+        }
+    }
+
+    // --- URL / URLSearchParams ---
+
+    const kList = Symbol("list");
+    const kUrl = Symbol("url");
+    const kReset = Symbol("reset");
+
+    class URLSearchParams {
+        constructor(init = "") {
+            this[kList] = [];
+            this[kUrl] = null;
+
+            if (typeof init === "object" && init !== null) {
+                if (typeof init[Symbol.iterator] === "function") {
+                    for (const pair of init) {
+                        const entry = [...pair];
+                        if (entry.length !== 2) {
+                            throw new TypeError("URLSearchParams: each pair must have exactly two items");
+                        }
+                        this[kList].push([String(entry[0]), String(entry[1])]);
+                    }
+                } else {
+                    for (const key of Object.keys(init)) {
+                        this[kList].push([key, String(init[key])]);
+                    }
+                }
+            } else {
+                this[kReset](String(init));
+            }
+        }
+
+        [kReset](query) {
+            this[kList] = native.formParse(query.startsWith("?") ? query.slice(1) : query);
+        }
+
+        #update() {
+            const url = this[kUrl];
+            if (url) url.search = this[kList].length ? this.toString() : "";
+        }
+
+        get size() {
+            return this[kList].length;
+        }
+
+        append(name, value) {
+            this[kList].push([String(name), String(value)]);
+            this.#update();
+        }
+
+        delete(name, value) {
+            name = String(name);
+            this[kList] = this[kList].filter(([k, v]) =>
+                k !== name || (value !== undefined && v !== String(value)));
+            this.#update();
+        }
+
+        get(name) {
+            const entry = this[kList].find(([k]) => k === String(name));
+            return entry ? entry[1] : null;
+        }
+
+        getAll(name) {
+            return this[kList].filter(([k]) => k === String(name)).map(([, v]) => v);
+        }
+
+        has(name, value) {
+            name = String(name);
+            return this[kList].some(([k, v]) =>
+                k === name && (value === undefined || v === String(value)));
+        }
+
+        set(name, value) {
+            name = String(name);
+            value = String(value);
+            const index = this[kList].findIndex(([k]) => k === name);
+            if (index === -1) {
+                this[kList].push([name, value]);
+            } else {
+                this[kList][index][1] = value;
+                this[kList] = this[kList].filter(([k], i) => k !== name || i === index);
+            }
+            this.#update();
+        }
+
+        sort() {
+            // Stable, by UTF-16 code units
+            this[kList].sort(([a], [b]) => (a < b ? -1 : a > b ? 1 : 0));
+            this.#update();
+        }
+
+        forEach(callback, thisArg) {
+            for (const [k, v] of this[kList]) callback.call(thisArg, v, k, this);
+        }
+
+        *entries() {
+            for (const [k, v] of this[kList]) yield [k, v];
+        }
+
+        *keys() {
+            for (const [k] of this[kList]) yield k;
+        }
+
+        *values() {
+            for (const [, v] of this[kList]) yield v;
+        }
+
+        [Symbol.iterator]() {
+            return this.entries();
+        }
+
+        toString() {
+            return native.formSerialize(this[kList]);
+        }
+    }
+
+    class URL {
+        #parts;
+        #searchParams = null;
+
+        constructor(url, base) {
+            const parts = native.urlParse(String(url), base === undefined ? undefined : String(base));
+            if (!parts) throw new TypeError(`Invalid URL: ${url}`);
+            this.#parts = parts;
+        }
+
+        static canParse(url, base) {
+            return !!native.urlParse(String(url), base === undefined ? undefined : String(base));
+        }
+
+        static parse(url, base) {
+            try {
+                return new URL(url, base);
+            } catch {
+                return null;
+            }
+        }
+
+        #set(component, value) {
+            this.#parts = native.urlSet(this.#parts.href, component, String(value)) || this.#parts;
+            if (this.#searchParams && component === "search") {
+                this.#searchParams[kReset](this.#parts.search);
+            }
+        }
+
+        get href() { return this.#parts.href; }
+        set href(value) {
+            const parts = native.urlParse(String(value));
+            if (!parts) throw new TypeError(`Invalid URL: ${value}`);
+            this.#parts = parts;
+            if (this.#searchParams) this.#searchParams[kReset](parts.search);
+        }
+
+        get origin() { return this.#parts.origin; }
+        get protocol() { return this.#parts.protocol; }
+        set protocol(value) { this.#set("protocol", value); }
+        get username() { return this.#parts.username; }
+        set username(value) { this.#set("username", value); }
+        get password() { return this.#parts.password; }
+        set password(value) { this.#set("password", value); }
+        get host() { return this.#parts.host; }
+        set host(value) { this.#set("host", value); }
+        get hostname() { return this.#parts.hostname; }
+        set hostname(value) { this.#set("hostname", value); }
+        get port() { return this.#parts.port; }
+        set port(value) { this.#set("port", value); }
+        get pathname() { return this.#parts.pathname; }
+        set pathname(value) { this.#set("pathname", value); }
+        get search() { return this.#parts.search; }
+        set search(value) { this.#set("search", value); }
+        get hash() { return this.#parts.hash; }
+        set hash(value) { this.#set("hash", value); }
+
+        get searchParams() {
+            if (!this.#searchParams) {
+                this.#searchParams = new URLSearchParams(this.#parts.search);
+                this.#searchParams[kUrl] = this;
+            }
+            return this.#searchParams;
+        }
+
+        toString() {
+            return this.href;
+        }
+
+        toJSON() {
+            return this.href;
+        }
+    }
+
+    // --- atob / btoa / structuredClone ---
+
+    function atob(data) {
+        if (arguments.length === 0) throw new TypeError("atob() requires 1 argument");
+        const result = native.atob(String(data));
+        if (result === undefined) {
+            throw new DOMException("The string to be decoded is not correctly encoded.", "InvalidCharacterError");
+        }
+        return result;
+    }
+
+    function btoa(data) {
+        if (arguments.length === 0) throw new TypeError("btoa() requires 1 argument");
+        const result = native.btoa(String(data));
+        if (result === undefined) {
+            throw new DOMException("The string to be encoded contains characters outside of the Latin1 range.", "InvalidCharacterError");
+        }
+        return result;
+    }
+
+    function structuredClone(value) {
+        try {
+            return native.structuredClone(value);
+        } catch (e) {
+            throw new DOMException(e.message, "DataCloneError");
+        }
+    }
+
+    // --- crypto ---
+
+    const webCrypto = globalThis.crypto || {};
+    webCrypto.getRandomValues = function getRandomValues(array) {
+        const integer = ArrayBuffer.isView(array) && !(array instanceof DataView) &&
+            !(array instanceof Float32Array) && !(array instanceof Float64Array);
+        if (!integer) {
+            throw new DOMException("getRandomValues() requires an integer typed array", "TypeMismatchError");
+        }
+        if (array.byteLength > 65536) {
+            throw new DOMException("getRandomValues() is limited to 65536 bytes", "QuotaExceededError");
+        }
+        native.randomFill(array);
+        return array;
+    };
+    webCrypto.randomUUID = function randomUUID() {
+        return native.randomUUID();
+    };
+
+    // --- console ---
+
+    const counts = new Map();
+    const timers = new Map();
+    const console = {
+        log: (...args) => native.console("log", ...args),
+        info: (...args) => native.console("info", ...args),
+        debug: (...args) => native.console("debug", ...args),
+        warn: (...args) => native.console("warn", ...args),
+        error: (...args) => native.console("error", ...args),
+        trace: (...args) => {
+            const stack = (new Error().stack || "").split("\n").slice(2).join("\n");
+            native.console("trace", ...args, "\n" + stack);
+        },
+        dir: (value) => native.console("log", value),
+        table: (data) => native.console("log", data),
+        assert: (condition, ...args) => {
+            if (!condition) native.console("error", "Assertion failed" + (args.length ? ":" : ""), ...args);
+        },
+        count: (label = "default") => {
+            const n = (counts.get(label) || 0) + 1;
+            counts.set(label, n);
+            native.console("log", `${label}: ${n}`);
+        },
+        countReset: (label = "default") => counts.delete(label),
+        time: (label = "default") => timers.set(label, native.now()),
+        timeLog: (label = "default", ...args) => timeLog(label, ...args),
+        timeEnd: (label = "default") => {
+            timeLog(label);
+            timers.delete(label);
+        },
+        group: (...args) => args.length && native.console("log", ...args),
+        groupCollapsed: (...args) => args.length && native.console("log", ...args),
+        groupEnd: () => {},
+    };
+
+    function timeLog(label, ...args) {
+        if (!timers.has(label)) return native.console("warn", `Timer '${label}' does not exist`);
+        native.console("log", `${label}: ${(native.now() - timers.get(label)).toFixed(3)}ms`, ...args);
+    }
+
+    // V8's own console reports to an attached DevTools session: on the
+    // inspected worker, forward every call to it as well
+    const inspectorConsole = inspected ? globalThis.console : undefined;
+    if (inspectorConsole) {
+        for (const [name, method] of Object.entries(console)) {
+            const forward = inspectorConsole[name];
+            if (typeof forward !== "function") continue;
+            console[name] = (...args) => {
+                forward.apply(inspectorConsole, args);
+                return method(...args);
+            };
+        }
+    }
+
+    // --- performance ---
+
+    const performance = {
+        now: () => native.now(),
+        // Read at call time, never baked into the startup snapshot
+        get timeOrigin() {
+            return native.timeOrigin();
+        },
+        toJSON() {
+            return { timeOrigin: this.timeOrigin };
+        },
+    };
+
+    Object.assign(globalThis, {
+        DOMException,
+        TextEncoder,
+        TextDecoder,
+        URL,
+        URLSearchParams,
+        atob,
+        btoa,
+        structuredClone,
+        console,
+        performance,
+    });
+    globalThis.crypto = webCrypto;
+})
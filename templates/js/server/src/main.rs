@@ -7,31 +7,39 @@
 //! 4. Early fast-path check BEFORE body/header parsing.
 //! 5. Mimalloc global allocator for faster allocations.
 //! 6. Optimized response construction.
+//! 7. Dev-mode debugging: `TITAN_INSPECT=9229` exposes a worker isolate to
+//!    Chrome DevTools.
 
 use anyhow::Result;
 use axum::{
     Router,
-    body::{Body, to_bytes},
+    body::{Body, Bytes, to_bytes},
     extract::State,
-    http::{Request, StatusCode},
-    response::{IntoResponse, Json},
+    http::{HeaderName, HeaderValue, Request, StatusCode, header},
+    response::{IntoResponse, Json, Response},
     routing::any,
 };
 use serde_json::Value;
 use smallvec::SmallVec;
-use std::time::Instant;
+use std::time::{Duration, Instant};
 use std::{collections::HashMap, fs, path::PathBuf, sync::Arc};
 use tokio::net::TcpListener;
 
 mod action_management;
+mod admin;
+mod cron_jobs;
+mod error_pages;
 mod extensions;
 mod fast_path;
+mod jobs;
+mod reload;
 mod runtime;
 mod utils;
 
-use action_management::{DynamicRoute, RouteVal, match_dynamic_route};
-use fast_path::{FastPathRegistry, PrecomputedRoute};
-use runtime::RuntimeManager;
+use action_management::{allowed_methods, match_dynamic_route};
+use error_pages::{ErrorContext, ErrorTarget, correlation_id, problem_response, problem_status};
+use reload::{RouteStore, RouteTable};
+use runtime::{ExecuteError, Isolation, PoolConfig, RuntimeManager, Scheduler, WorkerResult};
 use utils::{blue, gray, green, red, white, yellow};
 
 /// Global allocator: mimalloc for ~5-15% better allocation throughput.
@@ -40,15 +48,18 @@ static GLOBAL: mimalloc::MiMalloc = mimalloc::MiMalloc;
 
 #[derive(Clone)]
 struct AppState {
-    routes: Arc<HashMap<String, RouteVal>>,
-    dynamic_routes: Arc<Vec<DynamicRoute>>,
+    /// Routes, fast paths and pre-computed replies (hot-swappable)
+    routes: Arc<RouteStore>,
     runtime: Arc<RuntimeManager>,
-    /// Pre-computed responses for static actions (bypass V8)
-    fast_paths: Arc<FastPathRegistry>,
-    /// Pre-serialized responses for reply routes (no re-serialization per request)
-    precomputed: Arc<HashMap<String, PrecomputedRoute>>,
     /// When true: disable per-request logging and timings injection
     production_mode: bool,
+    /// `__config.request_timeout_ms`: deadline for the whole request (→ 504).
+    /// A script still running then is terminated and the request released.
+    request_timeout: Option<Duration>,
+    /// `__cron` jobs and their last-run status
+    cron: Arc<cron_jobs::CronScheduler>,
+    /// `t.jobs.enqueue` queue and dead-letter list
+    jobs: Arc<jobs::JobQueue>,
 }
 
 async fn root_route(state: State<AppState>, req: Request<Body>) -> impl IntoResponse {
@@ -65,6 +76,10 @@ async fn handler(State(state): State<AppState>, req: Request<Body>) -> impl Into
     let path = req.uri().path().to_string();
     let strict_key = format!("{}:{}", method, path);
 
+    // Snapshot the route table once: a hot reload mid-request must not
+    // change which version this request is served from.
+    let table = state.routes.current();
+
     // Phase 1: Fast-Path Check (before ANY body/header parsing)
     // This is the critical optimization. For static actions and reply routes,
     // we return pre-computed bytes without touching the request body, headers,
@@ -73,16 +88,16 @@ async fn handler(State(state): State<AppState>, req: Request<Body>) -> impl Into
     let start = Instant::now();
     let log_enabled = !state.production_mode;
 
-    if let Some(route) = state
+    if let Some(route) = table
         .routes
         .get(&strict_key)
-        .or_else(|| state.routes.get(&path))
+        .or_else(|| table.routes.get(&path))
     {
         match route.r#type.as_str() {
 
             // Precomputed reply routes
             "json" | "text" => {
-                if let Some(precomputed) = state.precomputed.get(&strict_key) {
+                if let Some(precomputed) = table.precomputed.get(&strict_key) {
 
                     if state.production_mode {
                         // Benchmark mode → zero overhead
@@ -126,7 +141,7 @@ async fn handler(State(state): State<AppState>, req: Request<Body>) -> impl Into
             "action" => {
                 let action_name = route.value.as_str().unwrap_or("");
 
-                if let Some(static_resp) = state.fast_paths.get(action_name) {
+                if let Some(static_resp) = table.fast_paths.get(action_name) {
 
                     if state.production_mode {
                         // Benchmark mode → zero overhead
@@ -209,11 +224,20 @@ async fn handler(State(state): State<AppState>, req: Request<Body>) -> impl Into
 
     // Headers & Body
     let (parts, body) = req.into_parts();
-    let headers_map: HashMap<String, String> = parts
-        .headers
-        .iter()
-        .map(|(k, v)| (k.to_string(), v.to_str().unwrap_or("").to_string()))
-        .collect();
+    // Repeated headers are folded into one value instead of last-one-wins:
+    // `Cookie` with "; " (RFC 6265), everything else with ", " (RFC 9110).
+    let mut headers_vec: SmallVec<[(String, String); 8]> = SmallVec::new();
+    for name in parts.headers.keys() {
+        let sep = if name == header::COOKIE { "; " } else { ", " };
+        let joined = parts
+            .headers
+            .get_all(name)
+            .iter()
+            .map(|v| v.to_str().unwrap_or(""))
+            .collect::<Vec<_>>()
+            .join(sep);
+        headers_vec.push((name.to_string(), joined));
+    }
 
     let body_bytes = match to_bytes(body, usize::MAX).await {
         Ok(b) => b,
@@ -223,20 +247,22 @@ async fn handler(State(state): State<AppState>, req: Request<Body>) -> impl Into
     // Route resolution
     let mut params: HashMap<String, String> = HashMap::new();
     let mut action_name: Option<String> = None;
+    let mut isolation: Option<&str> = None;
     let mut route_kind = "none";
     let mut route_label = String::from("not_found");
 
     // Exact route lookup (may find action routes not caught in fast-path phase)
-    let route = state
+    let route = table
         .routes
         .get(&strict_key)
-        .or_else(|| state.routes.get(&path));
+        .or_else(|| table.routes.get(&path));
     if let Some(route) = route {
         route_kind = "exact";
         if route.r#type == "action" {
             let name = route.value.as_str().unwrap_or("unknown").to_string();
             route_label = name.clone();
             action_name = Some(name);
+            isolation = route.isolation.as_deref();
         } else if route.r#type == "json" {
             // This path shouldn't be reached (handled in Phase 1), but keep as safety
             if log_enabled {
@@ -265,37 +291,51 @@ async fn handler(State(state): State<AppState>, req: Request<Body>) -> impl Into
 
     // Dynamic route matching
     if action_name.is_none() {
-        if let Some((action, p)) =
-            match_dynamic_route(&method, &path, state.dynamic_routes.as_slice())
+        if let Some((route, p)) =
+            match_dynamic_route(&method, &path, table.dynamic_routes.as_slice())
         {
             route_kind = "dynamic";
-            route_label = action.clone();
-            action_name = Some(action);
+            route_label = route.action.clone();
+            action_name = Some(route.action.clone());
+            isolation = route.isolation.as_deref();
             params = p;
         }
     }
 
+    let query_vec: SmallVec<[(String, String); 4]> = query_map.into_iter().collect();
+    let request_id = headers_vec
+        .iter()
+        .find(|(k, _)| k == "x-request-id")
+        .map(|(_, v)| v.clone());
+
     let action_name = match action_name {
         Some(a) => a,
         None => {
+            let allow = allowed_methods(&path, &table.routes, &table.dynamic_routes);
+            let (status, message) = if allow.is_empty() {
+                (404, "Not Found")
+            } else {
+                (405, "Method Not Allowed")
+            };
             if log_enabled {
                 println!(
                     "{} {} {} {}",
                     blue("[Titan]"),
                     white(&format!("{} {}", method, path)),
-                    white("→ 404"),
+                    white(&format!("→ {}", status)),
                     gray(&format!("in {:.2?}", start.elapsed()))
                 );
             }
-            return (StatusCode::NOT_FOUND, "Not Found").into_response();
+            let mut ctx = ErrorContext::new(status, message, correlation_id(request_id.as_deref()));
+            ctx.allow = allow;
+            return error_response(&state, &table, ctx, &method, &path, headers_vec, query_vec)
+                .await;
         }
     };
 
     // Phase 3: V8 Execution (dispatch to worker pool)
 
-    let headers_vec: SmallVec<[(String, String); 8]> = headers_map.into_iter().collect();
     let params_vec: SmallVec<[(String, String); 4]> = params.into_iter().collect();
-    let query_vec: SmallVec<[(String, String); 4]> = query_map.into_iter().collect();
 
     let body_arg = if !body_bytes.is_empty() {
         Some(body_bytes)
@@ -303,19 +343,73 @@ async fn handler(State(state): State<AppState>, req: Request<Body>) -> impl Into
         None
     };
 
-    let (result_json, timings) = state
-        .runtime
-        .execute(
-            action_name.clone(),
-            method.clone(),
-            path.clone(),
-            body_arg,
-            headers_vec,
-            params_vec,
-            query_vec,
-        )
-        .await
-        .unwrap_or_else(|e| (serde_json::json!({"error": e}), vec![]));
+    let isolation = isolation
+        .and_then(Isolation::parse)
+        .unwrap_or(table.isolation);
+
+    // Error actions need the request again; only pay for the clone if configured
+    let error_parts = (!table.errors.is_empty()).then(|| (headers_vec.clone(), query_vec.clone()));
+
+    let exec = state.runtime.execute(
+        action_name.clone(),
+        method.clone(),
+        path.clone(),
+        body_arg,
+        headers_vec,
+        params_vec,
+        query_vec,
+        None,
+        isolation,
+    );
+    let outcome = match state.request_timeout {
+        Some(limit) => tokio::time::timeout(limit, exec).await.ok(),
+        None => Some(exec.await),
+    };
+
+    let WorkerResult {
+        json: result_json,
+        timings,
+        body: binary_body,
+        queue_ms,
+    } = match outcome {
+        Some(Ok(res)) => res,
+        Some(Err(ExecuteError::Overloaded { retry_after })) => {
+            if log_enabled {
+                println!(
+                    "{} {} {} {}",
+                    blue("[Titan]"),
+                    red(&format!("{} {}", method, path)),
+                    red("→ 503 overloaded"),
+                    gray(&format!("in {:.2?}", start.elapsed()))
+                );
+            }
+            return (
+                StatusCode::SERVICE_UNAVAILABLE,
+                [(header::RETRY_AFTER, retry_after.to_string())],
+                "Service Unavailable",
+            )
+                .into_response();
+        }
+        Some(Err(ExecuteError::Failed(e))) => {
+            WorkerResult::json(serde_json::json!({"error": e}), vec![])
+        }
+        None => {
+            let cid = correlation_id(request_id.as_deref());
+            if log_enabled {
+                println!(
+                    "{} {} {} {}",
+                    blue("[Titan]"),
+                    red(&format!("{} {}", method, path)),
+                    red("→ 504 timeout"),
+                    gray(&format!("in {:.2?} [{}]", start.elapsed(), cid))
+                );
+            }
+            let mut ctx = ErrorContext::new(504, "Action timed out", cid);
+            ctx.action = Some(action_name);
+            let (headers, query) = error_parts.unwrap_or_default();
+            return error_response(&state, &table, ctx, &method, &path, headers, query).await;
+        }
+    };
 
     // Phase 4: Response Construction
 
@@ -324,8 +418,41 @@ async fn handler(State(state): State<AppState>, req: Request<Body>) -> impl Into
     // {"message":"Hello, World!"} which fails TechEmpower validation).
     // Timing info is available via the Server-Timing HTTP header instead.
 
+    // Thrown t.HttpError → problem+json with the error's own status. For 5xx
+    // in production the detail is redacted from the body and only logged.
+    if let Some(problem) = result_json.get("__httpError") {
+        let status = problem_status(problem);
+        let redact = state.production_mode && status.is_server_error();
+        let response = problem_response(problem, &path, redact);
+        if log_enabled {
+            let paint = if status.is_server_error() {
+                red
+            } else {
+                yellow
+            };
+            println!(
+                "{} {} {} {}",
+                blue("[Titan]"),
+                paint(&format!("{} {}", method, path)),
+                paint(&format!("→ {}", status.as_u16())),
+                gray(&format!("in {:.2?}", start.elapsed()))
+            );
+            if status.is_server_error() {
+                println!(
+                    "{} {} {}",
+                    blue("[Titan]"),
+                    red("Action Error:"),
+                    red(problem["detail"].as_str().unwrap_or("Unknown"))
+                );
+            }
+        }
+        return response;
+    }
+
     // Error handling
     if let Some(err) = result_json.get("error") {
+        let message = err.as_str().unwrap_or("Unknown").to_string();
+        let cid = correlation_id(request_id.as_deref());
         if log_enabled {
             let prefix = if !timings.is_empty() {
                 format!("{} {}", blue("[Titan"), blue("Drift]"))
@@ -340,73 +467,35 @@ async fn handler(State(state): State<AppState>, req: Request<Body>) -> impl Into
                 gray(&format!("in {:.2?}", start.elapsed()))
             );
             println!(
-                "{} {} {}",
+                "{} {} {} {}",
                 prefix,
                 red("Action Error:"),
-                red(err.as_str().unwrap_or("Unknown"))
+                red(&message),
+                gray(&format!("[{}]", cid))
             );
+            if let Some(stack) = result_json["stack"].as_str() {
+                println!("{}", gray(stack));
+            }
         }
-        let response = (StatusCode::INTERNAL_SERVER_ERROR, Json(result_json)).into_response();
-        return response;
+        let mut ctx = ErrorContext::new(500, message, cid);
+        ctx.action = Some(action_name);
+        ctx.stack = result_json["stack"].as_str().map(str::to_string);
+        let (headers, query) = error_parts.unwrap_or_default();
+        return error_response(&state, &table, ctx, &method, &path, headers, query).await;
     }
 
     // Response object construction
-    let mut response = if let Some(is_resp) = result_json.get("_isResponse") {
-        if is_resp.as_bool().unwrap_or(false) {
-            let status_u16 = result_json
-                .get("status")
-                .and_then(|v| v.as_u64())
-                .unwrap_or(200) as u16;
-            let status = StatusCode::from_u16(status_u16).unwrap_or(StatusCode::OK);
-            let mut builder = axum::http::Response::builder().status(status);
-
-            if let Some(hmap) = result_json.get("headers").and_then(|v| v.as_object()) {
-                for (k, v) in hmap {
-                    if let Some(vs) = v.as_str() {
-                        builder = builder.header(k, vs);
-                    }
-                }
-            }
-
-            let mut is_redirect = false;
-            if let Some(location) = result_json.get("redirect") {
-                if let Some(url) = location.as_str() {
-                    let mut final_status_u16 = status.as_u16();
-                    if !(300..400).contains(&final_status_u16) {
-                        final_status_u16 = 302;
-                    }
-                    builder = builder
-                        .status(StatusCode::from_u16(final_status_u16).unwrap_or(StatusCode::FOUND))
-                        .header("Location", url);
-                    is_redirect = true;
-                }
+    let mut response = action_response(result_json, binary_body, StatusCode::OK);
+
+    // Server-Timing header: drift timings only outside benchmark mode; queue
+    // wait always in dev, and in production once it is noticeable (>= 1ms)
+    if !state.production_mode || queue_ms >= 1.0 {
+        let mut server_timing = format!("queue;dur={:.2}", queue_ms);
+        if !state.production_mode {
+            for (i, (name, duration)) in timings.iter().enumerate() {
+                server_timing.push_str(&format!(", {}_{};dur={:.2}", name, i, duration));
             }
-
-            let body_text = if is_redirect {
-                "".to_string()
-            } else {
-                match result_json.get("body") {
-                    Some(Value::String(s)) => s.clone(),
-                    Some(v) => v.to_string(),
-                    None => "".to_string(),
-                }
-            };
-            builder.body(Body::from(body_text)).unwrap()
-        } else {
-            Json(result_json).into_response()
         }
-    } else {
-        Json(result_json).into_response()
-    };
-
-    // Server-Timing header (only outside benchmark mode)
-    if !state.production_mode && !timings.is_empty() {
-        let server_timing = timings
-            .iter()
-            .enumerate()
-            .map(|(i, (name, duration))| format!("{}_{};dur={:.2}", name, i, duration))
-            .collect::<Vec<_>>()
-            .join(", ");
         response
             .headers_mut()
             .insert("Server-Timing", server_timing.parse().unwrap());
@@ -462,6 +551,181 @@ async fn handler(State(state): State<AppState>, req: Request<Body>) -> impl Into
     response
 }
 
+/// Build the HTTP response for an action result: `_isResponse` objects
+/// (status, headers, redirect, text or binary body) or plain JSON.
+fn action_response(
+    result_json: Value,
+    binary_body: Option<Bytes>,
+    default_status: StatusCode,
+) -> Response {
+    let is_response = result_json
+        .get("_isResponse")
+        .and_then(|v| v.as_bool())
+        .unwrap_or(false);
+    if !is_response {
+        return (default_status, Json(result_json)).into_response();
+    }
+
+    let status_u16 = result_json
+        .get("status")
+        .and_then(|v| v.as_u64())
+        .unwrap_or(200) as u16;
+    let status = StatusCode::from_u16(status_u16).unwrap_or(StatusCode::OK);
+    let mut builder = axum::http::Response::builder().status(status);
+
+    if let Some(hmap) = result_json.get("headers").and_then(|v| v.as_object()) {
+        for (k, v) in hmap {
+            // Array values become repeated headers (e.g. several Set-Cookie)
+            let values: SmallVec<[&str; 1]> = match v {
+                Value::String(s) => smallvec::smallvec![s.as_str()],
+                Value::Array(items) => items.iter().filter_map(|i| i.as_str()).collect(),
+                _ => continue,
+            };
+            let Ok(name) = HeaderName::try_from(k.as_str()) else {
+                continue;
+            };
+            for vs in values {
+                if let Ok(value) = HeaderValue::from_str(vs) {
+                    builder = builder.header(&name, value);
+                }
+            }
+        }
+    }
+
+    let mut is_redirect = false;
+    if let Some(location) = result_json.get("redirect") {
+        if let Some(url) = location.as_str() {
+            let mut final_status_u16 = status.as_u16();
+            if !(300..400).contains(&final_status_u16) {
+                final_status_u16 = 302;
+            }
+            builder = builder
+                .status(StatusCode::from_u16(final_status_u16).unwrap_or(StatusCode::FOUND))
+                .header("Location", url);
+            is_redirect = true;
+        }
+    }
+
+    let body = if is_redirect {
+        Body::empty()
+    } else if let Some(bytes) = binary_body {
+        // Binary body: copied out of V8 when the action returned
+        Body::from(bytes)
+    } else {
+        match result_json.get("body") {
+            Some(Value::String(s)) => Body::from(s.clone()),
+            Some(v) => Body::from(v.to_string()),
+            None => Body::empty(),
+        }
+    };
+    builder.body(body).unwrap()
+}
+
+/// Serve a 404 / 405 / 500 / 504 through the configured error action or
+/// template (`__config.errors`), falling back to the built-in response.
+async fn error_response(
+    state: &AppState,
+    table: &RouteTable,
+    ctx: ErrorContext,
+    method: &str,
+    path: &str,
+    headers: SmallVec<[(String, String); 8]>,
+    query: SmallVec<[(String, String); 4]>,
+) -> Response {
+    let status = StatusCode::from_u16(ctx.status).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR);
+
+    let mut response = match table.errors.get(ctx.status) {
+        // Never route an action's failure back into itself
+        Some(ErrorTarget::Action(name)) if ctx.action.as_deref() != Some(name.as_str()) => {
+            let extra = serde_json::json!({ "error": ctx.to_json() });
+            let res = state
+                .runtime
+                .execute(
+                    name.clone(),
+                    method.to_string(),
+                    path.to_string(),
+                    None,
+                    headers,
+                    SmallVec::new(),
+                    query,
+                    Some(extra),
+                    table.isolation_for(name),
+                )
+                .await;
+            match res {
+                Ok(res) if res.json.get("error").is_none() => {
+                    let mut resp = action_response(res.json, res.body, status);
+                    // An un-statused t.response.* from an error action keeps the error status
+                    if resp.status() == StatusCode::OK {
+                        *resp.status_mut() = status;
+                    }
+                    resp
+                }
+                Ok(res) => {
+                    println!(
+                        "{} {} {}",
+                        blue("[Titan]"),
+                        red(&format!("Error handler '{}' failed:", name)),
+                        red(res.json["error"].as_str().unwrap_or("Unknown"))
+                    );
+                    default_error_response(&ctx, state.production_mode)
+                }
+                Err(e) => {
+                    println!(
+                        "{} {} {}",
+                        blue("[Titan]"),
+                        red(&format!("Error handler '{}' failed:", name)),
+                        red(&e.to_string())
+                    );
+                    default_error_response(&ctx, state.production_mode)
+                }
+            }
+        }
+        Some(ErrorTarget::Template { body, content_type }) => axum::http::Response::builder()
+            .status(status)
+            .header("content-type", *content_type)
+            .body(Body::from(ctx.render(body)))
+            .unwrap(),
+        _ => default_error_response(&ctx, state.production_mode),
+    };
+
+    if let Ok(v) = HeaderValue::from_str(&ctx.correlation_id) {
+        response.headers_mut().insert("x-correlation-id", v);
+    }
+    if !ctx.allow.is_empty() {
+        if let Ok(v) = HeaderValue::from_str(&ctx.allow.join(", ")) {
+            response.headers_mut().insert(header::ALLOW, v);
+        }
+    }
+    response
+}
+
+/// Built-in error bodies. 5xx messages are redacted in production; the raw
+/// message only goes to the log, tied to the response by its correlation ID.
+fn default_error_response(ctx: &ErrorContext, production_mode: bool) -> Response {
+    let status = StatusCode::from_u16(ctx.status).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR);
+    match ctx.status {
+        404 => (status, "Not Found").into_response(),
+        405 => (status, "Method Not Allowed").into_response(),
+        _ => {
+            let message = if production_mode {
+                status.canonical_reason().unwrap_or("Internal Server Error")
+            } else {
+                ctx.message.as_str()
+            };
+            let mut body = serde_json::json!({
+                "error": message,
+                "correlationId": ctx.correlation_id,
+            });
+            // Source-mapped stack trace, for development only
+            if let (false, Some(stack)) = (production_mode, &ctx.stack) {
+                body["stack"] = Value::from(stack.as_str());
+            }
+            (status, Json(body)).into_response()
+        }
+    }
+}
+
 #[tokio::main]
 async fn main() -> Result<()> {
     dotenvy::dotenv().ok();
@@ -469,7 +733,8 @@ async fn main() -> Result<()> {
     // Configuration
     let production_mode = std::env::var("TITAN_DEV").unwrap_or_default() != "1";
 
-    let raw = fs::read_to_string("./routes.json").unwrap_or_else(|_| "{}".to_string());
+    let routes_path = PathBuf::from("./routes.json");
+    let raw = fs::read_to_string(&routes_path).unwrap_or_else(|_| "{}".to_string());
     let json: Value = serde_json::from_str(&raw).unwrap_or_default();
 
     let port = std::env::var("PORT")
@@ -479,42 +744,38 @@ async fn main() -> Result<()> {
         .unwrap_or(3000);
 
     let thread_count = json["__config"]["threads"].as_u64();
-    let routes_json = json["routes"].clone();
-    let map: HashMap<String, RouteVal> = serde_json::from_value(routes_json).unwrap_or_default();
-    let dynamic_routes: Vec<DynamicRoute> =
-        serde_json::from_value(json["__dynamic_routes"].clone()).unwrap_or_default();
+
+    // Per-request deadline (0 disables)
+    let request_timeout_ms = json["__config"]["request_timeout_ms"].as_u64();
+    let request_timeout = Some(request_timeout_ms.unwrap_or(60_000))
+        .filter(|ms| *ms > 0)
+        .map(Duration::from_millis);
 
     let project_root = resolve_project_root();
 
     // Load extensions
     extensions::load_project_extensions(project_root.clone());
 
-    // Build pre-computed route responses
-    let mut precomputed = HashMap::new();
-    for (key, route) in &map {
-        match route.r#type.as_str() {
-            "json" => {
-                precomputed.insert(key.clone(), PrecomputedRoute::from_json(&route.value));
-            }
-            "text" => {
-                if let Some(s) = route.value.as_str() {
-                    precomputed.insert(key.clone(), PrecomputedRoute::from_text(s));
-                }
-            }
-            _ => {}
-        }
-    }
-    if !precomputed.is_empty() {
-        println!(
-            "{} {} reply route(s) pre-computed",
-            blue("[Titan]"),
-            precomputed.len()
-        );
+    // `--build-snapshot`: write the V8 startup snapshot and exit (build step,
+    // or spawned by `snapshot::prepare` on first boot)
+    if std::env::args().any(|a| a == "--build-snapshot") {
+        let stack_mb = json["__config"]["stack_mb"].as_u64().unwrap_or(8);
+        extensions::snapshot::build_command(&project_root, (stack_mb as usize) * 1024 * 1024)
+            .map_err(anyhow::Error::msg)?;
+        return Ok(());
     }
 
-    // Build fast-path registry (scan action files for static patterns)
+    extensions::source_maps::load(&project_root);
+
+    // Build route table (pre-computed replies + fast-path registry)
     let actions_dir = find_actions_dir(&project_root);
-    let fast_paths = FastPathRegistry::build(&actions_dir);
+    let table = RouteTable::from_json(&json, &actions_dir, &project_root);
+    let route_store = Arc::new(RouteStore::new(
+        table,
+        routes_path,
+        actions_dir,
+        project_root.clone(),
+    ));
 
     // Initialize Runtime Manager (V8 Worker Pool)
     let threads = match thread_count {
@@ -526,28 +787,111 @@ async fn main() -> Result<()> {
         }
     };
 
+    // Autoscaling bounds: `threads` unless `workers_min` / `workers_max` say
+    // otherwise (min == max is a fixed pool)
+    let workers_max = json["__config"]["workers_max"]
+        .as_u64()
+        .filter(|n| *n > 0)
+        .map_or(threads, |n| n as usize);
+    let workers_min = json["__config"]["workers_min"]
+        .as_u64()
+        .filter(|n| *n > 0)
+        .map_or(threads, |n| n as usize)
+        .min(workers_max);
+    let pool = PoolConfig {
+        min: workers_min,
+        max: workers_max,
+        pin_cores: json["__config"]["pin_workers"].as_bool() == Some(true),
+    };
+
     let stack_mb = json["__config"]["stack_mb"].as_u64().unwrap_or(8);
     let stack_size = (stack_mb as usize) * 1024 * 1024;
 
+    // Per-isolate heap cap; an isolate that hits it is recycled, not the process
+    let heap_limit = json["__config"]["heap_mb"]
+        .as_u64()
+        .filter(|mb| *mb > 0)
+        .map(|mb| (mb as usize) * 1024 * 1024);
+
+    // Admission control: max requests waiting once every worker queue is full,
+    // and how long each may wait before being shed with 503
+    let max_queue = json["__config"]["max_queue"].as_u64().unwrap_or(1024) as usize;
+    let max_queue_wait =
+        Duration::from_millis(json["__config"]["max_queue_wait_ms"].as_u64().unwrap_or(5000));
+
+    let scheduler = Scheduler::from_config(json["__config"]["scheduler"].as_str());
+
+    // Default per-op drift timeout (`drift(op, { timeout })` overrides; 0 disables)
+    let drift_timeout = Some(json["__config"]["drift_timeout_ms"].as_u64().unwrap_or(30_000))
+        .filter(|ms| *ms > 0)
+        .map(Duration::from_millis);
+
+    // Default max ops in flight per `drift([...])` batch (unset or 0 = no limit)
+    let drift_concurrency = json["__config"]["drift_concurrency"]
+        .as_u64()
+        .filter(|n| *n > 0)
+        .map(|n| n as usize);
+
+    // DevTools inspector for one worker (dev mode, `TITAN_INSPECT`)
+    let inspect = extensions::inspector::configure(production_mode, pool.min);
+
+    // Workers boot from a snapshot of the bootstrapped context
+    if json["__config"]["snapshot"].as_bool() != Some(false) {
+        extensions::snapshot::prepare(&project_root);
+    }
+
     let runtime_manager = Arc::new(RuntimeManager::new(
         project_root.clone(),
-        threads,
+        pool,
         stack_size,
+        heap_limit,
+        max_queue,
+        max_queue_wait,
+        scheduler,
+        drift_timeout,
+        drift_concurrency,
     ));
 
+    // `t.call` dispatches nested actions into this pool
+    runtime::install(&runtime_manager, &route_store, production_mode);
+
+    if inspect {
+        tokio::spawn(extensions::inspector::serve());
+    }
+
+    // Hot reload triggers
+    reload::spawn_sighup_listener(route_store.clone(), runtime_manager.clone());
+    if !production_mode {
+        reload::spawn_dev_watcher(route_store.clone(), runtime_manager.clone());
+    }
+
+    // Scheduled actions (`__cron`) share the worker pool with requests
+    let cron = Arc::new(cron_jobs::CronScheduler::from_json(&json));
+    cron.start(route_store.clone(), runtime_manager.clone(), request_timeout);
+
+    // Background jobs (`t.jobs.enqueue`), same pool
+    let jobs = jobs::JobQueue::start(
+        &json,
+        &project_root,
+        route_store.clone(),
+        runtime_manager.clone(),
+        request_timeout,
+    );
+
     // Build AppState
     let state = AppState {
-        routes: Arc::new(map),
-        dynamic_routes: Arc::new(dynamic_routes),
+        routes: route_store,
         runtime: runtime_manager,
-        fast_paths: Arc::new(fast_paths),
-        precomputed: Arc::new(precomputed),
         production_mode,
+        request_timeout,
+        cron,
+        jobs,
     };
 
     // Router
     let app = Router::new()
         .route("/", any(root_route))
+        .nest("/__titan/admin", admin::router())
         .fallback(any(dynamic_route))
         .with_state(state);
 
@@ -556,7 +900,11 @@ async fn main() -> Result<()> {
     println!(
         "\x1b[38;5;39mTitan server running at:\x1b[0m http://localhost:{}  \x1b[90m(Threads: {}, Stack: {}MB{})\x1b[0m",
         port,
-        threads,
+        if pool.max > pool.min {
+            format!("{}-{}", pool.min, pool.max)
+        } else {
+            pool.min.to_string()
+        },
         stack_mb,
         if production_mode { "" } else { ", Dev Mode" }
     );
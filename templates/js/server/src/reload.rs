@@ -0,0 +1,300 @@
+//! Hot Reload of routes.json and action bundles
+//!
+//! The route table (exact routes, dynamic routes, fast-path registry and
+//! pre-computed replies) lives behind a single `Arc` that is swapped
+//! atomically. Every request clones the `Arc` once at entry, so in-flight
+//! requests keep running against the version they started with.
+//!
+//! Triggers:
+//! 1. `SIGHUP` (unix only).
+//! 2. `POST /__titan/admin/reload` (see `admin.rs`).
+//! 3. A polling file watcher on routes.json + the actions dir (dev mode only).
+
+use serde_json::Value;
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, RwLock};
+use std::time::{Duration, SystemTime};
+
+use crate::action_management::{DynamicRoute, RouteVal};
+use crate::error_pages::ErrorPages;
+use crate::extensions::{code_cache, source_maps};
+use crate::fast_path::{FastPathRegistry, PrecomputedRoute};
+use crate::runtime::{Isolation, RuntimeManager};
+use crate::utils::{blue, gray, green, red};
+
+/// Everything derived from routes.json + the action bundles.
+pub struct RouteTable {
+    pub routes: HashMap<String, RouteVal>,
+    pub dynamic_routes: Vec<DynamicRoute>,
+    /// Pre-computed responses for static actions (bypass V8)
+    pub fast_paths: FastPathRegistry,
+    /// Pre-serialized responses for reply routes (no re-serialization per request)
+    pub precomputed: HashMap<String, PrecomputedRoute>,
+    /// Custom 404/405/500/504 handlers from `__config.errors`
+    pub errors: ErrorPages,
+    /// `__config.isolation`: default for routes without their own `isolation`
+    pub isolation: Isolation,
+}
+
+impl RouteTable {
+    /// Build the table from a parsed routes.json document.
+    pub fn from_json(json: &Value, actions_dir: &Path, root: &Path) -> Self {
+        let routes: HashMap<String, RouteVal> =
+            serde_json::from_value(json["routes"].clone()).unwrap_or_default();
+        let dynamic_routes: Vec<DynamicRoute> =
+            serde_json::from_value(json["__dynamic_routes"].clone()).unwrap_or_default();
+
+        // Build pre-computed route responses
+        let mut precomputed = HashMap::new();
+        for (key, route) in &routes {
+            match route.r#type.as_str() {
+                "json" => {
+                    precomputed.insert(key.clone(), PrecomputedRoute::from_json(&route.value));
+                }
+                "text" => {
+                    if let Some(s) = route.value.as_str() {
+                        precomputed.insert(key.clone(), PrecomputedRoute::from_text(s));
+                    }
+                }
+                _ => {}
+            }
+        }
+        if !precomputed.is_empty() {
+            println!(
+                "{} {} reply route(s) pre-computed",
+                blue("[Titan]"),
+                precomputed.len()
+            );
+        }
+
+        // Build fast-path registry (scan action files for static patterns)
+        let fast_paths = FastPathRegistry::build(actions_dir);
+
+        let errors = ErrorPages::from_config(&json["__config"]["errors"], root);
+
+        // Validate isolation modes once here; requests fall back to the default
+        let mut isolation = Isolation::Shared;
+        if let Some(name) = json["__config"]["isolation"].as_str() {
+            match Isolation::parse(name) {
+                Some(mode) => isolation = mode,
+                None => warn_isolation("__config", name),
+            }
+        }
+        let route_modes = routes
+            .iter()
+            .map(|(key, r)| (key.as_str(), r.isolation.as_deref()))
+            .chain(
+                dynamic_routes
+                    .iter()
+                    .map(|r| (r.pattern.as_str(), r.isolation.as_deref())),
+            );
+        for (route, mode) in route_modes {
+            if let Some(name) = mode.filter(|m| Isolation::parse(m).is_none()) {
+                warn_isolation(route, name);
+            }
+        }
+
+        Self {
+            routes,
+            dynamic_routes,
+            fast_paths,
+            precomputed,
+            errors,
+            isolation,
+        }
+    }
+
+    /// Isolation for running `action` outside of its routes (cron, jobs,
+    /// `t.call`, error handlers): the mode of the routes pointing at it,
+    /// `Context` if any of them asks for it, else the table default.
+    pub fn isolation_for(&self, action: &str) -> Isolation {
+        let exact = self
+            .routes
+            .values()
+            .filter(|r| r.r#type == "action" && r.value.as_str() == Some(action))
+            .map(|r| r.isolation.as_deref());
+        let dynamic = self
+            .dynamic_routes
+            .iter()
+            .filter(|r| r.action == action)
+            .map(|r| r.isolation.as_deref());
+        let mut modes = exact
+            .chain(dynamic)
+            .map(|mode| mode.and_then(Isolation::parse).unwrap_or(self.isolation))
+            .peekable();
+        if modes.peek().is_none() {
+            return self.isolation;
+        }
+        if modes.any(|mode| mode == Isolation::Context) {
+            Isolation::Context
+        } else {
+            Isolation::Shared
+        }
+    }
+}
+
+fn warn_isolation(scope: &str, name: &str) {
+    println!(
+        "{} {} '{}' for {} (expected shared or context)",
+        blue("[Titan]"),
+        red("Unknown isolation mode"),
+        name,
+        scope
+    );
+}
+
+/// Holder for the current route table. Readers take a cheap `Arc` snapshot.
+pub struct RouteStore {
+    current: RwLock<Arc<RouteTable>>,
+    routes_path: PathBuf,
+    actions_dir: PathBuf,
+    root: PathBuf,
+}
+
+impl RouteStore {
+    pub fn new(
+        table: RouteTable,
+        routes_path: PathBuf,
+        actions_dir: PathBuf,
+        root: PathBuf,
+    ) -> Self {
+        Self {
+            current: RwLock::new(Arc::new(table)),
+            routes_path,
+            actions_dir,
+            root,
+        }
+    }
+
+    /// Snapshot of the active table. O(1) refcount bump.
+    #[inline(always)]
+    pub fn current(&self) -> Arc<RouteTable> {
+        self.current.read().unwrap().clone()
+    }
+
+    /// Re-read routes.json, rebuild the table and swap it in.
+    /// The old table is not touched, so a parse failure keeps serving it.
+    pub fn reload_table(&self) -> Result<(), String> {
+        let raw = fs::read_to_string(&self.routes_path)
+            .map_err(|e| format!("Failed to read {}: {}", self.routes_path.display(), e))?;
+        let json: Value = serde_json::from_str(&raw)
+            .map_err(|e| format!("Invalid routes.json: {}", e))?;
+
+        let table = RouteTable::from_json(&json, &self.actions_dir, &self.root);
+        *self.current.write().unwrap() = Arc::new(table);
+        Ok(())
+    }
+}
+
+/// Full reload: route table first, then ask every worker to recompile its actions.
+///
+/// Worker channels are FIFO, so requests queued before the reload command
+/// still run the old bundles; suspended (drifting) requests keep the
+/// function handle they started with.
+pub async fn reload_all(store: &RouteStore, runtime: &Arc<RuntimeManager>) -> Result<(), String> {
+    let start = std::time::Instant::now();
+
+    if let Err(e) = store.reload_table() {
+        println!("{} {} {}", blue("[Titan]"), red("Reload failed:"), e);
+        return Err(e);
+    }
+
+    // Isolates rebuilt from now on (recycle, respawn) must not boot old bundles
+    crate::extensions::snapshot::invalidate();
+    source_maps::load(&store.root);
+
+    let rt = runtime.clone();
+    tokio::task::spawn_blocking(move || rt.reload_actions())
+        .await
+        .map_err(|e| e.to_string())?;
+
+    println!(
+        "{} {} {}",
+        blue("[Titan]"),
+        green("Routes and actions reloaded"),
+        gray(&format!("in {:.2?}", start.elapsed()))
+    );
+    Ok(())
+}
+
+/// Reload on SIGHUP.
+#[cfg(unix)]
+pub fn spawn_sighup_listener(store: Arc<RouteStore>, runtime: Arc<RuntimeManager>) {
+    use tokio::signal::unix::{SignalKind, signal};
+
+    tokio::spawn(async move {
+        let mut hup = match signal(SignalKind::hangup()) {
+            Ok(s) => s,
+            Err(e) => {
+                println!("{} {} {}", blue("[Titan]"), red("SIGHUP listener failed:"), e);
+                return;
+            }
+        };
+        while hup.recv().await.is_some() {
+            println!("{} {}", blue("[Titan]"), gray("SIGHUP received, reloading..."));
+            let _ = reload_all(&store, &runtime).await;
+        }
+    });
+}
+
+#[cfg(not(unix))]
+pub fn spawn_sighup_listener(_store: Arc<RouteStore>, _runtime: Arc<RuntimeManager>) {}
+
+/// Poll routes.json and the actions dir for changes (dev mode only).
+///
+/// Polling keeps us free of a watcher dependency. A change is only applied
+/// once the fingerprint is stable across two ticks, so a bundler that is
+/// still writing files does not trigger a half-built reload.
+pub fn spawn_dev_watcher(store: Arc<RouteStore>, runtime: Arc<RuntimeManager>) {
+    tokio::spawn(async move {
+        let mut applied = fingerprint(&store.routes_path, &store.actions_dir);
+        let mut pending: Option<Vec<(PathBuf, SystemTime)>> = None;
+        let mut ticker = tokio::time::interval(Duration::from_millis(500));
+
+        loop {
+            ticker.tick().await;
+            let seen = fingerprint(&store.routes_path, &store.actions_dir);
+
+            if seen == applied {
+                pending = None;
+                continue;
+            }
+
+            if pending.as_ref() == Some(&seen) {
+                if reload_all(&store, &runtime).await.is_ok() {
+                    applied = seen;
+                }
+                pending = None;
+            } else {
+                pending = Some(seen);
+            }
+        }
+    });
+}
+
+/// Sorted (path, mtime) list of routes.json and every action bundle.
+fn fingerprint(routes_path: &Path, actions_dir: &Path) -> Vec<(PathBuf, SystemTime)> {
+    let mut out = Vec::new();
+    let mut push = |p: PathBuf| {
+        if let Ok(m) = fs::metadata(&p).and_then(|m| m.modified()) {
+            out.push((p, m));
+        }
+    };
+
+    push(routes_path.to_path_buf());
+    if let Ok(entries) = fs::read_dir(actions_dir) {
+        for entry in entries.flatten() {
+            let path = entry.path();
+            // Code caches are written by the workers themselves
+            if code_cache::is_cache_file(&path) {
+                continue;
+            }
+            push(path);
+        }
+    }
+
+    out.sort();
+    out
+}
@@ -318,6 +318,9 @@ async function rebuild() {
     }
 }
 
+// Sources that only feed routes.json and the JS bundles
+const HOT_RELOADABLE = /\.(m?[jt]sx?|json)$/;
+
 async function startDev() {
     prepareRuntime();
     const root = process.cwd();
@@ -367,6 +370,13 @@ async function startDev() {
         if (timer) clearTimeout(timer);
         timer = setTimeout(async () => {
             try {
+                // The running server hot-reloads routes.json and action bundles
+                // on its own. Anything else (.env, Rust actions) needs a
+                // recompile and/or restart.
+                if (serverProcess && HOT_RELOADABLE.test(file)) {
+                    await rebuild();
+                    return;
+                }
                 await killServer();
                 await rebuild();
                 await startRustServer();
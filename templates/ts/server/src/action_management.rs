@@ -15,6 +15,9 @@ pub struct RouteVal {
     pub r#type: String,
     #[serde(alias = "target")]
     pub value: Value,
+    /// `"context"` runs the action in a fresh V8 context (see `runtime::Isolation`)
+    #[serde(default)]
+    pub isolation: Option<String>,
 }
 
 #[derive(Debug, Deserialize, Clone)]
@@ -22,6 +25,8 @@ pub struct DynamicRoute {
     pub method: String,
     pub pattern: String,
     pub action: String,
+    #[serde(default)]
+    pub isolation: Option<String>,
 }
 
 /// Resolve the directory path where actions are stored.
@@ -85,7 +90,7 @@ pub fn match_dynamic_route(
     method: &str,
     path: &str,
     routes: &[DynamicRoute],
-) -> Option<(String, HashMap<String, String>)> {
+) -> Option<(&DynamicRoute, HashMap<String, String>)> {
     let path_segments: Vec<&str> =
         path.trim_matches('/').split('/').collect();
 
@@ -94,49 +99,82 @@ pub fn match_dynamic_route(
             continue;
         }
 
-        let pattern_segments: Vec<&str> =
-            route.pattern.trim_matches('/').split('/').collect();
+        if let Some(params) = match_pattern(&route.pattern, &path_segments) {
+            return Some((route, params));
+        }
+    }
 
-        if pattern_segments.len() != path_segments.len() {
-            continue;
+    None
+}
+
+/// Methods that WOULD match this path (for 405 + `Allow`). Empty if the path
+/// is unknown or is registered without a method (matches any method).
+pub fn allowed_methods(
+    path: &str,
+    routes: &HashMap<String, RouteVal>,
+    dynamic_routes: &[DynamicRoute],
+) -> Vec<String> {
+    if routes.contains_key(path) {
+        return Vec::new();
+    }
+
+    let mut methods: Vec<String> = routes
+        .keys()
+        .filter_map(|k| k.split_once(':'))
+        .filter(|(_, p)| *p == path)
+        .map(|(m, _)| m.to_string())
+        .collect();
+
+    let path_segments: Vec<&str> =
+        path.trim_matches('/').split('/').collect();
+    for route in dynamic_routes {
+        if match_pattern(&route.pattern, &path_segments).is_some() {
+            methods.push(route.method.clone());
         }
+    }
+
+    methods.sort();
+    methods.dedup();
+    methods
+}
 
-        let mut params = HashMap::new();
-        let mut matched = true;
+/// Match `/users/:id<number>`-style patterns segment by segment.
+fn match_pattern(pattern: &str, path_segments: &[&str]) -> Option<HashMap<String, String>> {
+    let pattern_segments: Vec<&str> =
+        pattern.trim_matches('/').split('/').collect();
 
-        for (pat, val) in pattern_segments.iter().zip(path_segments.iter()) {
-            if pat.starts_with(':') {
-                let inner = &pat[1..];
+    if pattern_segments.len() != path_segments.len() {
+        return None;
+    }
 
-                let (name, ty) = inner
-                    .split_once('<')
-                    .map(|(n, t)| (n, t.trim_end_matches('>')))
-                    .unwrap_or((inner, "string"));
+    let mut params = HashMap::new();
 
-                let valid = match ty {
-                    "number" => val.parse::<i64>().is_ok(),
-                    "string" => true,
-                    _ => false,
-                };
+    for (pat, val) in pattern_segments.iter().zip(path_segments.iter()) {
+        if pat.starts_with(':') {
+            let inner = &pat[1..];
 
-                if !valid {
-                    matched = false;
-                    break;
-                }
+            let (name, ty) = inner
+                .split_once('<')
+                .map(|(n, t)| (n, t.trim_end_matches('>')))
+                .unwrap_or((inner, "string"));
+
+            let valid = match ty {
+                "number" => val.parse::<i64>().is_ok(),
+                "string" => true,
+                _ => false,
+            };
 
-                params.insert(name.to_string(), (*val).to_string());
-            } else if pat != val {
-                matched = false;
-                break;
+            if !valid {
+                return None;
             }
-        }
 
-        if matched {
-            return Some((route.action.clone(), params));
+            params.insert(name.to_string(), (*val).to_string());
+        } else if pat != val {
+            return None;
         }
     }
 
-    None
+    Some(params)
 }
 
 /// Scan the resolved actions directory and return a map of action names to file paths.
@@ -159,7 +197,7 @@ pub fn scan_actions(root: &PathBuf) -> HashMap<String, PathBuf> {
             if !path.is_file() { continue; }
             
             let ext = path.extension().and_then(|s| s.to_str()).unwrap_or("");
-            if ext != "js" && ext != "jsbundle" {
+            if ext != "js" && ext != "jsbundle" && ext != "mjs" {
                 continue;
             }
             
@@ -0,0 +1,338 @@
+//! Custom Error Pages
+//!
+//! `__config.errors` in routes.json maps a status code to a user action or a
+//! static template:
+//!
+//! ```json
+//! "errors": {
+//!   "404": "notFound",
+//!   "500": { "action": "serverError" },
+//!   "504": { "template": "static/504.html" }
+//! }
+//! ```
+//!
+//! Actions receive the normal request plus `req.error` (see `ErrorContext`).
+//! Templates are read once per route-table build and may use `{{status}}`,
+//! `{{code}}` and `{{correlationId}}` placeholders.
+//!
+//! Errors thrown as `t.HttpError` (or a subclass) bypass this table and are
+//! answered directly as RFC 9457 problem+json (see `problem_response`).
+
+use axum::{
+    body::Body,
+    http::{HeaderMap, HeaderName, HeaderValue, StatusCode, header},
+    response::Response,
+};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::utils::{blue, red};
+
+/// Status codes that can be customized.
+pub const HANDLED_STATUSES: [u16; 4] = [404, 405, 500, 504];
+
+pub enum ErrorTarget {
+    Action(String),
+    Template {
+        body: String,
+        content_type: &'static str,
+    },
+}
+
+#[derive(Default)]
+pub struct ErrorPages {
+    targets: HashMap<u16, ErrorTarget>,
+}
+
+impl ErrorPages {
+    /// Parse `__config.errors`. Templates resolve relative to the project root.
+    pub fn from_config(cfg: &Value, root: &Path) -> Self {
+        let mut targets = HashMap::new();
+        let Some(map) = cfg.as_object() else {
+            return Self { targets };
+        };
+
+        for (key, val) in map {
+            let Some(status) = key.parse::<u16>().ok().filter(|s| HANDLED_STATUSES.contains(s))
+            else {
+                println!(
+                    "{} {} '{}' (supported: 404, 405, 500, 504)",
+                    blue("[Titan]"),
+                    red("Ignoring error handler for unsupported status"),
+                    key
+                );
+                continue;
+            };
+
+            let action = val
+                .as_str()
+                .or_else(|| val.get("action").and_then(|a| a.as_str()));
+            if let Some(name) = action {
+                targets.insert(status, ErrorTarget::Action(name.to_string()));
+                continue;
+            }
+
+            if let Some(file) = val.get("template").and_then(|t| t.as_str()) {
+                let path = root.join(file);
+                match std::fs::read_to_string(&path) {
+                    Ok(body) => {
+                        targets.insert(
+                            status,
+                            ErrorTarget::Template {
+                                body,
+                                content_type: content_type_for(&path),
+                            },
+                        );
+                    }
+                    Err(e) => println!(
+                        "{} {} {} ({})",
+                        blue("[Titan]"),
+                        red("Error template not readable:"),
+                        path.display(),
+                        e
+                    ),
+                }
+            }
+        }
+
+        Self { targets }
+    }
+
+    #[inline(always)]
+    pub fn get(&self, status: u16) -> Option<&ErrorTarget> {
+        self.targets.get(&status)
+    }
+
+    #[inline(always)]
+    pub fn is_empty(&self) -> bool {
+        self.targets.is_empty()
+    }
+}
+
+/// Structured error information handed to error actions as `req.error`.
+pub struct ErrorContext {
+    pub status: u16,
+    pub code: &'static str,
+    /// Raw message (never sent to clients in production by the default handler)
+    pub message: String,
+    pub correlation_id: String,
+    pub action: Option<String>,
+    pub allow: Vec<String>,
+    /// Source-mapped stack of a thrown error (shown outside production only)
+    pub stack: Option<String>,
+}
+
+impl ErrorContext {
+    pub fn new(status: u16, message: impl Into<String>, correlation_id: String) -> Self {
+        Self {
+            status,
+            code: code_for(status),
+            message: message.into(),
+            correlation_id,
+            action: None,
+            allow: Vec::new(),
+            stack: None,
+        }
+    }
+
+    pub fn to_json(&self) -> Value {
+        serde_json::json!({
+            "status": self.status,
+            "code": self.code,
+            "message": self.message,
+            "correlationId": self.correlation_id,
+            "action": self.action,
+            "allow": self.allow,
+        })
+    }
+
+    /// Apply `{{status}}`, `{{code}}` and `{{correlationId}}` to a template.
+    pub fn render(&self, template: &str) -> String {
+        template
+            .replace("{{status}}", &self.status.to_string())
+            .replace("{{code}}", self.code)
+            .replace("{{correlationId}}", &self.correlation_id)
+    }
+}
+
+pub fn code_for(status: u16) -> &'static str {
+    match status {
+        400 => "BAD_REQUEST",
+        404 => "NOT_FOUND",
+        405 => "METHOD_NOT_ALLOWED",
+        503 => "SERVICE_UNAVAILABLE",
+        504 => "GATEWAY_TIMEOUT",
+        _ => "INTERNAL_ERROR",
+    }
+}
+
+fn content_type_for(path: &Path) -> &'static str {
+    match path.extension().and_then(|e| e.to_str()).unwrap_or("") {
+        "html" | "htm" => "text/html; charset=utf-8",
+        "json" => "application/json",
+        _ => "text/plain; charset=utf-8",
+    }
+}
+
+static CORRELATION_COUNTER: AtomicU64 = AtomicU64::new(0);
+
+/// Short unique ID for correlating a client-visible error with server logs.
+/// Reuses a well-formed incoming `x-request-id` when present.
+pub fn correlation_id(incoming: Option<&str>) -> String {
+    if let Some(id) = incoming {
+        if !id.is_empty()
+            && id.len() <= 128
+            && id.bytes().all(|b| b.is_ascii_alphanumeric() || b == b'-' || b == b'_')
+        {
+            return id.to_string();
+        }
+    }
+
+    let nanos = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_nanos() as u64)
+        .unwrap_or(0);
+    let n = CORRELATION_COUNTER.fetch_add(1, Ordering::Relaxed);
+    format!("{:x}-{:04x}", nanos, n & 0xffff)
+}
+
+/// RFC 9457 `application/problem+json` response for a thrown `t.HttpError`.
+///
+/// `problem` is the error's `toJSON()` output (`status`, `title`, `detail`,
+/// `code`, `type`, `details`, `headers`). `redact` drops `detail` and
+/// `details` (5xx in production, where they may carry internals).
+pub fn problem_response(problem: &Value, instance: &str, redact: bool) -> Response {
+    let status = problem_status(problem);
+
+    let title = problem["title"]
+        .as_str()
+        .or(status.canonical_reason())
+        .unwrap_or("Error");
+
+    let mut body = serde_json::Map::new();
+    body.insert(
+        "type".into(),
+        Value::from(problem["type"].as_str().unwrap_or("about:blank")),
+    );
+    body.insert("title".into(), Value::from(title));
+    body.insert("status".into(), Value::from(status.as_u16()));
+    if let Some(detail) = problem["detail"].as_str().filter(|_| !redact) {
+        body.insert("detail".into(), Value::from(detail));
+    }
+    body.insert("instance".into(), Value::from(instance));
+    if let Some(code) = problem["code"].as_str() {
+        body.insert("code".into(), Value::from(code));
+    }
+    if !redact && !problem["details"].is_null() {
+        body.insert("details".into(), problem["details"].clone());
+    }
+
+    let mut response = Response::builder()
+        .status(status)
+        .header(header::CONTENT_TYPE, "application/problem+json")
+        .body(Body::from(Value::Object(body).to_string()))
+        .unwrap();
+    append_problem_headers(response.headers_mut(), problem);
+    response
+}
+
+/// Status of a thrown `t.HttpError`. Non-4xx/5xx statuses become 500.
+pub fn problem_status(problem: &Value) -> StatusCode {
+    problem["status"]
+        .as_u64()
+        .and_then(|s| u16::try_from(s).ok())
+        .and_then(|s| StatusCode::from_u16(s).ok())
+        .filter(|s| s.is_client_error() || s.is_server_error())
+        .unwrap_or(StatusCode::INTERNAL_SERVER_ERROR)
+}
+
+/// Append a thrown error's extra headers (e.g. WWW-Authenticate, Retry-After).
+fn append_problem_headers(headers: &mut HeaderMap, problem: &Value) {
+    let Some(hmap) = problem["headers"].as_object() else {
+        return;
+    };
+    for (k, v) in hmap {
+        let Ok(name) = HeaderName::try_from(k.as_str()) else {
+            continue;
+        };
+        let values = match v {
+            Value::Array(items) => items.iter().filter_map(|i| i.as_str()).collect(),
+            Value::String(s) => vec![s.as_str()],
+            _ => continue,
+        };
+        for vs in values {
+            if let Ok(value) = HeaderValue::from_str(vs) {
+                headers.append(&name, value);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    async fn body_json(response: Response) -> Value {
+        let bytes = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        serde_json::from_slice(&bytes).unwrap()
+    }
+
+    fn service_unavailable() -> Value {
+        serde_json::json!({
+            "status": 503,
+            "title": "Service Unavailable",
+            "detail": "database pool exhausted",
+            "code": "SERVICE_UNAVAILABLE",
+            "details": { "pool": "primary" },
+            "headers": { "Retry-After": "30" },
+        })
+    }
+
+    #[tokio::test]
+    async fn thrown_503_is_problem_json() {
+        let response = problem_response(&service_unavailable(), "/api/orders", false);
+        assert_eq!(response.status(), StatusCode::SERVICE_UNAVAILABLE);
+        assert_eq!(
+            response.headers()[header::CONTENT_TYPE],
+            "application/problem+json"
+        );
+        assert_eq!(response.headers()[header::RETRY_AFTER], "30");
+
+        let body = body_json(response).await;
+        assert_eq!(body["type"], "about:blank");
+        assert_eq!(body["status"], 503);
+        assert_eq!(body["instance"], "/api/orders");
+        assert_eq!(body["detail"], "database pool exhausted");
+        assert_eq!(body["details"]["pool"], "primary");
+    }
+
+    #[tokio::test]
+    async fn redacted_503_keeps_status_title_and_headers() {
+        let response = problem_response(&service_unavailable(), "/api/orders", true);
+        assert_eq!(response.status(), StatusCode::SERVICE_UNAVAILABLE);
+        assert_eq!(response.headers()[header::RETRY_AFTER], "30");
+
+        let body = body_json(response).await;
+        assert_eq!(body["title"], "Service Unavailable");
+        assert_eq!(body["code"], "SERVICE_UNAVAILABLE");
+        assert!(body.get("detail").is_none());
+        assert!(body.get("details").is_none());
+    }
+
+    #[test]
+    fn out_of_range_status_becomes_500() {
+        assert_eq!(
+            problem_status(&serde_json::json!({ "status": 302 })),
+            StatusCode::INTERNAL_SERVER_ERROR
+        );
+        assert_eq!(
+            problem_status(&serde_json::json!({ "status": 409 })),
+            StatusCode::CONFLICT
+        );
+    }
+}
@@ -8,12 +8,109 @@ if (!globalThis.__TITAN_CORE_LOADED__) {
     // ensure t exists early
     if (!globalThis.t) globalThis.t = {};
 
+    // HTTP Errors
+    // Thrown from actions; the server answers with an RFC 9457
+    // application/problem+json body and the error's status.
+    //   throw new t.NotFound("User not found", { details: { id } })
+    const HTTP_TITLES = {
+        400: "Bad Request",
+        401: "Unauthorized",
+        403: "Forbidden",
+        404: "Not Found",
+        405: "Method Not Allowed",
+        409: "Conflict",
+        410: "Gone",
+        422: "Unprocessable Content",
+        429: "Too Many Requests",
+        500: "Internal Server Error",
+        501: "Not Implemented",
+        503: "Service Unavailable",
+    };
+
+    class HttpError extends Error {
+        constructor(status = 500, message, options = {}) {
+            status = Number.isInteger(status) && status >= 400 && status <= 599 ? status : 500;
+            const title = HTTP_TITLES[status] || (status >= 500 ? "Server Error" : "Client Error");
+            super(message || title);
+            this.name = "HttpError";
+            this.status = status;
+            this.title = title;
+            this.code = options.code || title.toUpperCase().replace(/ /g, "_");
+            this.details = options.details;
+            this.type = options.type;
+            this.headers = options.headers;
+        }
+
+        toJSON() {
+            return {
+                status: this.status,
+                title: this.title,
+                detail: this.message,
+                code: this.code,
+                type: this.type,
+                details: this.details,
+                headers: this.headers,
+            };
+        }
+    }
+
+    t.HttpError = HttpError;
+    for (const [status, name] of [
+        [400, "BadRequest"],
+        [401, "Unauthorized"],
+        [403, "Forbidden"],
+        [404, "NotFound"],
+        [405, "MethodNotAllowed"],
+        [409, "Conflict"],
+        [410, "Gone"],
+        [422, "UnprocessableEntity"],
+        [429, "TooManyRequests"],
+        [500, "InternalServerError"],
+        [501, "NotImplemented"],
+        [503, "ServiceUnavailable"],
+    ]) {
+        const cls = class extends HttpError {
+            constructor(message, options) {
+                super(status, message, options);
+                this.name = name;
+            }
+        };
+        Object.defineProperty(cls, "name", { value: name });
+        t[name] = cls;
+    }
+
+    // Result handed to _finish_request when an action throws
+    function _errorResult(err) {
+        if (err instanceof HttpError) {
+            return { error: err.message, __httpError: err.toJSON() };
+        }
+        const result = { error: (err && err.message) || String(err) };
+        if (err instanceof Error && err.stack) result.stack = err.stack;
+        return result;
+    }
+
     // defineAction identity helper
     globalThis.defineAction = (fn) => {
         if (fn.__titanWrapped) return fn;
 
+        // `async` actions await promise-based drift() and continue in place;
+        // sync actions keep the suspend-and-replay model. Actions that only
+        // return a promise (transpiled async, promise chains) are replayed
+        // until one settles, then switch to promise-based drift().
+        let isAsync = fn[Symbol.toStringTag] === "AsyncFunction";
+        const settledAsync = () => {
+            if (isAsync) return;
+            isAsync = true;
+            console.warn(
+                `[Titan] Action '${fn.name || "anonymous"}' returns a promise but is not ` +
+                "an async function: drift() now resolves in place for it. Declare it " +
+                "`async` so the first requests are not replayed."
+            );
+        };
+
         const wrapped = function (req) {
             const requestId = req.__titan_request_id;
+            req.__titan_async = isAsync;
 
             if (req.rawBody && req.rawBody.byteLength !== undefined) {
                 try {
@@ -51,10 +148,14 @@ if (!globalThis.__TITAN_CORE_LOADED__) {
 
                 if (result && typeof result.then === 'function') {
                     result.then(
-                        (data) => t._finish_request(requestId, data),
+                        (data) => {
+                            settledAsync();
+                            t._finish_request(requestId, data);
+                        },
                         (err) => {
                             if (isSuspend(err)) return;
-                            t._finish_request(requestId, { error: err.message || String(err) });
+                            settledAsync();
+                            t._finish_request(requestId, _errorResult(err));
                         }
                     );
                 } else {
@@ -62,7 +163,7 @@ if (!globalThis.__TITAN_CORE_LOADED__) {
                 }
             } catch (err) {
                 if (isSuspend(err)) return;
-                t._finish_request(requestId, { error: err.message || String(err) });
+                t._finish_request(requestId, _errorResult(err));
             }
         };
 
@@ -71,15 +172,20 @@ if (!globalThis.__TITAN_CORE_LOADED__) {
     };
 
 
-    // TextDecoder Polyfill
-    globalThis.TextDecoder = class TextDecoder {
-        decode(buffer) {
-            return t.decodeUtf8(buffer);
-        }
-    };
-
-    // Titan Environment API
-    t.env = t.loadEnv ? t.loadEnv() : {};
+    // Titan Environment API. Read on first use, so a startup snapshot (built
+    // before any request) never holds the environment
+    let env = null;
+    Object.defineProperty(t, "env", {
+        configurable: true,
+        enumerable: true,
+        get() {
+            if (env === null) env = t.loadEnv ? t.loadEnv() : {};
+            return env;
+        },
+        set(value) {
+            env = value;
+        },
+    });
 
     // Async Proxy Creator
     function createAsyncOp(op) {
@@ -120,13 +226,18 @@ if (!globalThis.__TITAN_CORE_LOADED__) {
         let extraHeaders = {};
 
         if (secondArg !== undefined && secondArg !== null && typeof secondArg === 'object') {
-            // Options object form: { status: N, headers: {...} }
+            // Options object form: { status: N, headers: {...}, cookies: [...] }
             status = secondArg.status || 200;
-            extraHeaders = secondArg.headers || {};
+            extraHeaders = { ...(secondArg.headers || {}) };
             // Also merge thirdArg if provided (defensive)
             if (thirdArg && typeof thirdArg === 'object') {
                 extraHeaders = { ...extraHeaders, ...thirdArg };
             }
+            if (Array.isArray(secondArg.cookies)) {
+                for (const c of secondArg.cookies) {
+                    _appendHeader(extraHeaders, "Set-Cookie", _serializeCookie(c.name, c.value, c));
+                }
+            }
         } else {
             // Positional form: (status, extraHeaders)
             status = secondArg || 200;
@@ -136,6 +247,49 @@ if (!globalThis.__TITAN_CORE_LOADED__) {
         return { status, extraHeaders };
     }
 
+    // Cookies
+    // A header value may be a string or an array of strings; arrays are sent
+    // as repeated headers, which is the only valid way to set several cookies.
+    const COOKIE_NAME = /^[!#$%&'*+\-.^_`|~0-9A-Za-z]+$/;
+
+    function _serializeCookie(name, value, opts = {}) {
+        if (!COOKIE_NAME.test(String(name))) {
+            throw new TypeError(`Invalid cookie name: ${name}`);
+        }
+        let str = `${name}=${encodeURIComponent(value == null ? "" : String(value))}`;
+
+        if (opts.maxAge !== undefined && opts.maxAge !== null) {
+            str += `; Max-Age=${Math.floor(Number(opts.maxAge))}`;
+        }
+        if (opts.expires) {
+            const date = opts.expires instanceof Date ? opts.expires : new Date(opts.expires);
+            str += `; Expires=${date.toUTCString()}`;
+        }
+        if (opts.domain) str += `; Domain=${opts.domain}`;
+        str += `; Path=${opts.path || "/"}`;
+        if (opts.httpOnly) str += "; HttpOnly";
+        if (opts.secure) str += "; Secure";
+        if (opts.sameSite) {
+            const s = String(opts.sameSite).toLowerCase();
+            if (s !== "strict" && s !== "lax" && s !== "none") {
+                throw new TypeError(`Invalid SameSite value: ${opts.sameSite}`);
+            }
+            str += `; SameSite=${s[0].toUpperCase()}${s.slice(1)}`;
+        }
+        return str;
+    }
+
+    function _appendHeader(headers, name, value) {
+        const lower = name.toLowerCase();
+        const existing = Object.keys(headers).find((k) => k.toLowerCase() === lower) || name;
+        const prev = headers[existing];
+        if (prev === undefined) {
+            headers[existing] = [value];
+        } else {
+            headers[existing] = Array.isArray(prev) ? [...prev, value] : [prev, value];
+        }
+    }
+
     const titanResponse = {
         json(data, second, third) {
             const { status, extraHeaders } = _parseResponseOpts(second, third);
@@ -164,6 +318,35 @@ if (!globalThis.__TITAN_CORE_LOADED__) {
                 body: String(data)
             };
         },
+        // Binary body (Uint8Array / ArrayBuffer / any TypedArray view).
+        // The bytes are copied out when the action returns; the buffer may
+        // be reused afterwards.
+        //   t.response.binary(bytes, { contentType: "image/png", headers: {...} })
+        binary(data, second, third) {
+            if (!(data instanceof ArrayBuffer) && !ArrayBuffer.isView(data)) {
+                throw new TypeError("t.response.binary() expects a Uint8Array or ArrayBuffer");
+            }
+            const { status, extraHeaders } = _parseResponseOpts(second, third);
+            const contentType =
+                (second && typeof second === 'object' && second.contentType) ||
+                "application/octet-stream";
+            return {
+                _isResponse: true,
+                status,
+                headers: { "Content-Type": contentType, ...extraHeaders },
+                body: data
+            };
+        },
+        // Attach a cookie to an existing response object (returns it for chaining).
+        //   return t.response.setCookie(t.response.json(data), "sid", token, { httpOnly: true })
+        setCookie(res, name, value, opts) {
+            if (!res.headers) res.headers = {};
+            _appendHeader(res.headers, "Set-Cookie", _serializeCookie(name, value, opts));
+            return res;
+        },
+        clearCookie(res, name, opts) {
+            return titanResponse.setCookie(res, name, "", { ...opts, maxAge: 0, expires: new Date(0) });
+        },
         redirect(url, second, third) {
             const { status: rawStatus, extraHeaders } = _parseResponseOpts(second, third);
             // For redirects, default to 302 and ensure 3xx range
@@ -181,7 +364,14 @@ if (!globalThis.__TITAN_CORE_LOADED__) {
     t.response = titanResponse;
 
     // Drift Support
-    globalThis.drift = function (value) {
+    //   drift(op)                      — default timeout (__config.drift_timeout_ms)
+    //   drift(op, { timeout: 2000 })   — per-op timeout in ms; a timed-out op
+    //                                    resolves to { error: "... timed out ..." }
+    //   drift([a, b, c], { concurrency: 2 })
+    //                                  — batch runs concurrently (at most N at
+    //                                    once); results keep order, a failed op
+    //                                    only yields its own { error } entry
+    globalThis.drift = function (value, options) {
         if (Array.isArray(value)) {
             for (const item of value) {
                 if (!item || !item.__titanAsync) {
@@ -192,7 +382,86 @@ if (!globalThis.__TITAN_CORE_LOADED__) {
             throw new Error("drift() must wrap async ops.");
         }
 
-        return t._drift_call(value);
+        const timeout = options && options.timeout;
+        if (timeout !== undefined && !(Number.isFinite(timeout) && timeout > 0)) {
+            throw new TypeError("drift() timeout must be a positive number of milliseconds");
+        }
+
+        const concurrency = options && options.concurrency;
+        if (concurrency !== undefined && !(Number.isInteger(concurrency) && concurrency > 0)) {
+            throw new TypeError("drift() concurrency must be a positive integer");
+        }
+
+        const req = globalThis.__titan_req;
+        return t._drift_call(value, !!(req && req.__titan_async), timeout, concurrency);
+    };
+
+    // Timers (request-scoped: a finished request's pending timers never fire)
+    function setTimer(callback, delay, args, repeat) {
+        if (typeof callback !== "function") {
+            throw new TypeError("Timer callback must be a function");
+        }
+        const fn = args.length ? () => callback(...args) : callback;
+        return t._timer_set(fn, Number(delay) || 0, repeat);
+    }
+
+    globalThis.setTimeout = (callback, delay, ...args) => setTimer(callback, delay, args, false);
+    globalThis.setInterval = (callback, delay, ...args) => setTimer(callback, delay, args, true);
+    globalThis.clearTimeout = (id) => {
+        if (id !== undefined && id !== null) t._timer_clear(id);
+    };
+    globalThis.clearInterval = globalThis.clearTimeout;
+
+    globalThis.queueMicrotask = (callback) => {
+        if (typeof callback !== "function") {
+            throw new TypeError("queueMicrotask() requires a function");
+        }
+        t._queue_microtask(callback);
+    };
+
+    // Background jobs: run `action` later on the worker pool with `payload` as req.body
+    const MAX_JOB_DELAY_MS = 24 * 60 * 60 * 1000; // jobs.rs MAX_DELAY_MS
+    t.jobs = {
+        enqueue(action, payload = null, options = {}) {
+            if (typeof action !== "string" || !action) {
+                throw new TypeError("t.jobs.enqueue() requires an action name");
+            }
+            const { delay = 0, retries = 0, backoff = {} } = options || {};
+            if (!(Number.isFinite(delay) && delay >= 0 && delay <= MAX_JOB_DELAY_MS)) {
+                throw new TypeError("t.jobs.enqueue() delay must be between 0 and 86400000 milliseconds (24h)");
+            }
+            if (!(Number.isInteger(retries) && retries >= 0)) {
+                throw new TypeError("t.jobs.enqueue() retries must be a non-negative integer");
+            }
+            // `backoff: 500` is a fixed delay; objects pick the strategy
+            const spec = typeof backoff === "number" ? { type: "fixed", delay: backoff } : backoff;
+            const type = spec.type || "exponential";
+            if (type !== "fixed" && type !== "exponential") {
+                throw new TypeError('t.jobs.enqueue() backoff type must be "fixed" or "exponential"');
+            }
+            const backoffDelay = spec.delay === undefined ? 1000 : spec.delay;
+            if (!(Number.isFinite(backoffDelay) && backoffDelay >= 0)) {
+                throw new TypeError("t.jobs.enqueue() backoff delay must be a non-negative number of milliseconds");
+            }
+
+            const json = JSON.stringify(payload === undefined ? null : payload);
+            return t._jobs_enqueue(action, json, delay, retries, type, backoffDelay);
+        }
+    };
+
+    // Action-to-action call: `drift(t.call("getUser", { params: { id } }))`
+    t.call = function (action, request = {}) {
+        if (typeof action !== "string" || !action) {
+            throw new TypeError("t.call() requires an action name");
+        }
+        // Callers already on the stack; the server refuses chains that get too deep
+        const parent = globalThis.__titan_req && globalThis.__titan_req.call;
+        const chain = parent ? parent.chain : [globalThis.__titan_action].filter(Boolean);
+        return createAsyncOp({
+            __titanAsync: true,
+            type: "call",
+            data: { action, request: request || {}, chain, depth: parent ? parent.depth : 0 }
+        });
     };
 
     // Safe Wrappers
@@ -0,0 +1,610 @@
+//! Web-standard globals
+//!
+//! Native backing for the globals bundled npm code expects: `TextEncoder`,
+//! `TextDecoder`, `URL`, `URLSearchParams`, `atob`, `btoa`,
+//! `structuredClone`, `crypto.getRandomValues`, `crypto.randomUUID`,
+//! `console` and `performance`.
+//!
+//! The classes themselves live in `web.js`, which receives the natives below
+//! as an argument (they are never reachable from action code directly).
+//! Installed by `inject_extensions` before the Titan APIs.
+
+use std::sync::OnceLock;
+use std::time::{Instant, SystemTime, UNIX_EPOCH};
+
+use base64::Engine;
+use base64::engine::{DecodePaddingMode, GeneralPurpose, GeneralPurposeConfig};
+
+use super::builtin::{current_action, log_parts};
+use super::{throw, v8_str, v8_to_string};
+use crate::utils::{blue, gray, red, yellow};
+
+const WEB_JS: &str = include_str!("web.js");
+
+/// `performance.timeOrigin` (process start)
+static TIME_ORIGIN: OnceLock<(Instant, f64)> = OnceLock::new();
+
+/// `atob` is forgiving: padding is optional and stray trailing bits are dropped
+const FORGIVING_BASE64: GeneralPurpose = GeneralPurpose::new(
+    &base64::alphabet::STANDARD,
+    GeneralPurposeConfig::new()
+        .with_decode_padding_mode(DecodePaddingMode::Indifferent)
+        .with_decode_allow_trailing_bits(true),
+);
+
+fn time_origin() -> &'static (Instant, f64) {
+    TIME_ORIGIN.get_or_init(|| {
+        let unix_ms = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs_f64() * 1000.0)
+            .unwrap_or(0.0);
+        (Instant::now(), unix_ms)
+    })
+}
+
+macro_rules! natives {
+    ($($name:literal => $f:ident),* $(,)?) => {
+        fn natives_object<'s>(scope: &mut v8::HandleScope<'s>) -> v8::Local<'s, v8::Object> {
+            let obj = v8::Object::new(scope);
+            $(
+                let func = v8::Function::new(scope, $f).unwrap();
+                let key = v8_str(scope, $name);
+                obj.set(scope, key.into(), func.into());
+            )*
+            obj
+        }
+
+        /// Every native in this module, for the startup snapshot.
+        pub fn external_references() -> Vec<v8::ExternalReference<'static>> {
+            use v8::MapFnTo;
+            vec![$(v8::ExternalReference { function: $f.map_fn_to() }),*]
+        }
+    };
+}
+
+natives! {
+    "encode" => native_encode,
+    "encodeInto" => native_encode_into,
+    "decode" => native_decode,
+    "urlParse" => native_url_parse,
+    "urlSet" => native_url_set,
+    "formParse" => native_form_parse,
+    "formSerialize" => native_form_serialize,
+    "atob" => native_atob,
+    "btoa" => native_btoa,
+    "structuredClone" => native_structured_clone,
+    "randomFill" => native_random_fill,
+    "randomUUID" => native_random_uuid,
+    "console" => native_console,
+    "now" => native_now,
+    "timeOrigin" => native_time_origin,
+}
+
+/// Run `web.js` with the natives object. On the `inspected` worker `console`
+/// also reports to V8's own console, which feeds DevTools.
+pub fn inject_web_globals(scope: &mut v8::HandleScope, inspected: bool) {
+    time_origin();
+
+    let natives = natives_object(scope);
+    let tc = &mut v8::TryCatch::new(scope);
+    let source = v8_str(tc, WEB_JS);
+    let installed = v8::Script::compile(tc, source, None)
+        .and_then(|script| script.run(tc))
+        .and_then(|f| v8::Local::<v8::Function>::try_from(f).ok())
+        .and_then(|f| {
+            let undefined = v8::undefined(tc).into();
+            let inspected = v8::Boolean::new(tc, inspected).into();
+            f.call(tc, undefined, &[natives.into(), inspected])
+        });
+    if installed.is_none() {
+        let msg = tc
+            .message()
+            .map(|m| m.get(tc).to_rust_string_lossy(tc))
+            .unwrap_or("Unknown".to_string());
+        println!(
+            "{} {} {}",
+            blue("[Titan]"),
+            red("Web globals init failed:"),
+            msg
+        );
+    }
+}
+
+/// Copy of the bytes behind an ArrayBuffer or view.
+fn bytes_of(value: v8::Local<v8::Value>) -> Option<Vec<u8>> {
+    if let Ok(view) = v8::Local::<v8::ArrayBufferView>::try_from(value) {
+        let mut buf = vec![0u8; view.byte_length()];
+        view.copy_contents(&mut buf);
+        return Some(buf);
+    }
+    let ab = v8::Local::<v8::ArrayBuffer>::try_from(value).ok()?;
+    let store = ab.get_backing_store();
+    Some(store.iter().map(|b| b.get()).collect())
+}
+
+/// Write `bytes` into a view's memory, from its start. Returns bytes written.
+fn write_into(
+    scope: &mut v8::HandleScope,
+    view: v8::Local<v8::ArrayBufferView>,
+    bytes: &[u8],
+) -> usize {
+    let Some(buffer) = view.buffer(scope) else {
+        return 0;
+    };
+    let store = buffer.get_backing_store();
+    let offset = view.byte_offset();
+    let len = bytes.len().min(view.byte_length());
+    for (cell, b) in store[offset..offset + len].iter().zip(bytes) {
+        cell.set(*b);
+    }
+    len
+}
+
+fn uint8_array<'s>(
+    scope: &mut v8::HandleScope<'s>,
+    bytes: Vec<u8>,
+) -> v8::Local<'s, v8::Uint8Array> {
+    let len = bytes.len();
+    let store = v8::ArrayBuffer::new_backing_store_from_vec(bytes).make_shared();
+    let ab = v8::ArrayBuffer::with_backing_store(scope, &store);
+    v8::Uint8Array::new(scope, ab, 0, len).unwrap()
+}
+
+// --- TextEncoder / TextDecoder ---
+
+/// `encode(string)` → Uint8Array (lone surrogates become U+FFFD)
+fn native_encode(
+    scope: &mut v8::HandleScope,
+    args: v8::FunctionCallbackArguments,
+    mut retval: v8::ReturnValue,
+) {
+    let text = v8_to_string(scope, args.get(0));
+    let arr = uint8_array(scope, text.into_bytes());
+    retval.set(arr.into());
+}
+
+/// `encodeInto(string, Uint8Array)` → `[read (UTF-16 units), written]`
+fn native_encode_into(
+    scope: &mut v8::HandleScope,
+    args: v8::FunctionCallbackArguments,
+    mut retval: v8::ReturnValue,
+) {
+    let text = v8_to_string(scope, args.get(0));
+    let Ok(dest) = v8::Local::<v8::ArrayBufferView>::try_from(args.get(1)) else {
+        throw(scope, "encodeInto() destination must be a Uint8Array");
+        return;
+    };
+
+    // Only whole characters are written
+    let capacity = dest.byte_length();
+    let (mut read, mut written) = (0usize, 0usize);
+    for c in text.chars() {
+        if written + c.len_utf8() > capacity {
+            break;
+        }
+        written += c.len_utf8();
+        read += c.len_utf16();
+    }
+    write_into(scope, dest, &text.as_bytes()[..written]);
+
+    let read = v8::Number::new(scope, read as f64).into();
+    let written = v8::Number::new(scope, written as f64).into();
+    let pair = v8::Array::new_with_elements(scope, &[read, written]);
+    retval.set(pair.into());
+}
+
+/// windows-1252 bytes 0x80..=0x9F (the rest matches Latin-1)
+const WINDOWS_1252: [u16; 32] = [
+    0x20AC, 0x0081, 0x201A, 0x0192, 0x201E, 0x2026, 0x2020, 0x2021, 0x02C6, 0x2030, 0x0160, 0x2039,
+    0x0152, 0x008D, 0x017D, 0x008F, 0x0090, 0x2018, 0x2019, 0x201C, 0x201D, 0x2022, 0x2013, 0x2014,
+    0x02DC, 0x2122, 0x0161, 0x203A, 0x0153, 0x009D, 0x017E, 0x0178,
+];
+
+/// Length of an incomplete UTF-8 sequence at the end of `bytes`.
+fn utf8_tail(bytes: &[u8]) -> usize {
+    for back in 1..=bytes.len().min(3) {
+        let b = bytes[bytes.len() - back];
+        if b & 0xC0 == 0x80 {
+            continue;
+        }
+        let need = match b {
+            0xC0..=0xDF => 2,
+            0xE0..=0xEF => 3,
+            0xF0..=0xF7 => 4,
+            _ => 1,
+        };
+        return if need > back { back } else { 0 };
+    }
+    0
+}
+
+/// `decode(Uint8Array, encoding, fatal, stripBOM, stream)` → `[text, tail]`,
+/// `tail` being the trailing bytes held back for the next streamed chunk.
+/// Returns `undefined` when `fatal` and the input is malformed.
+fn native_decode(
+    scope: &mut v8::HandleScope,
+    args: v8::FunctionCallbackArguments,
+    mut retval: v8::ReturnValue,
+) {
+    let bytes = bytes_of(args.get(0)).unwrap_or_default();
+    let encoding = v8_to_string(scope, args.get(1));
+    let fatal = args.get(2).boolean_value(scope);
+    let strip_bom = args.get(3).boolean_value(scope);
+    let stream = args.get(4).boolean_value(scope);
+
+    let (text, tail) = match encoding.as_str() {
+        "utf-16le" => {
+            let mut tail = if stream { bytes.len() % 2 } else { 0 };
+            let mut units: Vec<u16> = bytes[..bytes.len() - tail]
+                .chunks(2)
+                .map(|c| u16::from_le_bytes([c[0], *c.get(1).unwrap_or(&0)]))
+                .collect();
+            // Keep a high surrogate whose pair is still to come
+            if stream && units.last().is_some_and(|u| (0xD800..0xDC00).contains(u)) {
+                units.pop();
+                tail += 2;
+            }
+            if !stream && bytes.len() % 2 == 1 && fatal {
+                return;
+            }
+            let mut text = match String::from_utf16(&units) {
+                Ok(s) => s,
+                Err(_) if fatal => return,
+                Err(_) => String::from_utf16_lossy(&units),
+            };
+            if !stream && bytes.len() % 2 == 1 {
+                text.push('\u{FFFD}');
+            }
+            if strip_bom && text.starts_with('\u{FEFF}') {
+                text.remove(0);
+            }
+            (text, tail)
+        }
+        "windows-1252" => {
+            let text = bytes
+                .iter()
+                .map(|&b| match b {
+                    0x80..=0x9F => {
+                        char::from_u32(WINDOWS_1252[(b - 0x80) as usize] as u32).unwrap()
+                    }
+                    _ => b as char,
+                })
+                .collect();
+            (text, 0)
+        }
+        _ => {
+            let tail = if stream { utf8_tail(&bytes) } else { 0 };
+            let body = &bytes[..bytes.len() - tail];
+            let body = if strip_bom {
+                body.strip_prefix(b"\xEF\xBB\xBF").unwrap_or(body)
+            } else {
+                body
+            };
+            let text = match std::str::from_utf8(body) {
+                Ok(s) => s.to_string(),
+                Err(_) if fatal => return,
+                Err(_) => String::from_utf8_lossy(body).into_owned(),
+            };
+            (text, tail)
+        }
+    };
+
+    let text = v8_str(scope, &text).into();
+    let tail = v8::Number::new(scope, tail as f64).into();
+    let pair = v8::Array::new_with_elements(scope, &[text, tail]);
+    retval.set(pair.into());
+}
+
+// --- URL / URLSearchParams ---
+
+fn url_object<'s>(scope: &mut v8::HandleScope<'s>, url: &url::Url) -> v8::Local<'s, v8::Object> {
+    use url::quirks;
+
+    let obj = v8::Object::new(scope);
+    let fields = [
+        ("href", quirks::href(url).to_string()),
+        ("origin", quirks::origin(url)),
+        ("protocol", quirks::protocol(url).to_string()),
+        ("username", quirks::username(url).to_string()),
+        ("password", quirks::password(url).to_string()),
+        ("host", quirks::host(url).to_string()),
+        ("hostname", quirks::hostname(url).to_string()),
+        ("port", quirks::port(url).to_string()),
+        ("pathname", quirks::pathname(url).to_string()),
+        ("search", quirks::search(url).to_string()),
+        ("hash", quirks::hash(url).to_string()),
+    ];
+    for (name, value) in fields {
+        let key = v8_str(scope, name);
+        let val = v8_str(scope, &value);
+        obj.set(scope, key.into(), val.into());
+    }
+    obj
+}
+
+/// `urlParse(input, base?)` → components, or `undefined` when invalid
+fn native_url_parse(
+    scope: &mut v8::HandleScope,
+    args: v8::FunctionCallbackArguments,
+    mut retval: v8::ReturnValue,
+) {
+    let input = v8_to_string(scope, args.get(0));
+    let base = if args.get(1).is_null_or_undefined() {
+        None
+    } else {
+        match url::Url::parse(&v8_to_string(scope, args.get(1))) {
+            Ok(base) => Some(base),
+            Err(_) => return,
+        }
+    };
+
+    if let Ok(url) = url::Url::options().base_url(base.as_ref()).parse(&input) {
+        let obj = url_object(scope, &url);
+        retval.set(obj.into());
+    }
+}
+
+/// `urlSet(href, component, value)` → components after the setter ran
+/// (invalid values leave the URL unchanged, as in browsers)
+fn native_url_set(
+    scope: &mut v8::HandleScope,
+    args: v8::FunctionCallbackArguments,
+    mut retval: v8::ReturnValue,
+) {
+    use url::quirks;
+
+    let Ok(mut url) = url::Url::parse(&v8_to_string(scope, args.get(0))) else {
+        return;
+    };
+    let component = v8_to_string(scope, args.get(1));
+    let value = v8_to_string(scope, args.get(2));
+
+    match component.as_str() {
+        "protocol" => {
+            let _ = quirks::set_protocol(&mut url, &value);
+        }
+        "username" => {
+            let _ = quirks::set_username(&mut url, &value);
+        }
+        "password" => {
+            let _ = quirks::set_password(&mut url, &value);
+        }
+        "host" => {
+            let _ = quirks::set_host(&mut url, &value);
+        }
+        "hostname" => {
+            let _ = quirks::set_hostname(&mut url, &value);
+        }
+        "port" => {
+            let _ = quirks::set_port(&mut url, &value);
+        }
+        "pathname" => quirks::set_pathname(&mut url, &value),
+        "search" => quirks::set_search(&mut url, &value),
+        "hash" => quirks::set_hash(&mut url, &value),
+        _ => {}
+    }
+    let obj = url_object(scope, &url);
+    retval.set(obj.into());
+}
+
+/// `formParse("a=1&b=2")` → `[["a", "1"], ["b", "2"]]`
+fn native_form_parse(
+    scope: &mut v8::HandleScope,
+    args: v8::FunctionCallbackArguments,
+    mut retval: v8::ReturnValue,
+) {
+    let input = v8_to_string(scope, args.get(0));
+    let pairs: Vec<v8::Local<v8::Value>> = url::form_urlencoded::parse(input.as_bytes())
+        .map(|(k, v)| {
+            let k = v8_str(scope, &k).into();
+            let v = v8_str(scope, &v).into();
+            v8::Array::new_with_elements(scope, &[k, v]).into()
+        })
+        .collect();
+    let arr = v8::Array::new_with_elements(scope, &pairs);
+    retval.set(arr.into());
+}
+
+/// `formSerialize([[k, v], ...])` → `"k=v&..."`
+fn native_form_serialize(
+    scope: &mut v8::HandleScope,
+    args: v8::FunctionCallbackArguments,
+    mut retval: v8::ReturnValue,
+) {
+    let mut out = url::form_urlencoded::Serializer::new(String::new());
+    if let Ok(list) = v8::Local::<v8::Array>::try_from(args.get(0)) {
+        for i in 0..list.length() {
+            let Some(pair) = list
+                .get_index(scope, i)
+                .and_then(|p| v8::Local::<v8::Array>::try_from(p).ok())
+            else {
+                continue;
+            };
+            let k = pair.get_index(scope, 0).unwrap();
+            let v = pair.get_index(scope, 1).unwrap();
+            let (k, v) = (v8_to_string(scope, k), v8_to_string(scope, v));
+            out.append_pair(&k, &v);
+        }
+    }
+    let s = v8_str(scope, &out.finish());
+    retval.set(s.into());
+}
+
+// --- atob / btoa ---
+
+/// `atob(data)` → binary string, or `undefined` when not valid base64
+fn native_atob(
+    scope: &mut v8::HandleScope,
+    args: v8::FunctionCallbackArguments,
+    mut retval: v8::ReturnValue,
+) {
+    let mut input: String = v8_to_string(scope, args.get(0))
+        .chars()
+        .filter(|c| !matches!(c, '\t' | '\n' | '\x0C' | '\r' | ' '))
+        .collect();
+    // Padding only counts on a whole number of quads (forgiving-base64)
+    if input.len() % 4 == 0 {
+        for _ in 0..2 {
+            if input.ends_with('=') {
+                input.pop();
+            }
+        }
+    }
+    if input.len() % 4 == 1 || input.contains('=') {
+        return;
+    }
+    if let Ok(bytes) = FORGIVING_BASE64.decode(&input) {
+        let binary: String = bytes.iter().map(|&b| b as char).collect();
+        let s = v8_str(scope, &binary);
+        retval.set(s.into());
+    }
+}
+
+/// `btoa(data)` → base64, or `undefined` when a character is above U+00FF
+fn native_btoa(
+    scope: &mut v8::HandleScope,
+    args: v8::FunctionCallbackArguments,
+    mut retval: v8::ReturnValue,
+) {
+    let input = v8_to_string(scope, args.get(0));
+    let bytes: Option<Vec<u8>> = input.chars().map(|c| u8::try_from(c as u32).ok()).collect();
+    if let Some(bytes) = bytes {
+        let s = v8_str(
+            scope,
+            &base64::engine::general_purpose::STANDARD.encode(bytes),
+        );
+        retval.set(s.into());
+    }
+}
+
+// --- structuredClone ---
+
+struct CloneDelegate;
+
+impl v8::ValueSerializerImpl for CloneDelegate {
+    fn throw_data_clone_error<'s>(
+        &self,
+        scope: &mut v8::HandleScope<'s>,
+        message: v8::Local<'s, v8::String>,
+    ) {
+        let error = v8::Exception::type_error(scope, message);
+        scope.throw_exception(error);
+    }
+}
+
+impl v8::ValueDeserializerImpl for CloneDelegate {}
+
+/// `structuredClone(value)` through V8's own serializer (the HTML algorithm)
+fn native_structured_clone(
+    scope: &mut v8::HandleScope,
+    args: v8::FunctionCallbackArguments,
+    mut retval: v8::ReturnValue,
+) {
+    let context = scope.get_current_context();
+
+    let serializer = v8::ValueSerializer::new(scope, Box::new(CloneDelegate));
+    serializer.write_header();
+    if serializer.write_value(context, args.get(0)) != Some(true) {
+        // The delegate already threw
+        return;
+    }
+    let data = serializer.release();
+
+    let deserializer = v8::ValueDeserializer::new(scope, Box::new(CloneDelegate), &data);
+    if deserializer.read_header(context) != Some(true) {
+        throw(scope, "structuredClone() failed");
+        return;
+    }
+    if let Some(value) = deserializer.read_value(context) {
+        retval.set(value);
+    }
+}
+
+// --- crypto ---
+
+/// `randomFill(typedArray)`: fill the view's bytes from the OS CSPRNG
+fn native_random_fill(
+    scope: &mut v8::HandleScope,
+    args: v8::FunctionCallbackArguments,
+    _retval: v8::ReturnValue,
+) {
+    let Ok(view) = v8::Local::<v8::ArrayBufferView>::try_from(args.get(0)) else {
+        throw(scope, "getRandomValues() requires a typed array");
+        return;
+    };
+    let mut bytes = vec![0u8; view.byte_length()];
+    if let Err(e) = getrandom::getrandom(&mut bytes) {
+        throw(scope, &format!("getRandomValues() failed: {}", e));
+        return;
+    }
+    write_into(scope, view, &bytes);
+}
+
+/// `randomUUID()` → RFC 4122 version 4 UUID
+fn native_random_uuid(
+    scope: &mut v8::HandleScope,
+    _args: v8::FunctionCallbackArguments,
+    mut retval: v8::ReturnValue,
+) {
+    let mut b = [0u8; 16];
+    if let Err(e) = getrandom::getrandom(&mut b) {
+        throw(scope, &format!("randomUUID() failed: {}", e));
+        return;
+    }
+    b[6] = (b[6] & 0x0F) | 0x40;
+    b[8] = (b[8] & 0x3F) | 0x80;
+
+    let hex: String = b.iter().map(|x| format!("{:02x}", x)).collect();
+    let uuid = format!(
+        "{}-{}-{}-{}-{}",
+        &hex[0..8],
+        &hex[8..12],
+        &hex[12..16],
+        &hex[16..20],
+        &hex[20..32]
+    );
+    let s = v8_str(scope, &uuid);
+    retval.set(s.into());
+}
+
+// --- console / performance ---
+
+/// `console(level, ...args)`: same output as `t.log`, tagged with the level
+fn native_console(
+    scope: &mut v8::HandleScope,
+    args: v8::FunctionCallbackArguments,
+    _retval: v8::ReturnValue,
+) {
+    let level = v8_to_string(scope, args.get(0));
+    let action_name = current_action(scope);
+    let message = log_parts(scope, &args, 1).join(" ");
+
+    let label = format!("{}({})", level, action_name);
+    let line = match level.as_str() {
+        "error" | "trace" => format!("{}: {}", red(&label), red(&message)),
+        "warn" => format!("{}: {}", yellow(&label), yellow(&message)),
+        _ => gray(&format!(
+            "\x1b[90m{}\x1b[0m\x1b[97m: {}\x1b[0m",
+            label, message
+        )),
+    };
+    println!("{} {}", blue("[Titan]"), line);
+}
+
+/// `performance.now()`: ms since `performance.timeOrigin`, sub-ms precision
+fn native_now(
+    scope: &mut v8::HandleScope,
+    _args: v8::FunctionCallbackArguments,
+    mut retval: v8::ReturnValue,
+) {
+    let ms = time_origin().0.elapsed().as_secs_f64() * 1000.0;
+    retval.set(v8::Number::new(scope, ms).into());
+}
+
+fn native_time_origin(
+    scope: &mut v8::HandleScope,
+    _args: v8::FunctionCallbackArguments,
+    mut retval: v8::ReturnValue,
+) {
+    retval.set(v8::Number::new(scope, time_origin().1).into());
+}
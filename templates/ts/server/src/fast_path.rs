@@ -6,7 +6,7 @@
 //! perform semantic analysis with constant propagation.
 //!
 //! Mechanism:
-//! 1. Parses bundled action files (.jsbundle) with OXC.
+//! 1. Parses bundled action files (.jsbundle / .mjs) with OXC.
 //! 2. Builds semantic data (symbol table, scopes).
 //! 3. Evaluates `t.response.json/text/html()` calls for static constancy.
 //! 4. If all calls produce the same static value, the action is fast-pathed.
@@ -74,7 +74,7 @@ impl FastPathRegistry {
                 }
 
                 let ext = path.extension().and_then(|s| s.to_str()).unwrap_or("");
-                if ext != "js" && ext != "jsbundle" {
+                if ext != "js" && ext != "jsbundle" && ext != "mjs" {
                     continue;
                 }
 
@@ -220,6 +220,9 @@ fn analyze_action_source(source: &str) -> Option<StaticResponse> {
         if let AstKind::CallExpression(call) = node.kind() {
             if let Some(method) = detect_response_method(call) {
                 analyze_response_call(call, method, semantic, &mut responses, &mut has_dynamic);
+            } else if matches!(response_member(call), Some("setCookie" | "clearCookie")) {
+                // Cookies are attached to the response at runtime
+                has_dynamic = true;
             }
         }
     }
@@ -234,16 +237,17 @@ fn analyze_action_source(source: &str) -> Option<StaticResponse> {
 /// Detect if a CallExpression is `t.response.json(...)`, `t.response.text(...)`,
 /// or `t.response.html(...)`. Returns the method name if matched.
 fn detect_response_method<'a>(call: &CallExpression<'a>) -> Option<&'a str> {
-    // Callee must be: t.response.<method>
+    response_member(call).filter(|method| matches!(*method, "json" | "text" | "html"))
+}
+
+/// Returns `<method>` if the callee of a CallExpression is `t.response.<method>`.
+fn response_member<'a>(call: &CallExpression<'a>) -> Option<&'a str> {
     let outer = match &call.callee {
         Expression::StaticMemberExpression(m) => m.as_ref(),
         _ => return None,
     };
 
     let method = outer.property.name.as_str();
-    if method != "json" && method != "text" && method != "html" {
-        return None;
-    }
 
     let inner = match &outer.object {
         Expression::StaticMemberExpression(m) => m.as_ref(),
@@ -288,6 +292,12 @@ fn analyze_response_call<'a>(
         arg => arg.as_expression(),
     });
 
+    // A third argument (extra headers) is merged at runtime
+    if call.arguments.len() > 2 {
+        *has_dynamic = true;
+        return;
+    }
+
     // Evaluate the body statically
     let body_value = match eval_static(body_expr, semantic, 0) {
         Some(v) => v,
@@ -299,8 +309,8 @@ fn analyze_response_call<'a>(
 
     // Evaluate options if present
     let options = if let Some(opts) = opts_expr {
-        match eval_static(opts, semantic, 0) {
-            Some(v) => extract_response_options(&v),
+        match eval_static(opts, semantic, 0).and_then(|v| extract_response_options(&v)) {
+            Some(options) => options,
             None => {
                 *has_dynamic = true;
                 return;
@@ -686,16 +696,26 @@ fn number_to_json(v: f64) -> Option<serde_json::Value> {
 }
 
 /// Extract ResponseOptions (status + headers) from a serde_json::Value.
-fn extract_response_options(val: &serde_json::Value) -> ResponseOptions {
+/// Returns None for options that cannot be precomputed: cookies, multi-value
+/// (array) headers or anything other than a status / options object.
+fn extract_response_options(val: &serde_json::Value) -> Option<ResponseOptions> {
     let mut opts = ResponseOptions {
         status: 200,
         headers: Vec::new(),
     };
 
-    let obj = match val.as_object() {
-        Some(o) => o,
-        None => return opts,
-    };
+    // Positional form: t.response.json(body, 201)
+    if let Some(n) = val.as_u64() {
+        if n >= 100 && n <= 599 {
+            opts.status = n as u16;
+        }
+        return Some(opts);
+    }
+
+    let obj = val.as_object()?;
+    if obj.contains_key("cookies") {
+        return None;
+    }
 
     if let Some(status) = obj.get("status") {
         if let Some(n) = status.as_u64() {
@@ -706,14 +726,66 @@ fn extract_response_options(val: &serde_json::Value) -> ResponseOptions {
     }
 
     if let Some(headers) = obj.get("headers") {
-        if let Some(h_obj) = headers.as_object() {
-            for (key, val) in h_obj {
-                if let Some(v_str) = val.as_str() {
-                    opts.headers.push((key.clone(), v_str.to_string()));
-                }
-            }
+        for (key, val) in headers.as_object()? {
+            opts.headers.push((key.clone(), val.as_str()?.to_string()));
         }
     }
 
-    opts
+    Some(opts)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn analyze(body: &str) -> Option<StaticResponse> {
+        analyze_action_source(&format!("export default function (req) {{ {body} }}"))
+    }
+
+    #[test]
+    fn constant_json_is_static() {
+        let res = analyze(
+            r#"return t.response.json({ ok: true }, { status: 201, headers: { "x-a": "1" } });"#,
+        )
+        .expect("static");
+        assert_eq!(&res.body[..], br#"{"ok":true}"#);
+        assert_eq!(res.status, 201);
+        assert_eq!(
+            res.extra_headers,
+            vec![("x-a".to_string(), "1".to_string())]
+        );
+    }
+
+    #[test]
+    fn positional_status_is_static() {
+        assert_eq!(
+            analyze(r#"return t.response.text("hi", 404);"#)
+                .unwrap()
+                .status,
+            404
+        );
+    }
+
+    #[test]
+    fn cookies_are_dynamic() {
+        assert!(
+            analyze(r#"return t.response.json({}, { cookies: [{ name: "a", value: "b" }] });"#)
+                .is_none()
+        );
+        assert!(
+            analyze(r#"return t.response.setCookie(t.response.json({}), "a", "b");"#).is_none()
+        );
+        assert!(analyze(r#"return t.response.clearCookie(t.response.json({}), "a");"#).is_none());
+    }
+
+    #[test]
+    fn array_and_extra_headers_are_dynamic() {
+        assert!(
+            analyze(
+                r#"return t.response.json({}, { headers: { "set-cookie": ["a=1", "b=2"] } });"#
+            )
+            .is_none()
+        );
+        assert!(analyze(r#"return t.response.json({}, 200, { "x-a": "1" });"#).is_none());
+    }
 }
@@ -0,0 +1,488 @@
+//! Background Jobs (`t.jobs.enqueue`)
+//!
+//! Actions defer work with `t.jobs.enqueue(action, payload, { delay, retries, backoff })`.
+//! Jobs are kept in a Rust-side queue and run on the regular worker pool.
+//!
+//! 1. Each run is a synthetic `JOB /__jobs/<action>` request: the payload is
+//!    the JSON body (`req.body`), attempt info is on `req.job`.
+//! 2. At most `__config.jobs_concurrency` jobs run at once (default 4).
+//! 3. A failed run (thrown error, timeout, overload) is retried up to
+//!    `retries` times, `backoff` apart (fixed or exponential). A job that
+//!    runs out of retries moves to the dead-letter list.
+//! 4. `__config.jobs_store` (a path, or `true` for `.titan/jobs.json`)
+//!    persists pending and dead jobs, so they survive restarts. Jobs that
+//!    were running at shutdown run again (at-least-once).
+//! 5. `GET /__titan/admin/jobs` shows queue counters and the dead-letter list.
+//! 6. Enqueueing is replay-safe: a sync action replayed after a drift gets
+//!    the job ID of its first run instead of enqueueing the job again.
+
+use bytes::Bytes;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use smallvec::SmallVec;
+use std::collections::{HashMap, VecDeque};
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, OnceLock};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+use tokio::sync::{Notify, OwnedSemaphorePermit, Semaphore};
+
+use crate::reload::RouteStore;
+use crate::runtime::{ExecuteError, RuntimeManager};
+use crate::utils::{blue, gray, green, red, yellow};
+
+/// Oldest dead jobs are dropped beyond this
+const DEAD_LETTER_MAX: usize = 1000;
+/// Upper bound for exponential backoff
+const MAX_BACKOFF_MS: u64 = 60 * 60 * 1000;
+/// Upper bound for `delay` (`t.jobs.enqueue` rejects longer ones)
+const MAX_DELAY_MS: u64 = 24 * 60 * 60 * 1000;
+
+static QUEUE: OnceLock<Arc<JobQueue>> = OnceLock::new();
+static COUNTER: AtomicU64 = AtomicU64::new(0);
+
+#[derive(Clone, Copy, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "lowercase")]
+pub enum Backoff {
+    Fixed { delay_ms: u64 },
+    Exponential { delay_ms: u64 },
+}
+
+impl Backoff {
+    /// `type` from `{ backoff: { type, delay } }`; unknown types are exponential.
+    pub fn new(kind: &str, delay_ms: u64) -> Self {
+        match kind {
+            "fixed" => Backoff::Fixed { delay_ms },
+            _ => Backoff::Exponential { delay_ms },
+        }
+    }
+
+    /// Wait before retry number `attempt` (1-based).
+    fn delay(&self, attempt: u32) -> Duration {
+        let ms = match *self {
+            Backoff::Fixed { delay_ms } => delay_ms,
+            Backoff::Exponential { delay_ms } => {
+                delay_ms.saturating_mul(1u64 << attempt.saturating_sub(1).min(32))
+            }
+        };
+        Duration::from_millis(ms.min(MAX_BACKOFF_MS))
+    }
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Job {
+    pub id: String,
+    pub action: String,
+    pub payload: Value,
+    pub retries: u32,
+    pub backoff: Backoff,
+    /// Failed runs so far
+    pub attempts: u32,
+    /// Unix ms
+    pub enqueued_at: u64,
+    pub run_at: u64,
+    #[serde(default)]
+    pub last_error: Option<String>,
+    #[serde(default)]
+    pub failed_at: Option<u64>,
+}
+
+/// On-disk layout of `jobs_store`.
+#[derive(Default, Serialize, Deserialize)]
+struct Stored {
+    pending: Vec<Job>,
+    dead: VecDeque<Job>,
+}
+
+#[derive(Default)]
+struct State {
+    pending: Vec<Job>,
+    running: HashMap<String, Job>,
+    dead: VecDeque<Job>,
+    completed: u64,
+    retried: u64,
+    failed: u64,
+}
+
+pub struct JobQueue {
+    state: Mutex<State>,
+    concurrency: usize,
+    store: Option<PathBuf>,
+    /// New or rescheduled job: re-check what is due
+    wake: Notify,
+    /// State changed: write the store
+    dirty: Notify,
+}
+
+fn now_ms() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or(0)
+}
+
+/// When a job waiting `delay` from `now` is due (delay capped at `MAX_DELAY_MS`).
+fn run_at(now: u64, delay: Duration) -> u64 {
+    let delay_ms = u64::try_from(delay.as_millis()).unwrap_or(u64::MAX);
+    now.saturating_add(delay_ms.min(MAX_DELAY_MS))
+}
+
+/// Queue a job from a worker (`t.jobs.enqueue`). Returns the job ID.
+pub fn enqueue(
+    action: String,
+    payload: Value,
+    delay: Duration,
+    retries: u32,
+    backoff: Backoff,
+) -> Result<String, String> {
+    let queue = QUEUE.get().ok_or("Job queue is not running")?;
+
+    let now = now_ms();
+    let id = format!("{:x}-{:x}", now, COUNTER.fetch_add(1, Ordering::Relaxed));
+    let job = Job {
+        id: id.clone(),
+        action,
+        payload,
+        retries,
+        backoff,
+        attempts: 0,
+        enqueued_at: now,
+        run_at: run_at(now, delay),
+        last_error: None,
+        failed_at: None,
+    };
+
+    queue.state.lock().unwrap().pending.push(job);
+    queue.wake.notify_one();
+    queue.dirty.notify_one();
+    Ok(id)
+}
+
+impl JobQueue {
+    /// Build the queue from `__config`, restore persisted jobs and start
+    /// dispatching.
+    pub fn start(
+        json: &Value,
+        root: &std::path::Path,
+        routes: Arc<RouteStore>,
+        runtime: Arc<RuntimeManager>,
+        timeout: Option<Duration>,
+    ) -> Arc<Self> {
+        let concurrency = json["__config"]["jobs_concurrency"]
+            .as_u64()
+            .filter(|n| *n > 0)
+            .unwrap_or(4) as usize;
+
+        let store = match &json["__config"]["jobs_store"] {
+            Value::Bool(true) => Some(root.join(".titan").join("jobs.json")),
+            Value::String(path) if !path.is_empty() => Some(root.join(path)),
+            _ => None,
+        };
+
+        let mut state = State::default();
+        if let Some(path) = &store {
+            match std::fs::read(path) {
+                Ok(data) => match serde_json::from_slice::<Stored>(&data) {
+                    Ok(stored) => {
+                        if !stored.pending.is_empty() {
+                            println!(
+                                "{} {} job(s) restored from {}",
+                                blue("[Titan]"),
+                                stored.pending.len(),
+                                path.display()
+                            );
+                        }
+                        state.pending = stored.pending;
+                        state.dead = stored.dead;
+                    }
+                    Err(e) => println!(
+                        "{} {} {} ({})",
+                        blue("[Titan]"),
+                        red("Ignoring unreadable job store"),
+                        path.display(),
+                        e
+                    ),
+                },
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+                Err(e) => println!(
+                    "{} {} {} ({})",
+                    blue("[Titan]"),
+                    red("Cannot read job store"),
+                    path.display(),
+                    e
+                ),
+            }
+        }
+
+        let queue = Arc::new(Self {
+            state: Mutex::new(state),
+            concurrency,
+            store,
+            wake: Notify::new(),
+            dirty: Notify::new(),
+        });
+        let _ = QUEUE.set(queue.clone());
+
+        tokio::spawn(queue.clone().dispatch(routes, runtime, timeout));
+        if queue.store.is_some() {
+            tokio::spawn(queue.clone().persist());
+        }
+        queue
+    }
+
+    /// Hand due jobs to the worker pool, `concurrency` at a time.
+    async fn dispatch(
+        self: Arc<Self>,
+        routes: Arc<RouteStore>,
+        runtime: Arc<RuntimeManager>,
+        timeout: Option<Duration>,
+    ) {
+        let slots = Arc::new(Semaphore::new(self.concurrency));
+        loop {
+            let Ok(permit) = slots.clone().acquire_owned().await else {
+                return;
+            };
+
+            let job = loop {
+                let wait = {
+                    let mut state = self.state.lock().unwrap();
+                    let now = now_ms();
+                    let next = state
+                        .pending
+                        .iter()
+                        .enumerate()
+                        .min_by_key(|(_, j)| j.run_at)
+                        .map(|(i, j)| (i, j.run_at));
+                    match next {
+                        Some((i, run_at)) if run_at <= now => {
+                            let job = state.pending.swap_remove(i);
+                            state.running.insert(job.id.clone(), job.clone());
+                            break job;
+                        }
+                        Some((_, run_at)) => Some(Duration::from_millis(run_at - now)),
+                        None => None,
+                    }
+                };
+                match wait {
+                    Some(d) => {
+                        let _ = tokio::time::timeout(d, self.wake.notified()).await;
+                    }
+                    None => self.wake.notified().await,
+                }
+            };
+
+            tokio::spawn(
+                self.clone()
+                    .run(job, permit, routes.clone(), runtime.clone(), timeout),
+            );
+        }
+    }
+
+    async fn run(
+        self: Arc<Self>,
+        mut job: Job,
+        _permit: OwnedSemaphorePermit,
+        routes: Arc<RouteStore>,
+        runtime: Arc<RuntimeManager>,
+        timeout: Option<Duration>,
+    ) {
+        let start = Instant::now();
+        let extra = serde_json::json!({
+            "job": {
+                "id": job.id,
+                "attempt": job.attempts + 1,
+                "retries": job.retries,
+                "enqueuedAt": job.enqueued_at,
+            }
+        });
+        let body = serde_json::to_vec(&job.payload).unwrap_or_default();
+        let mut headers = SmallVec::new();
+        headers.push(("content-type".to_string(), "application/json".to_string()));
+
+        let exec = runtime.execute(
+            job.action.clone(),
+            "JOB".to_string(),
+            format!("/__jobs/{}", job.action),
+            Some(Bytes::from(body)),
+            headers,
+            SmallVec::new(),
+            SmallVec::new(),
+            Some(extra),
+            routes.current().isolation_for(&job.action),
+        );
+        let outcome = match timeout {
+            Some(limit) => tokio::time::timeout(limit, exec).await.ok(),
+            None => Some(exec.await),
+        };
+
+        let error = match outcome {
+            Some(Ok(res)) => res.json.get("error").map(|e| {
+                e.as_str()
+                    .map(str::to_string)
+                    .unwrap_or_else(|| e.to_string())
+            }),
+            Some(Err(ExecuteError::Overloaded { .. })) => {
+                Some("worker pool overloaded".to_string())
+            }
+            Some(Err(ExecuteError::Failed(e))) => Some(e),
+            None => Some("timed out".to_string()),
+        };
+        let elapsed = gray(&format!("in {:.2?}", start.elapsed()));
+        let label = format!("job {} ({})", job.action, job.id);
+
+        {
+            let mut state = self.state.lock().unwrap();
+            state.running.remove(&job.id);
+
+            match error {
+                None => {
+                    state.completed += 1;
+                    println!("{} {} {}", blue("[Titan]"), green(&label), elapsed);
+                }
+                Some(e) if job.attempts < job.retries => {
+                    job.attempts += 1;
+                    let delay = job.backoff.delay(job.attempts);
+                    job.run_at = run_at(now_ms(), delay);
+                    job.last_error = Some(e.clone());
+                    println!(
+                        "{} {} {} {}",
+                        blue("[Titan]"),
+                        yellow(&label),
+                        yellow(&format!(
+                            "→ {} (retry {}/{} in {:.2?})",
+                            e, job.attempts, job.retries, delay
+                        )),
+                        elapsed
+                    );
+                    state.retried += 1;
+                    state.pending.push(job);
+                }
+                Some(e) => {
+                    job.attempts += 1;
+                    job.last_error = Some(e.clone());
+                    job.failed_at = Some(now_ms());
+                    println!(
+                        "{} {} {} {}",
+                        blue("[Titan]"),
+                        red(&label),
+                        red(&format!("→ {} (moved to dead-letter list)", e)),
+                        elapsed
+                    );
+                    state.failed += 1;
+                    state.dead.push_back(job);
+                    if state.dead.len() > DEAD_LETTER_MAX {
+                        state.dead.pop_front();
+                    }
+                }
+            }
+        }
+
+        self.wake.notify_one();
+        self.dirty.notify_one();
+    }
+
+    /// Write the store whenever the queue changes (bursts coalesce).
+    async fn persist(self: Arc<Self>) {
+        let Some(path) = self.store.clone() else {
+            return;
+        };
+        if let Some(dir) = path.parent() {
+            let _ = tokio::fs::create_dir_all(dir).await;
+        }
+        let tmp = path.with_extension("tmp");
+
+        loop {
+            self.dirty.notified().await;
+
+            let data = {
+                let state = self.state.lock().unwrap();
+                // Running jobs are stored as pending: a crash re-runs them
+                let stored = Stored {
+                    pending: state
+                        .pending
+                        .iter()
+                        .chain(state.running.values())
+                        .cloned()
+                        .collect(),
+                    dead: state.dead.clone(),
+                };
+                serde_json::to_vec(&stored).unwrap_or_default()
+            };
+
+            // Write then rename, so a crash mid-write keeps the previous store
+            let written = match tokio::fs::write(&tmp, &data).await {
+                Ok(()) => tokio::fs::rename(&tmp, &path).await,
+                Err(e) => Err(e),
+            };
+            if let Err(e) = written {
+                println!(
+                    "{} {} {} ({})",
+                    blue("[Titan]"),
+                    red("Cannot write job store"),
+                    path.display(),
+                    e
+                );
+            }
+        }
+    }
+
+    /// Counters and the dead-letter list (admin endpoint).
+    pub fn status(&self) -> Value {
+        let state = self.state.lock().unwrap();
+        serde_json::json!({
+            "concurrency": self.concurrency,
+            "persistent": self.store.is_some(),
+            "pending": state.pending.len(),
+            "running": state.running.len(),
+            "completed": state.completed,
+            "retried": state.retried,
+            "failed": state.failed,
+            "dead": state.dead,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fixed_backoff_is_constant() {
+        let backoff = Backoff::new("fixed", 250);
+        assert_eq!(backoff.delay(1), Duration::from_millis(250));
+        assert_eq!(backoff.delay(5), Duration::from_millis(250));
+    }
+
+    #[test]
+    fn exponential_backoff_doubles() {
+        let backoff = Backoff::new("exponential", 100);
+        assert_eq!(backoff.delay(1), Duration::from_millis(100));
+        assert_eq!(backoff.delay(2), Duration::from_millis(200));
+        assert_eq!(backoff.delay(4), Duration::from_millis(800));
+    }
+
+    #[test]
+    fn backoff_is_capped() {
+        let max = Duration::from_millis(MAX_BACKOFF_MS);
+        assert_eq!(Backoff::new("exponential", 1000).delay(40), max);
+        assert_eq!(Backoff::new("exponential", u64::MAX).delay(2), max);
+        assert_eq!(Backoff::new("fixed", u64::MAX).delay(1), max);
+    }
+
+    #[test]
+    fn huge_delay_is_capped() {
+        let max = 1_000 + MAX_DELAY_MS;
+        assert_eq!(run_at(1_000, Duration::from_millis(u64::MAX)), max);
+        assert_eq!(run_at(1_000, Duration::MAX), max);
+        assert_eq!(run_at(u64::MAX, Duration::from_secs(1)), u64::MAX);
+        assert_eq!(run_at(1_000, Duration::from_millis(500)), 1_500);
+    }
+
+    #[test]
+    fn unknown_backoff_is_exponential() {
+        assert!(matches!(
+            Backoff::new("linear", 10),
+            Backoff::Exponential { delay_ms: 10 }
+        ));
+    }
+}
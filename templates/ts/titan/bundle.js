@@ -86,37 +86,23 @@ async function bundleJs(actionsDir, outDir) {
   for (const file of files) {
     const actionName = path.basename(file, path.extname(file));
     const entry = path.join(actionsDir, file);
-    const outfile = path.join(outDir, actionName + ".jsbundle");
+    // Loaded by the server as an ES module. The action is its default export;
+    // an export named after the action is only a fallback for older bundles.
+    const outfile = path.join(outDir, actionName + ".mjs");
 
     await esbuild.build({
       entryPoints: [entry],
       outfile,
       bundle: true,
-      format: "iife",
-      globalName: "__titan_exports",
+      format: "esm",
       platform: "node",          // important for npm libs
-      target: "es2020",
+      target: "es2022",          // top-level await
+      sourcemap: true,           // original .ts positions in stack traces
       logLevel: "silent",
       plugins: [titanNodeCompatPlugin],
 
       banner: {
         js: "var Titan = t;"
-      },
-
-      footer: {
-        js: `
-(function () {
-  const fn =
-    __titan_exports["${actionName}"] ||
-    __titan_exports.default;
-
-  if (typeof fn !== "function") {
-    throw new Error("[Titan] Action '${actionName}' not found or not a function");
-  }
-
-  globalThis["${actionName}"] = globalThis.defineAction(fn);
-})();
-`
       }
     });
   }
@@ -333,8 +333,11 @@ function startTypeChecker() {
             }
             if (line.includes("Found 0 errors")) {
                 isTsHealthy = true;
-                // TS is happy, so we rebuild and restart (or start) the server
-                rebuild().then(startRustServer);
+                // TS is happy, so we rebuild. A running server hot-reloads the
+                // new bundles itself; otherwise start it.
+                rebuild().then(() => {
+                    if (!serverProcess) return startRustServer();
+                });
 
             } else if (line.includes("error TS")) {
                 isTsHealthy = false;
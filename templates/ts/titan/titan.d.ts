@@ -1,14 +1,26 @@
 // -- Module Definitions (for imports from "titan") --
 
+export interface RouteOptions {
+    /** "context" runs each request in a fresh V8 context; default from `__config.isolation` ("shared") */
+    isolation?: "shared" | "context";
+}
+
+export interface CronOptions {
+    /** IANA zone (e.g. "Europe/Berlin"); default from `__config.cron_timezone` (UTC) */
+    timezone?: string;
+}
+
 export interface RouteHandler {
     reply(value: any): void;
-    action(name: string): void;
+    action(name: string, options?: RouteOptions): void;
 }
 
 export interface TitanBuilder {
     get(route: string): RouteHandler;
     post(route: string): RouteHandler;
     log(module: string, msg: string): void;
+    config(options: Record<string, any>): void;
+    cron(schedule: string, action: string, options?: CronOptions): void;
     start(port?: number, msg?: string, threads?: number): Promise<void>;
 }
 
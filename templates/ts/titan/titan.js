@@ -9,6 +9,8 @@ const green = (t) => `\x1b[32m${t}\x1b[0m`;
 const routes = {};
 const dynamicRoutes = {};
 const actionMap = {};
+const config = {};
+const cronJobs = [];
 
 function addRoute(method, route) {
     const key = `${method.toUpperCase()}:${route}`;
@@ -21,18 +23,25 @@ function addRoute(method, route) {
             };
         },
 
-        action(name) {
+        /**
+         * @param options.isolation "context" runs each request in a fresh V8
+         *   context (no global state shared with other requests; slower)
+         */
+        action(name, options = {}) {
+            const isolation = options.isolation;
             if (route.includes(":")) {
                 if (!dynamicRoutes[method]) dynamicRoutes[method] = [];
                 dynamicRoutes[method].push({
                     method: method.toUpperCase(),
                     pattern: route,
-                    action: name
+                    action: name,
+                    isolation
                 });
             } else {
                 routes[key] = {
                     type: "action",
-                    value: name
+                    value: name,
+                    isolation
                 };
                 actionMap[key] = name;
             }
@@ -68,6 +77,23 @@ const t = {
         return addRoute("POST", route);
     },
 
+    /**
+     * Extra server settings merged into routes.json `__config`
+     * (e.g. `errors`, `request_timeout_ms`)
+     */
+    config(options) {
+        Object.assign(config, options);
+    },
+
+    /**
+     * Run an action on a schedule (5-field crontab, or 6/7 fields with
+     * seconds/years). `options.timezone` defaults to `__config.cron_timezone`
+     * (else UTC). Runs never overlap; the action sees `req.cron`.
+     */
+    cron(schedule, action, options = {}) {
+        cronJobs.push({ schedule, action, timezone: options.timezone });
+    },
+
     log(module, msg) {
         console.log(`[\x1b[35m${module}\x1b[0m] ${msg}`);
     },
@@ -96,9 +122,10 @@ const t = {
                 routesPath,
                 JSON.stringify(
                     {
-                        __config: { port, threads, stack_mb },
+                        __config: { ...config, port, threads, stack_mb },
                         routes,
-                        __dynamic_routes: Object.values(dynamicRoutes).flat()
+                        __dynamic_routes: Object.values(dynamicRoutes).flat(),
+                        __cron: cronJobs
                     },
                     null,
                     2
@@ -32,6 +32,7 @@ vi.mock("path", () => ({
 // Mock de url
 vi.mock("url", () => ({
     fileURLToPath: vi.fn(() => "/mocked/project/path/index.js"),
+    pathToFileURL: vi.fn((p) => ({ href: p })),
 }));
 
 // Mock de fs
@@ -576,6 +577,24 @@ describe("cli.js (Titan CLI)", () => {
 
             expect(fs.mkdirSync).toHaveBeenCalled();
         });
+
+        it("should pick up .mjs and .jsbundle action bundles", async () => {
+            vi.mocked(fs.existsSync).mockImplementation((p) => String(p).includes("app/app.js"));
+            vi.mocked(fs.readdirSync).mockReturnValueOnce([
+                "hello.mjs",
+                "legacy.jsbundle",
+                "hello.mjs.v8cache",
+                "notes.txt",
+            ]);
+            vi.doMock(`${root}/titan/bundle.js`, () => ({ bundle: vi.fn() }));
+
+            await buildProd();
+
+            expect(console.log).toHaveBeenCalledWith(cyan("→ Found action bundle: hello.mjs"));
+            expect(console.log).toHaveBeenCalledWith(cyan("→ Found action bundle: legacy.jsbundle"));
+            expect(console.log).not.toHaveBeenCalledWith(cyan("→ Found action bundle: hello.mjs.v8cache"));
+            expect(console.log).not.toHaveBeenCalledWith(yellow("⚠ Warning: No JS or Rust actions found."));
+        });
     });
 
     describe("startProd()", () => {
@@ -0,0 +1,114 @@
+import { describe, it, expect, vi } from "vitest";
+import fs from "fs";
+import vm from "vm";
+import { fileURLToPath } from "url";
+
+const CORE_PATH = fileURLToPath(
+    new URL("../templates/js/server/src/extensions/titan_core.js", import.meta.url)
+);
+
+// Evaluate titan_core.js in a fresh context with the natives it calls stubbed
+function loadCore() {
+    const t = {
+        _finish_request: vi.fn(),
+        _drift_call: vi.fn(),
+        _jobs_enqueue: vi.fn(() => "job-1"),
+    };
+    const warn = vi.fn();
+    const context = vm.createContext({
+        t,
+        console: { log: () => { }, warn },
+        TextDecoder,
+        URLSearchParams,
+    });
+    vm.runInContext(fs.readFileSync(CORE_PATH, "utf8"), context);
+    return { t, warn, defineAction: context.defineAction };
+}
+
+const flush = () => new Promise((resolve) => setTimeout(resolve, 0));
+
+const request = (id) => ({ __titan_request_id: id });
+
+describe("titan_core.js (defineAction)", () => {
+    it("should use promise drift for async functions from the first request", () => {
+        const { defineAction } = loadCore();
+        const action = defineAction(async () => ({ ok: true }));
+
+        const req = request(1);
+        action(req);
+        expect(req.__titan_async).toBe(true);
+    });
+
+    it("should keep the replay model for sync functions", async () => {
+        const { t, warn, defineAction } = loadCore();
+        const action = defineAction(() => ({ ok: true }));
+
+        const first = request(1);
+        action(first);
+        await flush();
+        const second = request(2);
+        action(second);
+
+        expect(first.__titan_async).toBe(false);
+        expect(second.__titan_async).toBe(false);
+        expect(t._finish_request).toHaveBeenCalledWith(1, { ok: true });
+        expect(warn).not.toHaveBeenCalled();
+    });
+
+    it("should switch promise-returning functions to promise drift once one settles", async () => {
+        const { t, warn, defineAction } = loadCore();
+        // What a transpiled `async function` looks like at runtime
+        const action = defineAction(function list() {
+            return Promise.resolve({ items: [] });
+        });
+
+        const first = request(1);
+        action(first);
+        expect(first.__titan_async).toBe(false);
+
+        await flush();
+        expect(t._finish_request).toHaveBeenCalledWith(1, { items: [] });
+        expect(warn).toHaveBeenCalledTimes(1);
+        expect(warn.mock.calls[0][0]).toContain("'list'");
+
+        const second = request(2);
+        action(second);
+        await flush();
+        expect(second.__titan_async).toBe(true);
+        expect(warn).toHaveBeenCalledTimes(1);
+    });
+
+    it("should not switch modes while a promise-returning action is suspended", async () => {
+        const { t, warn, defineAction } = loadCore();
+        const action = defineAction(() => Promise.reject(new Error("__SUSPEND__")));
+
+        action(request(1));
+        await flush();
+        const replay = request(1);
+        action(replay);
+
+        expect(replay.__titan_async).toBe(false);
+        expect(t._finish_request).not.toHaveBeenCalled();
+        expect(warn).not.toHaveBeenCalled();
+    });
+});
+
+describe("titan_core.js (t.jobs.enqueue)", () => {
+    it("should pass a delay within the cap to the queue", () => {
+        const { t } = loadCore();
+
+        expect(t.jobs.enqueue("report", { id: 1 }, { delay: 86400000 })).toBe("job-1");
+        expect(t._jobs_enqueue).toHaveBeenCalledWith(
+            "report", '{"id":1}', 86400000, 0, "exponential", 1000
+        );
+    });
+
+    it("should reject delays above 24h, negative or not finite", () => {
+        const { t } = loadCore();
+
+        for (const delay of [1e300, 86400001, -1, Infinity, NaN]) {
+            expect(() => t.jobs.enqueue("report", null, { delay })).toThrow(/delay must be between/);
+        }
+        expect(t._jobs_enqueue).not.toHaveBeenCalled();
+    });
+});
@@ -0,0 +1,267 @@
+//! Admin Endpoints
+//!
+//! Mounted under `/__titan/admin`. Access rules:
+//! - If `TITAN_ADMIN_TOKEN` is set, the request must carry a matching
+//!   `x-titan-admin-token` header (works in any mode).
+//! - Otherwise the endpoints are only reachable in dev mode.
+//!
+//! Endpoints:
+//! - `POST /reload` — reload routes.json and action bundles.
+//! - `GET /workers` — worker pool size and bounds, in-flight load and
+//!   crash/respawn counts.
+//! - `GET /cron` — `__cron` jobs with next run and last-run status.
+//! - `GET /jobs` — background job counters and the dead-letter list.
+//! - `POST /profile/start` — start the V8 CPU profiler (`interval_us`
+//!   sets the sampling interval).
+//! - `POST /profile/stop` — stop it and collect the `.cpuprofile`.
+//! - `POST /heap/snapshot` — take a `.heapsnapshot`.
+//! - `GET /heap` — heap statistics per isolate.
+//!
+//! Diagnostics act on every worker, or on one with `?worker=N`. With a
+//! single worker, profiles and snapshots are the response body (a download);
+//! for all workers they are written to `.titan/diagnostics/` under the
+//! project root and the response lists the files.
+
+use axum::{
+    Router,
+    extract::{Query, State},
+    http::{HeaderMap, StatusCode, header},
+    response::{IntoResponse, Json, Response},
+    routing::{get, post},
+};
+use serde::Deserialize;
+use serde_json::{Value, json};
+
+use crate::AppState;
+use crate::extensions::PROJECT_ROOT;
+use crate::extensions::diagnostics::Diagnostic;
+use crate::reload;
+
+#[derive(Deserialize)]
+struct DiagnoseQuery {
+    worker: Option<usize>,
+    interval_us: Option<u32>,
+}
+
+pub fn router() -> Router<AppState> {
+    Router::new()
+        .route("/reload", post(reload_handler))
+        .route("/workers", get(workers_handler))
+        .route("/cron", get(cron_handler))
+        .route("/jobs", get(jobs_handler))
+        .route("/profile/start", post(profile_start_handler))
+        .route("/profile/stop", post(profile_stop_handler))
+        .route("/heap/snapshot", post(heap_snapshot_handler))
+        .route("/heap", get(heap_handler))
+}
+
+/// Returns `Some(response)` when the caller is NOT allowed in.
+pub fn deny(state: &AppState, headers: &HeaderMap) -> Option<Response> {
+    match std::env::var("TITAN_ADMIN_TOKEN") {
+        Ok(token) if !token.is_empty() => {
+            let given = headers
+                .get("x-titan-admin-token")
+                .and_then(|v| v.to_str().ok())
+                .unwrap_or("");
+            if given == token {
+                None
+            } else {
+                Some((StatusCode::UNAUTHORIZED, "Unauthorized").into_response())
+            }
+        }
+        _ if !state.production_mode => None,
+        _ => Some((StatusCode::NOT_FOUND, "Not Found").into_response()),
+    }
+}
+
+async fn reload_handler(State(state): State<AppState>, headers: HeaderMap) -> Response {
+    if let Some(resp) = deny(&state, &headers) {
+        return resp;
+    }
+
+    match reload::reload_all(&state.routes, &state.runtime).await {
+        Ok(()) => Json(serde_json::json!({ "reloaded": true })).into_response(),
+        Err(e) => (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(serde_json::json!({ "reloaded": false, "error": e })),
+        )
+            .into_response(),
+    }
+}
+
+async fn workers_handler(State(state): State<AppState>, headers: HeaderMap) -> Response {
+    if let Some(resp) = deny(&state, &headers) {
+        return resp;
+    }
+
+    let restarts = state.runtime.worker_restarts();
+    let (min, max) = state.runtime.pool_bounds();
+    Json(serde_json::json!({
+        "workers": restarts.len(),
+        "workers_min": min,
+        "workers_max": max,
+        "in_flight_per_worker": state.runtime.worker_loads(),
+        "restarts": restarts.iter().sum::<u64>(),
+        "restarts_per_worker": restarts,
+    }))
+    .into_response()
+}
+
+async fn cron_handler(State(state): State<AppState>, headers: HeaderMap) -> Response {
+    if let Some(resp) = deny(&state, &headers) {
+        return resp;
+    }
+
+    Json(state.cron.status()).into_response()
+}
+
+async fn jobs_handler(State(state): State<AppState>, headers: HeaderMap) -> Response {
+    if let Some(resp) = deny(&state, &headers) {
+        return resp;
+    }
+
+    Json(state.jobs.status()).into_response()
+}
+
+async fn profile_start_handler(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Query(query): Query<DiagnoseQuery>,
+) -> Response {
+    if let Some(resp) = deny(&state, &headers) {
+        return resp;
+    }
+
+    let op = Diagnostic::StartProfile {
+        interval_us: query.interval_us.filter(|us| *us > 0),
+    };
+    match state.runtime.diagnose(query.worker, op).await {
+        Ok(results) => Json(json!({ "workers": per_worker(results) })).into_response(),
+        Err(e) => (StatusCode::BAD_REQUEST, Json(json!({ "error": e }))).into_response(),
+    }
+}
+
+async fn profile_stop_handler(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Query(query): Query<DiagnoseQuery>,
+) -> Response {
+    if let Some(resp) = deny(&state, &headers) {
+        return resp;
+    }
+
+    collect(&state, query.worker, Diagnostic::StopProfile, "cpuprofile").await
+}
+
+async fn heap_snapshot_handler(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Query(query): Query<DiagnoseQuery>,
+) -> Response {
+    if let Some(resp) = deny(&state, &headers) {
+        return resp;
+    }
+
+    collect(
+        &state,
+        query.worker,
+        Diagnostic::HeapSnapshot,
+        "heapsnapshot",
+    )
+    .await
+}
+
+async fn heap_handler(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Query(query): Query<DiagnoseQuery>,
+) -> Response {
+    if let Some(resp) = deny(&state, &headers) {
+        return resp;
+    }
+
+    match state
+        .runtime
+        .diagnose(query.worker, Diagnostic::HeapStats)
+        .await
+    {
+        Ok(results) => Json(json!({ "isolates": per_worker(results) })).into_response(),
+        Err(e) => (StatusCode::BAD_REQUEST, Json(json!({ "error": e }))).into_response(),
+    }
+}
+
+/// One JSON entry per worker: its result, or `{ worker, error }`.
+fn per_worker(results: Vec<(usize, Result<String, String>)>) -> Vec<Value> {
+    results
+        .into_iter()
+        .map(|(worker, result)| {
+            result
+                .and_then(|text| serde_json::from_str(&text).map_err(|e| e.to_string()))
+                .unwrap_or_else(|e| json!({ "worker": worker, "error": e }))
+        })
+        .collect()
+}
+
+/// Profile / snapshot: the file itself for one worker, else files written
+/// to `.titan/diagnostics/`.
+async fn collect(state: &AppState, worker: Option<usize>, op: Diagnostic, ext: &str) -> Response {
+    let results = match state.runtime.diagnose(worker, op).await {
+        Ok(results) => results,
+        Err(e) => return (StatusCode::BAD_REQUEST, Json(json!({ "error": e }))).into_response(),
+    };
+    let stamp = chrono::Local::now().format("%Y%m%d-%H%M%S");
+
+    if worker.is_some() {
+        let Some((id, result)) = results.into_iter().next() else {
+            return StatusCode::INTERNAL_SERVER_ERROR.into_response();
+        };
+        return match result {
+            Ok(body) => {
+                let disposition =
+                    format!("attachment; filename=\"worker-{}-{}.{}\"", id, stamp, ext);
+                (
+                    [
+                        (header::CONTENT_TYPE, "application/json".to_string()),
+                        (header::CONTENT_DISPOSITION, disposition),
+                    ],
+                    body,
+                )
+                    .into_response()
+            }
+            Err(e) => (
+                StatusCode::CONFLICT,
+                Json(json!({ "worker": id, "error": e })),
+            )
+                .into_response(),
+        };
+    }
+
+    let dir = PROJECT_ROOT
+        .get()
+        .cloned()
+        .unwrap_or_default()
+        .join(".titan")
+        .join("diagnostics");
+    if let Err(e) = tokio::fs::create_dir_all(&dir).await {
+        let error = format!("Cannot create {}: {}", dir.display(), e);
+        return (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(json!({ "error": error })),
+        )
+            .into_response();
+    }
+
+    let mut files = Vec::with_capacity(results.len());
+    for (id, result) in results {
+        let path = dir.join(format!("worker-{}-{}.{}", id, stamp, ext));
+        let entry = match result {
+            Ok(body) => match tokio::fs::write(&path, body).await {
+                Ok(()) => json!({ "worker": id, "path": path.to_string_lossy() }),
+                Err(e) => json!({ "worker": id, "error": e.to_string() }),
+            },
+            Err(e) => json!({ "worker": id, "error": e }),
+        };
+        files.push(entry);
+    }
+    Json(json!({ "files": files })).into_response()
+}
@@ -0,0 +1,462 @@
+//! Scheduled Actions (`__cron`)
+//!
+//! routes.json may carry a `__cron` section mapping cron expressions to
+//! action names:
+//!
+//! ```json
+//! "__cron": [
+//!   { "schedule": "*/5 * * * *", "action": "cleanup" },
+//!   { "schedule": "0 0 9 * * Mon-Fri", "action": "report", "timezone": "Europe/Berlin" }
+//! ]
+//! ```
+//!
+//! (the object form `{ "*/5 * * * *": "cleanup" }` is accepted too).
+//!
+//! 1. Five-field expressions are classic crontab: minute-based, day of week
+//!    0-7 with Sunday as 0 or 7. Six/seven fields add seconds and years and
+//!    follow the `cron` crate (day of week 1-7 from Sunday).
+//! 2. Times are evaluated in the job's `timezone`, else
+//!    `__config.cron_timezone`, else UTC.
+//! 3. Each run is a synthetic `CRON /__cron/<action>` request dispatched
+//!    through `RuntimeManager::execute_within`; the action sees `req.cron`.
+//! 4. A job never overlaps itself: a tick that fires while the previous run
+//!    is still going is skipped. A run past `request_timeout_ms` is stopped,
+//!    and counts as running until its worker has let go of it.
+//! 5. Last-run status is logged and exposed at `GET /__titan/admin/cron`.
+//!
+//! Schedules are read at boot; changing them needs a restart.
+
+use chrono::{DateTime, Utc};
+use chrono_tz::Tz;
+use serde::Serialize;
+use serde_json::Value;
+use smallvec::SmallVec;
+use std::collections::BTreeSet;
+use std::str::FromStr;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use crate::reload::RouteStore;
+use crate::runtime::{ExecuteError, RuntimeManager};
+use crate::utils::{blue, gray, green, red, yellow};
+
+#[derive(Default, Clone, Serialize)]
+pub struct CronStatus {
+    /// RFC 3339 start time of the last run (or skipped tick)
+    pub last_run: Option<String>,
+    pub duration_ms: Option<f64>,
+    /// "ok", "error" or "skipped"
+    pub result: Option<&'static str>,
+    pub error: Option<String>,
+    pub next_run: Option<String>,
+    pub runs: u64,
+    pub failures: u64,
+    pub skipped: u64,
+}
+
+pub struct CronJob {
+    expression: String,
+    schedule: ::cron::Schedule,
+    action: String,
+    timezone: Tz,
+    running: AtomicBool,
+    status: Mutex<CronStatus>,
+}
+
+impl CronJob {
+    fn parse(
+        expression: &str,
+        action: &str,
+        timezone: Option<&str>,
+        default_tz: Tz,
+    ) -> Option<Self> {
+        let timezone = match timezone {
+            Some(name) => match name.parse::<Tz>() {
+                Ok(tz) => tz,
+                Err(_) => {
+                    warn(action, &format!("unknown timezone '{}'", name));
+                    return None;
+                }
+            },
+            None => default_tz,
+        };
+
+        let normalized = normalize(expression);
+        let schedule = match ::cron::Schedule::from_str(&normalized) {
+            Ok(s) => s,
+            Err(e) => {
+                warn(
+                    action,
+                    &format!("invalid schedule '{}' ({})", expression, e),
+                );
+                return None;
+            }
+        };
+
+        Some(Self {
+            expression: expression.to_string(),
+            schedule,
+            action: action.to_string(),
+            timezone,
+            running: AtomicBool::new(false),
+            status: Mutex::new(CronStatus::default()),
+        })
+    }
+
+    fn next_after(&self, now: DateTime<Utc>) -> Option<DateTime<Tz>> {
+        self.schedule
+            .after(&now.with_timezone(&self.timezone))
+            .next()
+    }
+}
+
+/// Crontab → `cron` crate syntax: the crate wants seconds first, which
+/// classic crontab has none of, and numbers weekdays differently.
+fn normalize(expression: &str) -> String {
+    let fields: Vec<&str> = expression.split_whitespace().collect();
+    if fields.len() != 5 {
+        return expression.to_string();
+    }
+    format!(
+        "0 {} {} {} {} {}",
+        fields[0],
+        fields[1],
+        fields[2],
+        fields[3],
+        crontab_weekdays(fields[4])
+    )
+}
+
+/// Crontab numbers weekdays 0-7 from Sunday (0 and 7 both Sunday), the cron
+/// crate 1-7 from Sunday. Numeric items are expanded and shifted; `*`, `?`
+/// and day names pass through.
+fn crontab_weekdays(field: &str) -> String {
+    field
+        .split(',')
+        .map(|item| match crontab_days(item) {
+            Some(days) => days
+                .into_iter()
+                .map(|d| d % 7 + 1)
+                .collect::<BTreeSet<u32>>()
+                .iter()
+                .map(u32::to_string)
+                .collect::<Vec<_>>()
+                .join(","),
+            None => item.to_string(),
+        })
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
+/// Days of a numeric crontab item: `n`, `a-b`, `*/s`, `a-b/s` or `a/s`.
+fn crontab_days(item: &str) -> Option<Vec<u32>> {
+    if item == "*" {
+        return None;
+    }
+    let (range, step) = match item.split_once('/') {
+        Some((range, step)) => (range, step.parse::<usize>().ok().filter(|s| *s > 0)?),
+        None => (item, 1),
+    };
+    let (start, end) = match range.split_once('-') {
+        _ if range == "*" => (0, 6),
+        Some((a, b)) => (a.parse::<u32>().ok()?, b.parse::<u32>().ok()?),
+        // `a/s` runs to the end of the week
+        None if item.contains('/') => (range.parse::<u32>().ok()?, 7),
+        None => {
+            let n = range.parse::<u32>().ok()?;
+            (n, n)
+        }
+    };
+    if start > end || end > 7 {
+        return None;
+    }
+    Some((start..=end).step_by(step).collect())
+}
+
+fn warn(action: &str, msg: &str) {
+    println!(
+        "{} {} {}: {}",
+        blue("[Titan]"),
+        red("Cron job skipped"),
+        action,
+        msg
+    );
+}
+
+/// All jobs from routes.json `__cron`.
+pub struct CronScheduler {
+    jobs: Vec<Arc<CronJob>>,
+}
+
+impl CronScheduler {
+    pub fn from_json(json: &Value) -> Self {
+        let default_tz = match json["__config"]["cron_timezone"].as_str() {
+            Some(name) => name.parse::<Tz>().unwrap_or_else(|_| {
+                println!(
+                    "{} {} '{}' (using UTC)",
+                    blue("[Titan]"),
+                    red("Unknown __config.cron_timezone"),
+                    name
+                );
+                Tz::UTC
+            }),
+            None => Tz::UTC,
+        };
+
+        let mut jobs = Vec::new();
+        match &json["__cron"] {
+            Value::Object(map) => {
+                for (expression, target) in map {
+                    let (action, timezone) = match target {
+                        Value::String(action) => (action.as_str(), None),
+                        Value::Object(o) => match o.get("action").and_then(|v| v.as_str()) {
+                            Some(action) => (action, o.get("timezone").and_then(|v| v.as_str())),
+                            None => {
+                                warn(expression, "missing action");
+                                continue;
+                            }
+                        },
+                        _ => {
+                            warn(expression, "missing action");
+                            continue;
+                        }
+                    };
+                    jobs.extend(CronJob::parse(expression, action, timezone, default_tz));
+                }
+            }
+            Value::Array(list) => {
+                for entry in list {
+                    let action = entry["action"].as_str().unwrap_or("");
+                    let Some(expression) =
+                        entry["schedule"].as_str().filter(|_| !action.is_empty())
+                    else {
+                        warn(action, "entries need both schedule and action");
+                        continue;
+                    };
+                    jobs.extend(CronJob::parse(
+                        expression,
+                        action,
+                        entry["timezone"].as_str(),
+                        default_tz,
+                    ));
+                }
+            }
+            _ => {}
+        }
+
+        Self {
+            jobs: jobs.into_iter().map(Arc::new).collect(),
+        }
+    }
+
+    /// Spawn one timer task per job.
+    pub fn start(
+        &self,
+        routes: Arc<RouteStore>,
+        runtime: Arc<RuntimeManager>,
+        timeout: Option<Duration>,
+    ) {
+        if self.jobs.is_empty() {
+            return;
+        }
+        println!(
+            "{} {} cron job(s) scheduled",
+            blue("[Titan]"),
+            self.jobs.len()
+        );
+
+        for job in &self.jobs {
+            let job = job.clone();
+            let routes = routes.clone();
+            let runtime = runtime.clone();
+            tokio::spawn(async move {
+                // Last fired occurrence, so an early timer wake-up can't fire it twice
+                let mut last = Utc::now();
+                loop {
+                    let Some(next) = job.next_after(last.max(Utc::now())) else {
+                        // Schedule has no future occurrence (e.g. a past year)
+                        job.status.lock().unwrap().next_run = None;
+                        return;
+                    };
+                    job.status.lock().unwrap().next_run = Some(next.to_rfc3339());
+
+                    let wait = (next.with_timezone(&Utc) - Utc::now())
+                        .to_std()
+                        .unwrap_or(Duration::ZERO);
+                    tokio::time::sleep(wait).await;
+                    last = next.with_timezone(&Utc);
+
+                    if job.running.swap(true, Ordering::AcqRel) {
+                        let mut status = job.status.lock().unwrap();
+                        status.last_run = Some(Utc::now().to_rfc3339());
+                        status.result = Some("skipped");
+                        status.duration_ms = None;
+                        status.error = None;
+                        status.skipped += 1;
+                        println!(
+                            "{} {} {}",
+                            blue("[Titan]"),
+                            yellow(&format!("cron {}", job.action)),
+                            yellow("→ skipped (previous run still active)")
+                        );
+                        continue;
+                    }
+
+                    // Run detached so the next tick is still observed (and skipped)
+                    tokio::spawn(run(
+                        job.clone(),
+                        next,
+                        routes.clone(),
+                        runtime.clone(),
+                        timeout,
+                    ));
+                }
+            });
+        }
+    }
+
+    /// Per-job schedule and last-run status (admin endpoint).
+    pub fn status(&self) -> Value {
+        let jobs: Vec<Value> = self
+            .jobs
+            .iter()
+            .map(|job| {
+                let status = job.status.lock().unwrap().clone();
+                let mut entry = serde_json::json!({
+                    "schedule": job.expression,
+                    "action": job.action,
+                    "timezone": job.timezone.name(),
+                    "running": job.running.load(Ordering::Acquire),
+                });
+                if let (Value::Object(map), Ok(Value::Object(extra))) =
+                    (&mut entry, serde_json::to_value(status))
+                {
+                    map.extend(extra);
+                }
+                entry
+            })
+            .collect();
+        serde_json::json!({ "jobs": jobs })
+    }
+}
+
+async fn run(
+    job: Arc<CronJob>,
+    scheduled: DateTime<Tz>,
+    routes: Arc<RouteStore>,
+    runtime: Arc<RuntimeManager>,
+    timeout: Option<Duration>,
+) {
+    let started_at = Utc::now();
+    let start = Instant::now();
+    let extra = serde_json::json!({
+        "cron": {
+            "schedule": job.expression,
+            "scheduledAt": scheduled.to_rfc3339(),
+            "timezone": job.timezone.name(),
+        }
+    });
+
+    // A timed-out run returns only once its worker let go of it, so `running`
+    // stays set (and later ticks are skipped) while any of it still runs
+    let outcome = runtime
+        .execute_within(
+            timeout,
+            job.action.clone(),
+            "CRON".to_string(),
+            format!("/__cron/{}", job.action),
+            None,
+            SmallVec::new(),
+            SmallVec::new(),
+            SmallVec::new(),
+            Some(extra),
+            routes.current().isolation_for(&job.action),
+        )
+        .await;
+
+    let error = match outcome {
+        Some(Ok(res)) => res.json.get("error").map(|e| {
+            e.as_str()
+                .map(str::to_string)
+                .unwrap_or_else(|| e.to_string())
+        }),
+        Some(Err(ExecuteError::Overloaded { .. })) => Some("worker pool overloaded".to_string()),
+        Some(Err(ExecuteError::Failed(e))) => Some(e),
+        None => Some("timed out".to_string()),
+    };
+    let elapsed = start.elapsed();
+
+    {
+        let mut status = job.status.lock().unwrap();
+        status.last_run = Some(started_at.to_rfc3339());
+        status.duration_ms = Some(elapsed.as_secs_f64() * 1000.0);
+        status.runs += 1;
+        if error.is_some() {
+            status.failures += 1;
+            status.result = Some("error");
+        } else {
+            status.result = Some("ok");
+        }
+        status.error = error.clone();
+    }
+    job.running.store(false, Ordering::Release);
+
+    match error {
+        None => println!(
+            "{} {} {}",
+            blue("[Titan]"),
+            green(&format!("cron {}", job.action)),
+            gray(&format!("in {:.2?}", elapsed))
+        ),
+        Some(e) => println!(
+            "{} {} {} {}",
+            blue("[Titan]"),
+            red(&format!("cron {}", job.action)),
+            red(&format!("→ {}", e)),
+            gray(&format!("in {:.2?}", elapsed))
+        ),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn five_fields_get_seconds() {
+        assert_eq!(normalize("*/5 * * * *"), "0 */5 * * * *");
+        assert_eq!(normalize("0 0 9 * * Mon-Fri"), "0 0 9 * * Mon-Fri");
+    }
+
+    #[test]
+    fn crontab_weekdays_are_shifted() {
+        assert_eq!(crontab_weekdays("0"), "1");
+        assert_eq!(crontab_weekdays("7"), "1");
+        assert_eq!(crontab_weekdays("1-5"), "2,3,4,5,6");
+        assert_eq!(crontab_weekdays("5-7"), "1,6,7");
+        assert_eq!(crontab_weekdays("*/2"), "1,3,5,7");
+        assert_eq!(crontab_weekdays("1/3"), "1,2,5");
+        assert_eq!(crontab_weekdays("0,6"), "1,7");
+    }
+
+    #[test]
+    fn crontab_weekday_names_pass_through() {
+        assert_eq!(crontab_weekdays("*"), "*");
+        assert_eq!(crontab_weekdays("MON-FRI"), "MON-FRI");
+        assert_eq!(crontab_weekdays("Sun,3"), "Sun,4");
+        assert_eq!(crontab_weekdays("9"), "9");
+    }
+
+    #[test]
+    fn weekday_schedule_fires_on_the_crontab_day() {
+        use chrono::{Datelike, TimeZone, Weekday};
+
+        let job = CronJob::parse("30 9 * * 1", "report", None, Tz::UTC).unwrap();
+        // 2026-10-18 is a Sunday
+        let now = Utc.with_ymd_and_hms(2026, 10, 18, 12, 0, 0).unwrap();
+        let next = job.next_after(now).unwrap();
+        assert_eq!(next.weekday(), Weekday::Mon);
+        assert_eq!(next.day(), 19);
+    }
+}
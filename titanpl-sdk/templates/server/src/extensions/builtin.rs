@@ -81,6 +81,24 @@ pub fn inject_builtin_extensions(scope: &mut v8::HandleScope, global: v8::Local<
     let finish_key = v8_str(scope, "_finish_request");
     t_obj.set(scope, finish_key.into(), finish_fn.into());
 
+    // t._timer_set / t._timer_clear / t._queue_microtask (setTimeout & co.)
+    let timer_set_fn = v8::Function::new(scope, native_timer_set).unwrap();
+    let timer_set_key = v8_str(scope, "_timer_set");
+    t_obj.set(scope, timer_set_key.into(), timer_set_fn.into());
+
+    let timer_clear_fn = v8::Function::new(scope, native_timer_clear).unwrap();
+    let timer_clear_key = v8_str(scope, "_timer_clear");
+    t_obj.set(scope, timer_clear_key.into(), timer_clear_fn.into());
+
+    let microtask_fn = v8::Function::new(scope, native_queue_microtask).unwrap();
+    let microtask_key = v8_str(scope, "_queue_microtask");
+    t_obj.set(scope, microtask_key.into(), microtask_fn.into());
+
+    // t._jobs_enqueue (t.jobs.enqueue)
+    let jobs_fn = v8::Function::new(scope, native_jobs_enqueue).unwrap();
+    let jobs_key = v8_str(scope, "_jobs_enqueue");
+    t_obj.set(scope, jobs_key.into(), jobs_fn.into());
+
     // t.loadEnv
     let env_fn = v8::Function::new(scope, native_load_env).unwrap();
     let env_key = v8_str(scope, "loadEnv");
@@ -102,6 +120,45 @@ pub fn inject_builtin_extensions(scope: &mut v8::HandleScope, global: v8::Local<
     }
 }
 
+/// Every native callback `inject_builtin_extensions` can install, for the
+/// startup snapshot. The order is part of the snapshot format.
+pub fn external_references() -> Vec<v8::ExternalReference<'static>> {
+    use v8::MapFnTo;
+
+    macro_rules! refs {
+        ($($f:ident),* $(,)?) => {
+            vec![$(v8::ExternalReference { function: $f.map_fn_to() }),*]
+        };
+    }
+
+    refs![
+        native_define_action,
+        native_read,
+        native_decode_utf8,
+        native_log,
+        native_fetch_meta,
+        native_drift_call,
+        native_finish_request,
+        native_timer_set,
+        native_timer_clear,
+        native_queue_microtask,
+        native_jobs_enqueue,
+        native_load_env,
+        native_jwt_sign,
+        native_jwt_verify,
+        native_password_hash,
+        native_password_verify,
+        share_context_get,
+        share_context_set,
+        share_context_delete,
+        share_context_keys,
+        share_context_broadcast,
+        native_db_connect,
+        native_db_query,
+        native_read_sync,
+    ]
+}
+
 fn setup_native_utils(scope: &mut v8::HandleScope, t_obj: v8::Local<v8::Object>) {
     // t.jwt
     let jwt_obj = v8::Object::new(scope);
@@ -196,6 +253,7 @@ fn native_read_sync(scope: &mut v8::HandleScope, args: v8::FunctionCallbackArgum
     
     if let Ok(target) = joined.canonicalize() {
         if target.starts_with(&root.canonicalize().unwrap_or(root.clone())) {
+            super::snapshot::record_input(&target);
             match std::fs::read_to_string(&target) {
                 Ok(content) => {
                     let v8_content = v8_str(scope, &content);
@@ -316,25 +374,45 @@ fn share_context_broadcast(scope: &mut v8::HandleScope, args: v8::FunctionCallba
 
 
 fn native_log(scope: &mut v8::HandleScope, args: v8::FunctionCallbackArguments, mut _retval: v8::ReturnValue) {
+    let action_name = current_action(scope);
+    let parts = log_parts(scope, &args, 0);
+
+    let titan_str = blue("[Titan]");
+    let log_msg = gray(&format!("\x1b[90mlog({})\x1b[0m\x1b[97m: {}\x1b[0m", action_name, parts.join(" ")));
+    println!(
+        "{} {}",
+        titan_str,
+        log_msg
+    );
+}
+
+/// Action whose code is running, for log prefixes ("init" at boot).
+pub(super) fn current_action(scope: &mut v8::HandleScope) -> String {
     let context = scope.get_current_context();
     let global = context.global(scope);
     let action_key = v8_str(scope, "__titan_action");
-    let action_name = if let Some(action_val) = global.get(scope, action_key.into()) {
-        if action_val.is_string() {
-            v8_to_string(scope, action_val)
-        } else {
-            "init".to_string()
-        }
-    } else {
-        "init".to_string()
-    };
+    match global.get(scope, action_key.into()) {
+        Some(action_val) if action_val.is_string() => v8_to_string(scope, action_val),
+        _ => "init".to_string(),
+    }
+}
 
+/// Log arguments from index `from` on: errors print their stack, other
+/// objects as JSON, everything else via `String()`.
+pub(super) fn log_parts(scope: &mut v8::HandleScope, args: &v8::FunctionCallbackArguments, from: i32) -> Vec<String> {
     let mut parts = Vec::new();
-    for i in 0..args.length() {
+    for i in from..args.length() {
         let val = args.get(i);
         let mut appended = false;
-        
-        if val.is_object() && !val.is_function() {
+
+        if val.is_native_error() {
+            let obj = val.to_object(scope).unwrap();
+            let stack_key = v8_str(scope, "stack");
+            if let Some(stack) = obj.get(scope, stack_key.into()).filter(|s| s.is_string()) {
+                parts.push(v8_to_string(scope, stack));
+                appended = true;
+            }
+        } else if val.is_object() && !val.is_function() {
              if let Some(json) = v8::json::stringify(scope, val) {
                  parts.push(json.to_rust_string_lossy(scope));
                  appended = true;
@@ -345,18 +423,9 @@ fn native_log(scope: &mut v8::HandleScope, args: v8::FunctionCallbackArguments,
             parts.push(v8_to_string(scope, val));
         }
     }
-    
-    let titan_str = blue("[Titan]");
-    let log_msg = gray(&format!("\x1b[90mlog({})\x1b[0m\x1b[97m: {}\x1b[0m", action_name, parts.join(" ")));
-    println!(
-        "{} {}",
-        titan_str,
-        log_msg
-    );
+    parts
 }
 
-
-
 fn native_jwt_sign(scope: &mut v8::HandleScope, args: v8::FunctionCallbackArguments, mut retval: v8::ReturnValue) {
     let payload_val = args.get(0);
     let json_str = v8::json::stringify(scope, payload_val).unwrap().to_rust_string_lossy(scope);
@@ -431,6 +500,13 @@ fn native_load_env(scope: &mut v8::HandleScope, _args: v8::FunctionCallbackArgum
 
     let mut map = serde_json::Map::new();
 
+    // Never bake the environment into a startup snapshot
+    if super::snapshot::building() {
+        super::snapshot::env_read();
+        retval.set(v8::Object::new(scope).into());
+        return;
+    }
+
     for (key, value) in std::env::vars() {
         map.insert(key, json!(value));
     }
@@ -619,6 +695,27 @@ fn parse_async_op(scope: &mut v8::HandleScope, op_val: v8::Local<v8::Value>) ->
             let path = v8_to_string(scope, path_obj);
             Some(super::TitanAsyncOp::FsRead { path })
         },
+        "call" => {
+            let action_key = v8_str(scope, "action");
+            let action_obj = data_obj.get(scope, action_key.into())?;
+            let action = v8_to_string(scope, action_obj);
+            let request_key = v8_str(scope, "request");
+            let request_val = data_obj.get(scope, request_key.into())?;
+            let request = v8::json::stringify(scope, request_val)
+                .and_then(|s| serde_json::from_str(&s.to_rust_string_lossy(scope)).ok())
+                .unwrap_or(Value::Null);
+            let chain_key = v8_str(scope, "chain");
+            let chain_val = data_obj.get(scope, chain_key.into())?;
+            let chain = v8::json::stringify(scope, chain_val)
+                .and_then(|s| serde_json::from_str(&s.to_rust_string_lossy(scope)).ok())
+                .unwrap_or_default();
+            let depth_key = v8_str(scope, "depth");
+            let depth = data_obj
+                .get(scope, depth_key.into())
+                .and_then(|v| v.uint32_value(scope))
+                .unwrap_or(0) as usize;
+            Some(super::TitanAsyncOp::Call { action, request, chain, depth })
+        },
         _ => None
     }
 }
@@ -642,12 +739,7 @@ fn native_drift_call(scope: &mut v8::HandleScope, mut args: v8::FunctionCallback
     } else {
         match parse_async_op(scope, arg0) {
             Some(op) => {
-                let t = match &op {
-                    super::TitanAsyncOp::Fetch { .. } => "fetch",
-                    super::TitanAsyncOp::DbQuery { .. } => "db_query",
-                    super::TitanAsyncOp::FsRead { .. } => "fs_read",
-                    _ => "unknown"
-                };
+                let t = op_type(&op);
                 (op, t.to_string())
             },
             None => {
@@ -659,19 +751,22 @@ fn native_drift_call(scope: &mut v8::HandleScope, mut args: v8::FunctionCallback
 
     let runtime_ptr = unsafe { args.get_isolate() }.get_data(0) as *mut super::TitanRuntime;
     let runtime = unsafe { &mut *runtime_ptr };
-    
-    let req_id = {
-        let context = scope.get_current_context();
-        let global = context.global(scope);
-        let req_key = v8_str(scope, "__titan_req");
-        if let Some(req_obj_val) = global.get(scope, req_key.into()) {
-            if req_obj_val.is_object() {
-                let req_obj = req_obj_val.to_object(scope).unwrap();
-                let id_key = v8_str(scope, "__titan_request_id");
-                req_obj.get(scope, id_key.into()).unwrap().uint32_value(scope).unwrap_or(0)
-            } else { 0 }
-        } else { 0 }
-    };
+
+    // Async actions (`async function`) get a real Promise; sync actions keep
+    // the suspend-and-replay model.
+    let promise_mode = args.get(1).boolean_value(scope);
+    let timeout = args
+        .get(2)
+        .number_value(scope)
+        .filter(|ms| ms.is_finite() && *ms > 0.0)
+        .map(|ms| std::time::Duration::from_millis(ms as u64));
+    let concurrency = args
+        .get(3)
+        .number_value(scope)
+        .filter(|n| n.is_finite() && *n >= 1.0)
+        .map(|n| n as usize);
+
+    let (req_id, req_obj) = current_request(scope);
 
     runtime.drift_counter += 1;
     let drift_id = runtime.drift_counter;
@@ -680,17 +775,19 @@ fn native_drift_call(scope: &mut v8::HandleScope, mut args: v8::FunctionCallback
         runtime.drift_to_request.insert(drift_id, req_id);
     }
 
-    // --- REPLAY CHECK ---
-    if let Some(res) = runtime.completed_drifts.get(&drift_id) {
-         let json_str = serde_json::to_string(res).unwrap_or_else(|_| "null".to_string());
-         let v8_str = v8::String::new(scope, &json_str).unwrap();
-         let mut try_catch = v8::TryCatch::new(scope);
-         if let Some(val) = v8::json::parse(&mut try_catch, v8_str) {
-             retval.set(val);
-         } else {
-             retval.set(v8::null(&mut try_catch).into());
-         }
-         return;
+    // --- REPLAY CHECK (sync actions only) ---
+    if !promise_mode {
+        if let Some(res) = runtime.completed_drifts.get(&drift_id) {
+             let json_str = serde_json::to_string(res).unwrap_or_else(|_| "null".to_string());
+             let v8_str = v8::String::new(scope, &json_str).unwrap();
+             let mut try_catch = v8::TryCatch::new(scope);
+             if let Some(val) = v8::json::parse(&mut try_catch, v8_str) {
+                 retval.set(val);
+             } else {
+                 retval.set(v8::null(&mut try_catch).into());
+             }
+             return;
+        }
     }
 
     let (tx, rx) = tokio::sync::oneshot::channel::<super::WorkerAsyncResult>();
@@ -700,6 +797,9 @@ fn native_drift_call(scope: &mut v8::HandleScope, mut args: v8::FunctionCallback
         drift_id,
         request_id: req_id,
         op_type,
+        timeout,
+        concurrency,
+        cancel: runtime.request_cancel.get(&req_id).cloned(),
         respond_tx: tx,
     };
     
@@ -712,6 +812,8 @@ fn native_drift_call(scope: &mut v8::HandleScope, mut args: v8::FunctionCallback
     let tokio_handle = runtime.tokio_handle.clone();
     let worker_tx = runtime.worker_tx.clone();
     
+    // An op dropped because its request was abandoned never resumes; the
+    // request's own watcher releases it (see `runtime::watch_cancel`)
     tokio_handle.spawn(async move {
         if let Ok(res) = rx.await {
             let _ = worker_tx.send(crate::runtime::WorkerCommand::Resume {
@@ -721,15 +823,69 @@ fn native_drift_call(scope: &mut v8::HandleScope, mut args: v8::FunctionCallback
         }
     });
 
+    if promise_mode {
+        // Resolved from `handle_resume`; the continuation runs with this
+        // request object restored as `__titan_req`.
+        if let Some(obj) = req_obj.filter(|_| req_id != 0) {
+            runtime
+                .request_objects
+                .entry(req_id)
+                .or_insert_with(|| v8::Global::new(scope, obj));
+        }
+        let resolver = v8::PromiseResolver::new(scope).unwrap();
+        let promise = resolver.get_promise(scope);
+        runtime.pending_drifts.insert(drift_id, v8::Global::new(scope, resolver));
+        retval.set(promise.into());
+        return;
+    }
+
     throw(scope, "__SUSPEND__");
 }
 
+/// Bytes of an ArrayBuffer / ArrayBufferView (Uint8Array, etc).
+///
+/// Copied rather than handed over zero-copy from the backing store: JS keeps
+/// access to the buffer (it may be module-level and reused by the next
+/// request, resized or transferred) while the body is written on another
+/// thread, so sharing the store would let the action rewrite a response that
+/// is still being sent. Detaching the buffer instead would empty it for the
+/// action, so one memcpy per binary response is the price of keeping it.
+fn binary_body(scope: &mut v8::HandleScope, val: v8::Local<v8::Value>) -> Option<bytes::Bytes> {
+    let (store, offset, len) = if let Ok(view) = v8::Local::<v8::ArrayBufferView>::try_from(val) {
+        let buf = view.buffer(scope)?;
+        (buf.get_backing_store(), view.byte_offset(), view.byte_length())
+    } else if let Ok(ab) = v8::Local::<v8::ArrayBuffer>::try_from(val) {
+        (ab.get_backing_store(), 0, ab.byte_length())
+    } else {
+        return None;
+    };
+
+    if len == 0 {
+        return Some(bytes::Bytes::new());
+    }
+    let data = store.data()?;
+    // SAFETY: `offset + len` lies within the store (V8 keeps views in bounds)
+    // and nothing runs JS while the slice is read
+    let slice =
+        unsafe { std::slice::from_raw_parts((data.as_ptr() as *const u8).add(offset), len) };
+    Some(bytes::Bytes::copy_from_slice(slice))
+}
+
 fn native_finish_request(scope: &mut v8::HandleScope, mut args: v8::FunctionCallbackArguments, _retval: v8::ReturnValue) {
     let request_id = args.get(0).uint32_value(scope).unwrap_or(0);
     let result_val = args.get(1);
+    let mut binary: Option<bytes::Bytes> = None;
 
     // --- OPTIMIZATION: Direct field extraction for _isResponse objects ---
-    let json = if result_val.is_object() {
+    let mut json = if result_val.is_array_buffer() || result_val.is_array_buffer_view() {
+        // Raw bytes returned directly from the action
+        binary = binary_body(scope, result_val);
+        serde_json::json!({
+            "_isResponse": true,
+            "status": 200,
+            "headers": { "Content-Type": "application/octet-stream" }
+        })
+    } else if result_val.is_object() {
         let obj = result_val.to_object(scope).unwrap();
         let is_resp_key = v8_str(scope, "_isResponse");
         let is_response = obj
@@ -756,7 +912,10 @@ fn native_finish_request(scope: &mut v8::HandleScope, mut args: v8::FunctionCall
             // body (already a JSON string from JS — extract as-is, no re-serialization)
             let body_key = v8_str(scope, "body");
             if let Some(b) = obj.get(scope, body_key.into()) {
-                if b.is_string() {
+                if b.is_array_buffer() || b.is_array_buffer_view() {
+                    // Binary body — copied out of the backing store (see `binary_body`)
+                    binary = binary_body(scope, b);
+                } else if b.is_string() {
                     let body_str = b.to_string(scope).unwrap().to_rust_string_lossy(scope);
                     map.insert("body".into(), Value::String(body_str));
                 } else if !b.is_null_or_undefined() {
@@ -766,7 +925,7 @@ fn native_finish_request(scope: &mut v8::HandleScope, mut args: v8::FunctionCall
                 }
             }
 
-            // headers (flat object with ~2-3 keys typically)
+            // headers (flat object with ~2-3 keys typically; array values = repeated header)
             let headers_key = v8_str(scope, "headers");
             if let Some(h) = obj.get(scope, headers_key.into()) {
                 if h.is_object() {
@@ -780,9 +939,18 @@ fn native_finish_request(scope: &mut v8::HandleScope, mut args: v8::FunctionCall
                                 if let Some(val) = h_obj.get(scope, key) {
                                     let k_str =
                                         key.to_string(scope).unwrap().to_rust_string_lossy(scope);
-                                    let v_str =
-                                        val.to_string(scope).unwrap().to_rust_string_lossy(scope);
-                                    h_map.insert(k_str, Value::String(v_str));
+                                    let v_json = if let Ok(arr) = v8::Local::<v8::Array>::try_from(val) {
+                                        let mut items = Vec::with_capacity(arr.length() as usize);
+                                        for j in 0..arr.length() {
+                                            if let Some(item) = arr.get_index(scope, j) {
+                                                items.push(Value::String(v8_to_string(scope, item)));
+                                            }
+                                        }
+                                        Value::Array(items)
+                                    } else {
+                                        Value::String(v8_to_string(scope, val))
+                                    };
+                                    h_map.insert(k_str, v_json);
                                 }
                             }
                         }
@@ -798,18 +966,168 @@ fn native_finish_request(scope: &mut v8::HandleScope, mut args: v8::FunctionCall
         super::v8_to_json(scope, result_val)
     };
 
+    // Thrown errors carry their stack: point it at the original sources
+    if json.get("error").is_some() {
+        if let Some(Value::String(stack)) = json.get_mut("stack") {
+            *stack = super::source_maps::remap_stack(stack);
+        }
+    }
+
     let runtime_ptr = unsafe { args.get_isolate() }.get_data(0) as *mut super::TitanRuntime;
     let runtime = unsafe { &mut *runtime_ptr };
     
     if let Some(tx) = runtime.pending_requests.remove(&request_id) {
         let timings = runtime.request_timings.remove(&request_id).unwrap_or_default();
+        let queue_ms = runtime.request_queue_ms.remove(&request_id).unwrap_or(0.0);
         let _ = tx.send(crate::runtime::WorkerResult {
              json,
-             timings
+             timings,
+             body: binary,
+             queue_ms,
         });
     }
 }
 
+/// The request currently executing (`__titan_req`) and its ID (0 if none).
+fn current_request<'s>(scope: &mut v8::HandleScope<'s>) -> (u32, Option<v8::Local<'s, v8::Object>>) {
+    let context = scope.get_current_context();
+    let global = context.global(scope);
+    let req_key = v8_str(scope, "__titan_req");
+    let req_val = global.get(scope, req_key.into()).filter(|v| v.is_object());
+    match req_val.and_then(|v| v.to_object(scope)) {
+        Some(req_obj) => {
+            let id_key = v8_str(scope, "__titan_request_id");
+            let id = req_obj.get(scope, id_key.into()).unwrap().uint32_value(scope).unwrap_or(0);
+            (id, Some(req_obj))
+        }
+        None => (0, None),
+    }
+}
+
+/// `t._timer_set(callback, delayMs, repeat)` → timer ID.
+///
+/// Timers belong to the current request and are dropped when it finishes,
+/// so they can never fire into a later request.
+fn native_timer_set(scope: &mut v8::HandleScope, mut args: v8::FunctionCallbackArguments, mut retval: v8::ReturnValue) {
+    let runtime_ptr = unsafe { args.get_isolate() }.get_data(0) as *mut super::TitanRuntime;
+    let runtime = unsafe { &mut *runtime_ptr };
+
+    let Ok(callback) = v8::Local::<v8::Function>::try_from(args.get(0)) else {
+        throw(scope, "Timer callback must be a function");
+        return;
+    };
+    let delay_ms = args
+        .get(1)
+        .number_value(scope)
+        .filter(|ms| ms.is_finite() && *ms > 0.0)
+        .unwrap_or(0.0);
+    let repeat = args.get(2).boolean_value(scope);
+
+    let (req_id, req_obj) = current_request(scope);
+    let Some(req_obj) = req_obj.filter(|_| req_id != 0) else {
+        throw(scope, "Timers can only be set while handling a request");
+        return;
+    };
+
+    // An async action may await the timer: keep its request object for the
+    // continuation instead of storing replay data (same as a promise drift)
+    let async_key = v8_str(scope, "__titan_async");
+    let is_async = req_obj
+        .get(scope, async_key.into())
+        .is_some_and(|v| v.boolean_value(scope));
+    if is_async {
+        runtime
+            .request_objects
+            .entry(req_id)
+            .or_insert_with(|| v8::Global::new(scope, req_obj));
+    }
+
+    runtime.timer_counter += 1;
+    let timer_id = runtime.timer_counter;
+    let delay = std::time::Duration::from_micros((delay_ms * 1000.0) as u64);
+    let interval = repeat.then(|| delay.max(std::time::Duration::from_millis(1)));
+    let task = super::arm_timer(runtime, timer_id, interval.unwrap_or(delay));
+
+    runtime.timers.insert(
+        timer_id,
+        super::Timer {
+            request_id: req_id,
+            callback: v8::Global::new(scope, callback),
+            request: v8::Global::new(scope, req_obj),
+            interval,
+            task,
+        },
+    );
+
+    retval.set(v8::Number::new(scope, timer_id as f64).into());
+}
+
+/// `t._timer_clear(id)` — unknown or already-fired IDs are ignored.
+fn native_timer_clear(scope: &mut v8::HandleScope, mut args: v8::FunctionCallbackArguments, _retval: v8::ReturnValue) {
+    let runtime_ptr = unsafe { args.get_isolate() }.get_data(0) as *mut super::TitanRuntime;
+    let runtime = unsafe { &mut *runtime_ptr };
+
+    let Some(timer_id) = args.get(0).uint32_value(scope) else {
+        return;
+    };
+    if let Some(timer) = runtime.timers.remove(&timer_id) {
+        timer.task.abort();
+    }
+}
+
+/// `t._queue_microtask(callback)`
+fn native_queue_microtask(scope: &mut v8::HandleScope, args: v8::FunctionCallbackArguments, _retval: v8::ReturnValue) {
+    let Ok(callback) = v8::Local::<v8::Function>::try_from(args.get(0)) else {
+        throw(scope, "queueMicrotask() requires a function");
+        return;
+    };
+    scope.enqueue_microtask(callback);
+}
+
+/// `t._jobs_enqueue(action, payloadJson, delayMs, retries, backoffType, backoffMs)` → job ID.
+///
+/// A sync action replays from the top after each drift, so within a request
+/// the call takes a drift position: a replay reaching it again gets the
+/// original job ID back instead of enqueueing a duplicate.
+fn native_jobs_enqueue(scope: &mut v8::HandleScope, args: v8::FunctionCallbackArguments, mut retval: v8::ReturnValue) {
+    let runtime_ptr = unsafe { args.get_isolate() }.get_data(0) as *mut super::TitanRuntime;
+    let runtime = unsafe { &mut *runtime_ptr };
+
+    let (req_id, _) = current_request(scope);
+    let position = (req_id != 0).then(|| {
+        runtime.drift_counter += 1;
+        runtime.drift_to_request.insert(runtime.drift_counter, req_id);
+        runtime.drift_counter
+    });
+    if let Some(id) = position
+        .and_then(|drift_id| runtime.completed_drifts.get(&drift_id))
+        .and_then(Value::as_str)
+    {
+        let id = v8_str(scope, id);
+        retval.set(id.into());
+        return;
+    }
+
+    let action = v8_to_string(scope, args.get(0));
+    let payload = serde_json::from_str(&v8_to_string(scope, args.get(1))).unwrap_or(Value::Null);
+    let delay_ms = args.get(2).number_value(scope).filter(|ms| ms.is_finite() && *ms > 0.0).unwrap_or(0.0);
+    let retries = args.get(3).uint32_value(scope).unwrap_or(0);
+    let backoff_type = v8_to_string(scope, args.get(4));
+    let backoff_ms = args.get(5).number_value(scope).filter(|ms| ms.is_finite() && *ms > 0.0).unwrap_or(0.0);
+
+    let backoff = crate::jobs::Backoff::new(&backoff_type, backoff_ms as u64);
+    match crate::jobs::enqueue(action, payload, std::time::Duration::from_millis(delay_ms as u64), retries, backoff) {
+        Ok(id) => {
+            if let Some(drift_id) = position {
+                runtime.completed_drifts.insert(drift_id, Value::from(id.as_str()));
+            }
+            let id = v8_str(scope, &id);
+            retval.set(id.into());
+        }
+        Err(e) => throw(scope, &e),
+    }
+}
+
 pub fn run_async_operation(op: super::TitanAsyncOp) -> std::pin::Pin<Box<dyn std::future::Future<Output = serde_json::Value> + Send>> {
     Box::pin(async move {
         match op {
@@ -916,13 +1234,80 @@ pub fn run_async_operation(op: super::TitanAsyncOp) -> std::pin::Pin<Box<dyn std
                      serde_json::json!({ "error": "Access denied" })
                 }
             },
-            super::TitanAsyncOp::Batch(ops) => {
-                let mut res = Vec::new();
-                for op in ops {
-                    res.push(run_async_operation(op).await);
-                }
-                serde_json::Value::Array(res)
-            }
+            super::TitanAsyncOp::Call { action, request, chain, depth } => crate::runtime::call_action(action, request, chain, depth + 1).await,
+            super::TitanAsyncOp::Batch(ops) => run_batch(ops, None, None).await.0,
         }
     })
-}
\ No newline at end of file
+}
+
+/// Short op name used for logs and Server-Timing.
+pub fn op_type(op: &super::TitanAsyncOp) -> &'static str {
+    match op {
+        super::TitanAsyncOp::Fetch { .. } => "fetch",
+        super::TitanAsyncOp::DbQuery { .. } => "db_query",
+        super::TitanAsyncOp::FsRead { .. } => "fs_read",
+        super::TitanAsyncOp::Call { .. } => "call",
+        super::TitanAsyncOp::Batch(_) => "batch",
+    }
+}
+
+/// Run one op, turning a timeout into an `{ error }` result.
+pub async fn run_with_timeout(op: super::TitanAsyncOp, limit: Option<std::time::Duration>) -> serde_json::Value {
+    let fut = run_async_operation(op);
+    match limit {
+        Some(limit) => tokio::time::timeout(limit, fut).await.unwrap_or_else(|_| {
+            serde_json::json!({
+                "error": format!("Drift operation timed out after {}ms", limit.as_millis())
+            })
+        }),
+        None => fut.await,
+    }
+}
+
+/// Run a batch concurrently, at most `concurrency` ops at a time.
+///
+/// `allSettled` semantics: results keep input order and a failing, timed-out
+/// or panicking op only yields its own `{ error }` entry. `limit` applies per
+/// op. Also returns `(batch_<op>, ms)` timings in input order. Dropping the
+/// future aborts every op still running.
+pub async fn run_batch(
+    ops: Vec<super::TitanAsyncOp>,
+    limit: Option<std::time::Duration>,
+    concurrency: Option<usize>,
+) -> (serde_json::Value, Vec<(String, f64)>) {
+    let permits = concurrency.map(|n| std::sync::Arc::new(tokio::sync::Semaphore::new(n.max(1))));
+    let count = ops.len();
+    let mut set = tokio::task::JoinSet::new();
+    let mut slots = HashMap::with_capacity(count);
+
+    for (i, op) in ops.into_iter().enumerate() {
+        let name = op_type(&op);
+        let permits = permits.clone();
+        let handle = set.spawn(async move {
+            // Time the op itself, not the wait for a permit
+            let _permit = match permits {
+                Some(p) => p.acquire_owned().await.ok(),
+                None => None,
+            };
+            let start = std::time::Instant::now();
+            let value = run_with_timeout(op, limit).await;
+            (value, start.elapsed().as_secs_f64() * 1000.0)
+        });
+        slots.insert(handle.id(), (i, name));
+    }
+
+    let mut results = vec![serde_json::Value::Null; count];
+    let mut timings = vec![(String::new(), 0.0); count];
+    while let Some(joined) = set.join_next_with_id().await {
+        let (id, (value, ms)) = match joined {
+            Ok(done) => done,
+            Err(e) => (e.id(), (serde_json::json!({ "error": format!("Drift operation failed: {}", e) }), 0.0)),
+        };
+        let (i, name) = slots[&id];
+        let suffix = if value.get("error").is_some() { "_error" } else { "" };
+        timings[i] = (format!("batch_{}{}", name, suffix), ms);
+        results[i] = value;
+    }
+
+    (serde_json::Value::Array(results), timings)
+}
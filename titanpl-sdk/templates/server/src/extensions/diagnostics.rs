@@ -0,0 +1,78 @@
+//! Isolate Diagnostics (admin endpoints)
+//!
+//! Run on a worker thread through `WorkerCommand::Diagnose`:
+//! 1. CPU profiles: start / stop the V8 sampling profiler (through an
+//!    in-process inspector session); stopping yields a `.cpuprofile`.
+//! 2. Heap snapshots in the `.heapsnapshot` format DevTools loads.
+//! 3. Heap statistics of the isolate.
+//!
+//! Every result is JSON text, so large profiles and snapshots are not
+//! re-parsed on their way to a file or response.
+
+use serde_json::json;
+
+use super::TitanRuntime;
+use super::inspector::Inspector;
+
+#[derive(Clone, Copy, Debug)]
+pub enum Diagnostic {
+    /// Start the CPU profiler; optional sampling interval in microseconds
+    StartProfile {
+        interval_us: Option<u32>,
+    },
+    StopProfile,
+    HeapSnapshot,
+    HeapStats,
+}
+
+/// Run `op` on this worker's isolate.
+pub fn run(runtime: &mut TitanRuntime, op: Diagnostic) -> Result<String, String> {
+    match op {
+        Diagnostic::StartProfile { interval_us } => {
+            // Workers other than the inspected one get an inspector on demand
+            let inspector = runtime.inspector.get_or_insert_with(|| {
+                Inspector::new(&mut runtime.isolate, crossbeam::channel::never())
+            });
+            let _scope = &mut v8::HandleScope::new(&mut runtime.isolate);
+            inspector.start_profiling(interval_us)?;
+            Ok(json!({ "worker": runtime.id, "profiling": true }).to_string())
+        }
+        Diagnostic::StopProfile => {
+            let inspector = runtime
+                .inspector
+                .as_mut()
+                .ok_or_else(|| "CPU profiler not running".to_string())?;
+            let _scope = &mut v8::HandleScope::new(&mut runtime.isolate);
+            inspector
+                .stop_profiling()
+                .map(|profile| profile.to_string())
+        }
+        Diagnostic::HeapSnapshot => {
+            let mut snapshot = Vec::new();
+            runtime.isolate.take_heap_snapshot(|chunk| {
+                snapshot.extend_from_slice(chunk);
+                true
+            });
+            String::from_utf8(snapshot).map_err(|e| e.to_string())
+        }
+        Diagnostic::HeapStats => {
+            let mut stats = v8::HeapStatistics::default();
+            runtime.isolate.get_heap_statistics(&mut stats);
+            Ok(json!({
+                "worker": runtime.id,
+                "used_heap_size": stats.used_heap_size(),
+                "total_heap_size": stats.total_heap_size(),
+                "total_heap_size_executable": stats.total_heap_size_executable(),
+                "total_physical_size": stats.total_physical_size(),
+                "total_available_size": stats.total_available_size(),
+                "heap_size_limit": stats.heap_size_limit(),
+                "malloced_memory": stats.malloced_memory(),
+                "peak_malloced_memory": stats.peak_malloced_memory(),
+                "external_memory": stats.external_memory(),
+                "number_of_native_contexts": stats.number_of_native_contexts(),
+                "number_of_detached_contexts": stats.number_of_detached_contexts(),
+            })
+            .to_string())
+        }
+    }
+}
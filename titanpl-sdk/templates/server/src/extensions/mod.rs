@@ -8,10 +8,17 @@
 
 #![allow(unused)]
 pub mod builtin;
+pub mod code_cache;
+pub mod diagnostics;
 pub mod external;
+pub mod inspector;
+pub mod modules;
+pub mod snapshot;
+pub mod source_maps;
+pub mod web;
 
 use crate::action_management::scan_actions;
-use crate::utils::{blue, gray, green, red};
+use crate::utils::{blue, gray, green, parse_cookies, red};
 use bytes::Bytes;
 use crossbeam::channel::Sender;
 use dashmap::DashMap;
@@ -20,6 +27,7 @@ use std::collections::{HashMap, HashSet};
 use std::fs;
 use std::path::PathBuf;
 use std::sync::Once;
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::{Arc, Mutex, OnceLock};
 use tokio::sync::broadcast;
 use v8;
@@ -67,6 +75,15 @@ pub enum TitanAsyncOp {
     FsRead {
         path: String,
     },
+    /// `t.call(action, req)`: run another action through the worker pool
+    Call {
+        action: String,
+        request: serde_json::Value,
+        /// Actions already on the call stack (for the loop error message)
+        chain: Vec<String>,
+        /// Nesting level of the caller (0 for a regular request)
+        depth: usize,
+    },
     Batch(Vec<TitanAsyncOp>),
 }
 
@@ -74,6 +91,20 @@ pub struct WorkerAsyncResult {
     pub drift_id: u32,
     pub result: serde_json::Value,
     pub duration_ms: f64,
+    /// Per-op `(name, ms)` for batches, surfaced in Server-Timing
+    pub op_timings: Vec<(String, f64)>,
+}
+
+/// A `setTimeout` / `setInterval` registration, owned by one request.
+pub struct Timer {
+    pub request_id: u32,
+    pub callback: v8::Global<v8::Function>,
+    /// Restored as `__titan_req` while the callback runs
+    pub request: v8::Global<v8::Object>,
+    /// `Some` for `setInterval`
+    pub interval: Option<std::time::Duration>,
+    /// The tokio sleep that will deliver `WorkerCommand::Timer`
+    pub task: tokio::task::AbortHandle,
 }
 
 pub struct AsyncOpRequest {
@@ -81,6 +112,13 @@ pub struct AsyncOpRequest {
     pub drift_id: u32,
     pub request_id: u32,
     pub op_type: String,
+    /// `drift(op, { timeout })` override; falls back to `__config.drift_timeout_ms`
+    pub timeout: Option<std::time::Duration>,
+    /// `drift([...], { concurrency })` override; falls back to `__config.drift_concurrency`
+    pub concurrency: Option<usize>,
+    /// Fires when the request is answered or gone (client disconnect,
+    /// request timeout); the op is dropped then
+    pub cancel: Option<tokio::sync::watch::Receiver<()>>,
     pub respond_tx: tokio::sync::oneshot::Sender<WorkerAsyncResult>,
 }
 
@@ -94,6 +132,7 @@ pub struct InternedKeys {
     pub headers: v8::Global<v8::String>,
     pub params: v8::Global<v8::String>,
     pub query: v8::Global<v8::String>,
+    pub cookies: v8::Global<v8::String>,
     pub raw_body: v8::Global<v8::String>,
     pub request_id: v8::Global<v8::String>,
     pub titan_req: v8::Global<v8::String>,
@@ -104,7 +143,13 @@ pub struct InternedKeys {
 
 pub struct TitanRuntime {
     pub id: usize,
+    pub root: PathBuf,
+    /// DevTools inspector (inspected worker) or CPU profiler host (created
+    /// on first use); must drop before `isolate` (declared before it)
+    pub inspector: Option<inspector::Inspector>,
     pub isolate: v8::OwnedIsolate,
+    /// Set when a heap cap is configured; must outlive `isolate` (declared after it)
+    pub heap_guard: Option<Box<HeapGuard>>,
     pub context: v8::Global<v8::Context>,
     pub actions: HashMap<String, v8::Global<v8::Function>>,
     pub worker_tx: crossbeam::channel::Sender<crate::runtime::WorkerCommand>,
@@ -118,7 +163,11 @@ pub struct TitanRuntime {
     // Async State
     pub async_rx: crossbeam::channel::Receiver<WorkerAsyncResult>,
     pub async_tx: crossbeam::channel::Sender<WorkerAsyncResult>,
+    /// Promise-based drifts (async actions) awaiting their result
     pub pending_drifts: HashMap<u32, v8::Global<v8::PromiseResolver>>,
+    /// Request objects of async actions suspended on a drift, restored as
+    /// `__titan_req` while their continuation runs
+    pub request_objects: HashMap<u32, v8::Global<v8::Object>>,
     pub pending_requests: HashMap<u32, tokio::sync::oneshot::Sender<crate::runtime::WorkerResult>>,
     pub drift_counter: u32,
     pub request_counter: u32,
@@ -130,17 +179,46 @@ pub struct TitanRuntime {
     pub completed_drifts: HashMap<u32, serde_json::Value>,
     pub active_requests: HashMap<u32, RequestData>,
     pub request_start_counters: HashMap<u32, u32>,
+    /// Time each request waited before this worker picked it up (ms)
+    pub request_queue_ms: HashMap<u32, f64>,
+    /// Cancellation token per request, handed to its drift ops
+    pub request_cancel: HashMap<u32, tokio::sync::watch::Receiver<()>>,
+    /// Pending timers (all requests), keyed by timer ID
+    pub timers: HashMap<u32, Timer>,
+    pub timer_counter: u32,
+    /// Per-request contexts of `Isolation::Context` requests
+    pub request_contexts: HashMap<u32, v8::Global<v8::Context>>,
+    /// Action bundles for `Isolation::Context` requests, scanned on first use
+    /// and compiled once (reset on hot reload)
+    pub isolated_actions: Option<HashMap<String, IsolatedAction>>,
+    /// Isolate was created from the startup snapshot (new contexts come
+    /// pre-bootstrapped)
+    pub from_snapshot: bool,
+}
+
+/// How an `Isolation::Context` request gets its action into a fresh context.
+pub enum IsolatedAction {
+    /// Classic bundle not compiled yet
+    Bundle(PathBuf),
+    /// Classic bundle compiled once, bound to each new context
+    Script(v8::Global<v8::UnboundScript>),
+    /// ES module bundle: module instances are per context, so it is loaded
+    /// again (its compiled code still comes from the code cache)
+    Module(PathBuf),
 }
 
 #[derive(Clone)]
 pub struct RequestData {
     pub action_name: String,
+    /// Function handle resolved when the request arrived (survives hot reload)
+    pub action_fn: Option<v8::Global<v8::Function>>,
     pub body: Option<Bytes>,
     pub method: String,
     pub path: String,
     pub headers: Vec<(String, String)>,
     pub params: Vec<(String, String)>,
     pub query: Vec<(String, String)>,
+    pub extra: Option<serde_json::Value>,
 }
 
 unsafe impl Send for TitanRuntime {}
@@ -151,6 +229,75 @@ impl TitanRuntime {
         let ptr = self as *mut TitanRuntime as *mut std::ffi::c_void;
         self.isolate.set_data(0, ptr);
     }
+
+    /// True once the isolate has hit its heap cap. The isolate is unusable
+    /// afterwards and must be rebuilt.
+    #[inline(always)]
+    pub fn heap_limit_hit(&self) -> bool {
+        self.heap_guard
+            .as_ref()
+            .is_some_and(|g| g.tripped.load(Ordering::Relaxed))
+    }
+
+    /// The context a request runs in: its own under `Isolation::Context`,
+    /// otherwise the worker's shared one.
+    #[inline(always)]
+    pub fn context_for(&self, request_id: u32) -> v8::Global<v8::Context> {
+        self.request_contexts
+            .get(&request_id)
+            .unwrap_or(&self.context)
+            .clone()
+    }
+
+    /// Drop the per-request context of an `Isolation::Context` request.
+    pub fn release_context(&mut self, request_id: u32) {
+        let Some(context) = self.request_contexts.remove(&request_id) else {
+            return;
+        };
+        if let Some(inspector) = self.inspector.as_mut() {
+            let scope = &mut v8::HandleScope::new(&mut self.isolate);
+            let context = v8::Local::new(scope, context);
+            inspector.context_destroyed(context);
+        }
+    }
+
+    /// Cancel every pending timer of a request.
+    #[inline]
+    pub fn clear_timers(&mut self, request_id: u32) {
+        if self.timers.is_empty() {
+            return;
+        }
+        self.timers.retain(|_, timer| {
+            if timer.request_id == request_id {
+                timer.task.abort();
+                false
+            } else {
+                true
+            }
+        });
+    }
+}
+
+// HEAP LIMITS
+
+/// Near-heap-limit watchdog. Boxed so V8 can hold a stable pointer to it.
+pub struct HeapGuard {
+    handle: v8::IsolateHandle,
+    tripped: AtomicBool,
+}
+
+/// Called by V8 right before it would abort the process with an OOM.
+/// Terminates the running script and grants temporary headroom so it can
+/// unwind; the worker then discards the isolate.
+extern "C" fn near_heap_limit_callback(
+    data: *mut std::ffi::c_void,
+    current_heap_limit: usize,
+    _initial_heap_limit: usize,
+) -> usize {
+    let guard = unsafe { &*(data as *const HeapGuard) };
+    guard.tripped.store(true, Ordering::Relaxed);
+    guard.handle.terminate_execution();
+    current_heap_limit * 2
 }
 
 // V8 INITIALIZATION
@@ -174,25 +321,52 @@ pub fn init_runtime_worker(
     tokio_handle: tokio::runtime::Handle,
     global_async_tx: tokio::sync::mpsc::Sender<AsyncOpRequest>,
     stack_size: usize,
+    heap_limit: Option<usize>,
 ) -> TitanRuntime {
     init_v8();
 
-    let params = v8::CreateParams::default();
+    // Boot from the startup snapshot when one is available (not when
+    // inspected: scripts from a snapshot are invisible to the debugger)
+    let snapshot = snapshot::blob().filter(|_| !inspector::inspects(id));
+    let mut params =
+        v8::CreateParams::default().external_references(&**snapshot::external_references());
+    if let Some(blob) = snapshot {
+        params = params.snapshot_blob(blob);
+    }
+    if let Some(max) = heap_limit {
+        params = params.heap_limits(0, max);
+    }
     let mut isolate = v8::Isolate::new(params);
+    // Microtasks run at well-defined points only: after an action call, a
+    // drift resolution or a timer callback
+    isolate.set_microtasks_policy(v8::MicrotasksPolicy::Explicit);
+
+    let mut inspector = inspector::attach(id, &mut isolate);
+
+    let heap_guard = heap_limit.map(|_| {
+        let guard = Box::new(HeapGuard {
+            handle: isolate.thread_safe_handle(),
+            tripped: AtomicBool::new(false),
+        });
+        let data = &*guard as *const HeapGuard as *mut std::ffi::c_void;
+        isolate.add_near_heap_limit_callback(near_heap_limit_callback, data);
+        guard
+    });
 
     let (global_context, actions_map, interned) = {
         let handle_scope = &mut v8::HandleScope::new(&mut isolate);
         let context = v8::Context::new(handle_scope, v8::ContextOptions::default());
+        if let Some(inspector) = inspector.as_mut() {
+            inspector.context_created(context, &format!("titan worker {}", id), true);
+        }
         let scope = &mut v8::ContextScope::new(handle_scope, context);
-        let global = context.global(scope);
 
-        // Inject Titan Runtime APIs
-        inject_extensions(scope, global);
-
-        // Root Metadata
-        let root_str = v8::String::new(scope, root.to_str().unwrap_or(".")).unwrap();
-        let root_key = v8_str(scope, "__titan_root");
-        global.set(scope, root_key.into(), root_str.into());
+        // A snapshot context is already bootstrapped: only pick up the actions
+        let map = if snapshot.is_some() {
+            snapshot::restore_actions(scope, &root)
+        } else {
+            bootstrap(scope, &root, id)
+        };
 
         // Pre-internalize common V8 string keys (created once, reused every request)
         let s_method = v8::String::new(scope, "method").unwrap();
@@ -200,6 +374,7 @@ pub fn init_runtime_worker(
         let s_headers = v8::String::new(scope, "headers").unwrap();
         let s_params = v8::String::new(scope, "params").unwrap();
         let s_query = v8::String::new(scope, "query").unwrap();
+        let s_cookies = v8::String::new(scope, "cookies").unwrap();
         let s_raw_body = v8::String::new(scope, "rawBody").unwrap();
         let s_request_id = v8::String::new(scope, "__titan_request_id").unwrap();
         let s_titan_req = v8::String::new(scope, "__titan_req").unwrap();
@@ -211,49 +386,13 @@ pub fn init_runtime_worker(
             headers: v8::Global::new(scope, s_headers),
             params: v8::Global::new(scope, s_params),
             query: v8::Global::new(scope, s_query),
+            cookies: v8::Global::new(scope, s_cookies),
             raw_body: v8::Global::new(scope, s_raw_body),
             request_id: v8::Global::new(scope, s_request_id),
             titan_req: v8::Global::new(scope, s_titan_req),
             titan_action: v8::Global::new(scope, s_titan_action),
         };
 
-        // Load Actions
-        let mut map = HashMap::new();
-        let action_files = scan_actions(&root);
-        for (name, path) in action_files {
-            if let Ok(code) = fs::read_to_string(&path) {
-                let wrapped_source =
-                    format!("(function() {{ {} }})(); globalThis[\"{}\"];", code, name);
-                let source_str = v8_str(scope, &wrapped_source);
-                let try_catch = &mut v8::TryCatch::new(scope);
-                if let Some(script) = v8::Script::compile(try_catch, source_str, None) {
-                    if let Some(val) = script.run(try_catch) {
-                        if val.is_function() {
-                            let func = v8::Local::<v8::Function>::try_from(val).unwrap();
-                            map.insert(name.clone(), v8::Global::new(try_catch, func));
-                        } else if id == 0 {
-                            println!(
-                                "[V8] Action '{}' did not evaluate to a function: {:?}",
-                                name,
-                                val.to_rust_string_lossy(try_catch)
-                            );
-                        }
-                    } else if id == 0 {
-                        let msg = try_catch
-                            .message()
-                            .map(|m| m.get(try_catch).to_rust_string_lossy(try_catch))
-                            .unwrap_or("Unknown run error".to_string());
-                        println!("[V8] Failed to run action '{}': {}", name, msg);
-                    }
-                } else if id == 0 {
-                    let msg = try_catch
-                        .message()
-                        .map(|m| m.get(try_catch).to_rust_string_lossy(try_catch))
-                        .unwrap_or("Unknown compile error".to_string());
-                    println!("[V8] Failed to compile action '{}': {}", name, msg);
-                }
-            }
-        }
         (v8::Global::new(scope, context), map, interned)
     };
 
@@ -261,7 +400,10 @@ pub fn init_runtime_worker(
 
     TitanRuntime {
         id,
+        root,
+        inspector,
         isolate,
+        heap_guard,
         context: global_context,
         actions: actions_map,
         worker_tx,
@@ -270,6 +412,7 @@ pub fn init_runtime_worker(
         async_rx,
         async_tx,
         pending_drifts: HashMap::new(),
+        request_objects: HashMap::new(),
         pending_requests: HashMap::new(),
         drift_counter: 0,
         request_counter: 0,
@@ -280,12 +423,298 @@ pub fn init_runtime_worker(
         completed_drifts: HashMap::new(),
         active_requests: HashMap::new(),
         request_start_counters: HashMap::new(),
+        request_queue_ms: HashMap::new(),
+        request_cancel: HashMap::new(),
+        timers: HashMap::new(),
+        timer_counter: 0,
+        request_contexts: HashMap::new(),
+        isolated_actions: None,
+        from_snapshot: snapshot.is_some(),
+    }
+}
+
+/// Set up a fresh context: Titan APIs, titan_core.js, extension JS, root
+/// metadata and every action bundle. Also what the startup snapshot captures.
+pub fn bootstrap(
+    scope: &mut v8::HandleScope,
+    root: &PathBuf,
+    id: usize,
+) -> HashMap<String, v8::Global<v8::Function>> {
+    // Extensions and actions share one ES module graph
+    let _pass = modules::Pass::enter();
+    install_globals(scope, root, id);
+    load_actions(scope, root, id)
+}
+
+/// Titan APIs and root metadata on the current context's global.
+fn install_globals(scope: &mut v8::HandleScope, root: &PathBuf, id: usize) {
+    let context = scope.get_current_context();
+    let global = context.global(scope);
+
+    // Inject Titan Runtime APIs
+    inject_extensions(scope, global, inspector::inspects(id));
+
+    // Root Metadata
+    let root_str = v8::String::new(scope, root.to_str().unwrap_or(".")).unwrap();
+    let root_key = v8_str(scope, "__titan_root");
+    global.set(scope, root_key.into(), root_str.into());
+}
+
+/// Fresh context for an `Isolation::Context` request, kept until the request
+/// is released. Returns the action function bound to it.
+///
+/// From a current snapshot the context comes fully bootstrapped. Otherwise
+/// the Titan APIs (unless the snapshot provided them) and the action's own
+/// bundle are evaluated into it — slower, but still only one bundle, and
+/// compiled only once per worker.
+pub fn isolated_context(
+    runtime: &mut TitanRuntime,
+    request_id: u32,
+    action_name: &str,
+) -> Option<v8::Global<v8::Function>> {
+    let root = runtime.root.clone();
+    let id = runtime.id;
+    let from_snapshot = runtime.from_snapshot;
+    let snapshot_current = from_snapshot && snapshot::blob().is_some();
+
+    let handle_scope = &mut v8::HandleScope::new(&mut runtime.isolate);
+    let context = v8::Context::new(handle_scope, v8::ContextOptions::default());
+    if let Some(inspector) = runtime.inspector.as_mut() {
+        inspector.context_created(context, &format!("request {}", request_id), false);
+    }
+    let scope = &mut v8::ContextScope::new(handle_scope, context);
+    let _pass = modules::Pass::enter();
+
+    if !from_snapshot {
+        install_globals(scope, &root, id);
+    }
+
+    let action_fn = if snapshot_current {
+        let global = context.global(scope);
+        let key = v8_str(scope, action_name);
+        global
+            .get(scope, key.into())
+            .and_then(|v| v8::Local::<v8::Function>::try_from(v).ok())
+            .map(|f| v8::Global::new(scope, f))
+    } else {
+        let actions = runtime.isolated_actions.get_or_insert_with(|| {
+            scan_actions(&root)
+                .into_iter()
+                .map(|(name, path)| {
+                    let entry = if modules::is_module(&path) {
+                        IsolatedAction::Module(path)
+                    } else {
+                        IsolatedAction::Bundle(path)
+                    };
+                    (name, entry)
+                })
+                .collect()
+        });
+        match actions.get(action_name) {
+            Some(IsolatedAction::Script(script)) => run_script(scope, action_name, script, id),
+            Some(IsolatedAction::Module(path)) => load_action(scope, action_name, path, id),
+            Some(IsolatedAction::Bundle(path)) => {
+                let loaded = load_script(scope, action_name, &path.clone(), id);
+                loaded.map(|(func, script)| {
+                    actions.insert(action_name.to_string(), IsolatedAction::Script(script));
+                    func
+                })
+            }
+            None => None,
+        }
+    };
+
+    runtime
+        .request_contexts
+        .insert(request_id, v8::Global::new(scope, context));
+    action_fn
+}
+
+// ACTION LOADING
+
+/// Compile and evaluate every action bundle into the current context.
+/// Only worker 0 reports failures to avoid N copies of the same message.
+pub fn load_actions(
+    scope: &mut v8::HandleScope,
+    root: &PathBuf,
+    id: usize,
+) -> HashMap<String, v8::Global<v8::Function>> {
+    let _pass = modules::Pass::enter();
+    let mut map = HashMap::new();
+    for (name, path) in scan_actions(root) {
+        if let Some(func) = load_action(scope, &name, &path, id) {
+            map.insert(name, func);
+        }
+    }
+    map
+}
+
+/// Compile (through the code cache) and evaluate one action bundle.
+/// `.mjs` bundles are loaded as ES modules.
+fn load_action(
+    scope: &mut v8::HandleScope,
+    name: &str,
+    path: &PathBuf,
+    id: usize,
+) -> Option<v8::Global<v8::Function>> {
+    if modules::is_module(path) {
+        return match modules::load_action(scope, name, path) {
+            Ok(func) => Some(func),
+            Err(e) => {
+                if id == 0 {
+                    println!("[V8] Failed to load action module '{}': {}", name, e);
+                }
+                None
+            }
+        };
     }
+
+    load_script(scope, name, path, id).map(|(func, _)| func)
+}
+
+/// Compile (through the code cache) and run a classic bundle. The compiled
+/// script is returned too, so other contexts can run it without compiling.
+fn load_script(
+    scope: &mut v8::HandleScope,
+    name: &str,
+    path: &PathBuf,
+    id: usize,
+) -> Option<(v8::Global<v8::Function>, v8::Global<v8::UnboundScript>)> {
+    let code = fs::read_to_string(path).ok()?;
+    // The wrapper gets a line of its own (origin line offset -1), so stack
+    // positions match the bundle and its source map
+    let wrapped_source = format!("(function() {{\n{}\n}})(); globalThis[\"{}\"];", code, name);
+    let source_str = v8_str(scope, &wrapped_source);
+    let script_name = v8_str(scope, &source_maps::script_name(path));
+    let origin = v8::ScriptOrigin::new(
+        scope,
+        script_name.into(),
+        -1,
+        0,
+        false,
+        0,
+        None,
+        false,
+        false,
+        false,
+        None,
+    );
+    let hash = code_cache::hash(&wrapped_source);
+    let cached = code_cache::get(path, hash);
+    let try_catch = &mut v8::TryCatch::new(scope);
+    let (script, rejected) = code_cache::compile(
+        try_catch,
+        source_str,
+        &origin,
+        cached.as_deref().map(|c| c.as_slice()),
+    );
+
+    let Some(script) = script else {
+        if id == 0 {
+            let msg = try_catch
+                .message()
+                .map(|m| m.get(try_catch).to_rust_string_lossy(try_catch))
+                .unwrap_or("Unknown compile error".to_string());
+            println!("[V8] Failed to compile action '{}': {}", name, msg);
+        }
+        return None;
+    };
+
+    let Some(val) = script.run(try_catch) else {
+        if id == 0 {
+            let msg = try_catch
+                .message()
+                .map(|m| m.get(try_catch).to_rust_string_lossy(try_catch))
+                .unwrap_or("Unknown run error".to_string());
+            println!("[V8] Failed to run action '{}': {}", name, msg);
+        }
+        return None;
+    };
+
+    if rejected {
+        code_cache::reject(path);
+    }
+    if cached.is_none() || rejected {
+        code_cache::store(try_catch, path, hash, script);
+    }
+
+    let unbound = script.get_unbound_script(try_catch);
+    let func = action_function(try_catch, name, val, id)?;
+    Some((func, v8::Global::new(try_catch, unbound)))
+}
+
+/// Run a bundle compiled by `load_script` in the current context.
+fn run_script(
+    scope: &mut v8::HandleScope,
+    name: &str,
+    script: &v8::Global<v8::UnboundScript>,
+    id: usize,
+) -> Option<v8::Global<v8::Function>> {
+    let unbound = v8::Local::new(scope, script);
+    let try_catch = &mut v8::TryCatch::new(scope);
+    let script = unbound.bind_to_current_context(try_catch);
+    let Some(val) = script.run(try_catch) else {
+        if id == 0 {
+            let msg = try_catch
+                .message()
+                .map(|m| m.get(try_catch).to_rust_string_lossy(try_catch))
+                .unwrap_or("Unknown run error".to_string());
+            println!("[V8] Failed to run action '{}': {}", name, msg);
+        }
+        return None;
+    };
+    action_function(try_catch, name, val, id)
+}
+
+/// The action function a bundle evaluated to.
+fn action_function(
+    scope: &mut v8::HandleScope,
+    name: &str,
+    val: v8::Local<v8::Value>,
+    id: usize,
+) -> Option<v8::Global<v8::Function>> {
+    match v8::Local::<v8::Function>::try_from(val) {
+        Ok(func) => Some(v8::Global::new(scope, func)),
+        Err(_) => {
+            if id == 0 {
+                println!(
+                    "[V8] Action '{}' did not evaluate to a function: {:?}",
+                    name,
+                    val.to_rust_string_lossy(scope)
+                );
+            }
+            None
+        }
+    }
+}
+
+/// Hot reload: recompile actions from disk and swap the worker's action map.
+/// Requests already suspended keep their pinned `RequestData::action_fn`.
+pub fn reload_actions(runtime: &mut TitanRuntime) {
+    let context_global = runtime.context.clone();
+    let root = runtime.root.clone();
+    let id = runtime.id;
+
+    let map = {
+        let handle_scope = &mut v8::HandleScope::new(&mut runtime.isolate);
+        let context = v8::Local::new(handle_scope, context_global);
+        let scope = &mut v8::ContextScope::new(handle_scope, context);
+        load_actions(scope, &root, id)
+    };
+
+    runtime.actions = map;
+    runtime.action_field_usage.clear();
+    runtime.isolated_actions = None;
 }
 
 // EXTENSION INJECTION
 
-pub fn inject_extensions(scope: &mut v8::HandleScope, global: v8::Local<v8::Object>) {
+/// `inspected`: the worker DevTools attaches to (see `web::inject_web_globals`).
+pub fn inject_extensions(
+    scope: &mut v8::HandleScope,
+    global: v8::Local<v8::Object>,
+    inspected: bool,
+) {
     let gt_key = v8_str(scope, "globalThis");
     global.set(scope, gt_key.into(), global.into());
 
@@ -295,6 +724,8 @@ pub fn inject_extensions(scope: &mut v8::HandleScope, global: v8::Local<v8::Obje
         .create_data_property(scope, t_key.into(), t_obj.into())
         .unwrap();
 
+    // Web globals first: titan_core.js and extensions may rely on them
+    web::inject_web_globals(scope, inspected);
     builtin::inject_builtin_extensions(scope, global, t_obj);
     external::inject_external_extensions(scope, global, t_obj);
 
@@ -417,19 +848,20 @@ pub fn execute_action_optimized(
     runtime: &mut TitanRuntime,
     request_id: u32,
     action_name: &str,
+    action_fn: Option<v8::Global<v8::Function>>,
     req_body: Option<bytes::Bytes>,
     req_method: &str,
     req_path: &str,
     headers: &[(String, String)],
     params: &[(String, String)],
     query: &[(String, String)],
+    extra: Option<&serde_json::Value>,
 ) {
     // =========================================================================
     // STEP 1: Extract all data from runtime BEFORE borrowing isolate.
     // v8::Global::clone() is O(1) refcount bump — no V8 heap allocation.
     // =========================================================================
-    let context_global = runtime.context.clone();
-    let actions_map = runtime.actions.clone();
+    let context_global = runtime.context_for(request_id);
 
     let ik = runtime.interned_keys.as_ref().unwrap();
     let gk_method = ik.method.clone();
@@ -437,6 +869,7 @@ pub fn execute_action_optimized(
     let gk_headers = ik.headers.clone();
     let gk_params = ik.params.clone();
     let gk_query = ik.query.clone();
+    let gk_cookies = ik.cookies.clone();
     let gk_raw_body = ik.raw_body.clone();
     let gk_request_id = ik.request_id.clone();
     let gk_titan_req = ik.titan_req.clone();
@@ -491,6 +924,18 @@ pub fn execute_action_optimized(
     }
     req_obj.set(scope, h_key.into(), h_obj.into());
 
+    // cookies — parsed natively from the (already folded) Cookie header
+    let c_key = v8::Local::new(scope, &gk_cookies);
+    let c_obj = v8::Object::new(scope);
+    if let Some((_, raw)) = headers.iter().find(|(k, _)| k.eq_ignore_ascii_case("cookie")) {
+        for (k, v) in parse_cookies(raw) {
+            let k_v8 = v8_str(scope, &k);
+            let v_v8 = v8_str(scope, &v);
+            c_obj.set(scope, k_v8.into(), v_v8.into());
+        }
+    }
+    req_obj.set(scope, c_key.into(), c_obj.into());
+
     // params
     let params_key = v8::Local::new(scope, &gk_params);
     let p_obj = v8::Object::new(scope);
@@ -511,6 +956,16 @@ pub fn execute_action_optimized(
     }
     req_obj.set(scope, q_key.into(), q_obj.into());
 
+    // extra — synthetic context from the server (error info, cron/job metadata, ...)
+    if let Some(serde_json::Value::Object(map)) = extra {
+        for (k, v) in map {
+            let k_v8 = v8_str(scope, k);
+            let json_str = v8_str(scope, &v.to_string());
+            let v_v8 = v8::json::parse(scope, json_str).unwrap_or_else(|| v8::null(scope).into());
+            req_obj.set(scope, k_v8.into(), v_v8);
+        }
+    }
+
     // Set __titan_req on global
     let global = context.global(scope);
     let req_tr_key = v8::Local::new(scope, &gk_titan_req);
@@ -519,44 +974,155 @@ pub fn execute_action_optimized(
     // =========================================================================
     // STEP 3: Execute action function
     // =========================================================================
-    if let Some(action_global) = actions_map.get(action_name) {
+    if let Some(action_global) = action_fn {
         let action_fn = v8::Local::new(scope, action_global);
         let tr_act_key = v8::Local::new(scope, &gk_titan_action);
         let tr_act_val = v8_str(scope, action_name);
         global.set(scope, tr_act_key.into(), tr_act_val.into());
         let try_catch = &mut v8::TryCatch::new(scope);
 
-        if action_fn
+        let called = action_fn
             .call(try_catch, global.into(), &[req_obj.into()])
-            .is_some()
+            .is_some();
+
+        let msg = if called {
+            None
+        } else if runtime
+            .heap_guard
+            .as_ref()
+            .is_some_and(|g| g.tripped.load(Ordering::Relaxed))
         {
-            return;
-        }
+            Some("Heap limit exceeded".to_string())
+        } else if try_catch.has_terminated() {
+            // Abandoned mid-script (see `runtime::Watchdog`)
+            Some("Request cancelled".to_string())
+        } else {
+            Some(
+                try_catch
+                    .message()
+                    .map(|m| m.get(try_catch).to_rust_string_lossy(try_catch))
+                    .unwrap_or("Unknown error".to_string()),
+            )
+        };
+        let stack = try_catch
+            .stack_trace()
+            .filter(|s| s.is_string())
+            .map(|s| source_maps::remap_stack(&s.to_rust_string_lossy(try_catch)));
 
-        let msg = try_catch
-            .message()
-            .map(|m| m.get(try_catch).to_rust_string_lossy(try_catch))
-            .unwrap_or("Unknown error".to_string());
+        // Drain promise jobs (async actions, queueMicrotask)
+        try_catch.perform_microtask_checkpoint();
+
+        let Some(msg) = msg else {
+            return;
+        };
 
         if msg.contains("SUSPEND") {
             return;
         }
 
         println!("[Isolate {}] Action Error: {}", runtime.id, msg);
+        if let Some(stack) = &stack {
+            println!("{}", stack);
+        }
         if let Some(tx) = runtime.pending_requests.remove(&request_id) {
-            let _ = tx.send(crate::runtime::WorkerResult {
-                json: serde_json::json!({"error": msg}),
-                timings: vec![],
-            });
+            let _ = tx.send(crate::runtime::WorkerResult::json(
+                serde_json::json!({"error": msg, "stack": stack}),
+                vec![],
+            ));
         }
     } else {
         if let Some(tx) = runtime.pending_requests.remove(&request_id) {
-            let _ = tx.send(crate::runtime::WorkerResult {
-                json: serde_json::json!({"error": format!("Action '{}' not found", action_name)}),
-                timings: vec![],
-            });
+            let _ = tx.send(crate::runtime::WorkerResult::json(
+                serde_json::json!({"error": format!("Action '{}' not found", action_name)}),
+                vec![],
+            ));
+        }
+    }
+}
+
+/// Resolve a promise-based drift and run the continuation of the async
+/// action that awaited it (microtasks are pumped before returning).
+pub fn resolve_drift(
+    runtime: &mut TitanRuntime,
+    request_id: u32,
+    resolver: v8::Global<v8::PromiseResolver>,
+    result: serde_json::Value,
+) {
+    let context_global = runtime.context_for(request_id);
+    let req_obj = runtime.request_objects.get(&request_id).cloned();
+    let gk_titan_req = runtime.interned_keys.as_ref().unwrap().titan_req.clone();
+
+    let isolate = &mut runtime.isolate;
+    let handle_scope = &mut v8::HandleScope::new(isolate);
+    let context = v8::Local::new(handle_scope, context_global);
+    let scope = &mut v8::ContextScope::new(handle_scope, context);
+
+    // The continuation may drift again; it must see its own request
+    if let Some(req_obj) = req_obj {
+        let global = context.global(scope);
+        let key = v8::Local::new(scope, &gk_titan_req);
+        let val = v8::Local::new(scope, &req_obj);
+        global.set(scope, key.into(), val.into());
+    }
+
+    let json_str = v8_str(scope, &result.to_string());
+    let value = v8::json::parse(scope, json_str).unwrap_or_else(|| v8::null(scope).into());
+    let resolver = v8::Local::new(scope, &resolver);
+    resolver.resolve(scope, value);
+    scope.perform_microtask_checkpoint();
+}
+
+/// Schedule `WorkerCommand::Timer` for `timer_id` after `delay`.
+pub fn arm_timer(
+    runtime: &TitanRuntime,
+    timer_id: u32,
+    delay: std::time::Duration,
+) -> tokio::task::AbortHandle {
+    let worker_tx = runtime.worker_tx.clone();
+    runtime
+        .tokio_handle
+        .spawn(async move {
+            tokio::time::sleep(delay).await;
+            let _ = worker_tx.send(crate::runtime::WorkerCommand::Timer { timer_id });
+        })
+        .abort_handle()
+}
+
+/// Run a due timer callback with its request restored as `__titan_req`,
+/// then drain microtasks. A throwing callback is logged; the request is not
+/// failed (as in browsers, nothing is waiting on the callback itself).
+pub fn fire_timer(
+    runtime: &mut TitanRuntime,
+    request_id: u32,
+    callback: v8::Global<v8::Function>,
+    request: v8::Global<v8::Object>,
+) {
+    let context_global = runtime.context_for(request_id);
+    let gk_titan_req = runtime.interned_keys.as_ref().unwrap().titan_req.clone();
+    let id = runtime.id;
+
+    let isolate = &mut runtime.isolate;
+    let handle_scope = &mut v8::HandleScope::new(isolate);
+    let context = v8::Local::new(handle_scope, context_global);
+    let scope = &mut v8::ContextScope::new(handle_scope, context);
+
+    let global = context.global(scope);
+    let key = v8::Local::new(scope, &gk_titan_req);
+    let val = v8::Local::new(scope, &request);
+    global.set(scope, key.into(), val.into());
+
+    let callback = v8::Local::new(scope, &callback);
+    let try_catch = &mut v8::TryCatch::new(scope);
+    if callback.call(try_catch, global.into(), &[]).is_none() {
+        let msg = try_catch
+            .message()
+            .map(|m| m.get(try_catch).to_rust_string_lossy(try_catch))
+            .unwrap_or("Unknown error".to_string());
+        if !msg.contains("SUSPEND") {
+            println!("[Isolate {}] Timer Error: {}", id, msg);
         }
     }
+    try_catch.perform_microtask_checkpoint();
 }
 
 // V8 HELPERS
@@ -0,0 +1,328 @@
+//! Source Maps for action bundles
+//!
+//! `titan build` emits a Source Map v3 file next to every bundle. They are
+//! loaded at boot (and again on hot reload) so stack traces of action errors
+//! point at the original file, line and column instead of the bundle.
+//!
+//! 1. A bundle's map is found through its `//# sourceMappingURL=` comment
+//!    (a relative file or an inline `data:` URL), else `<bundle>.map`.
+//! 2. `remap_stack` rewrites every `file:line:col` of a known bundle in a V8
+//!    stack trace; unknown files and unmapped positions are left untouched.
+//! 3. Original paths are shown relative to the project root.
+//!
+//! Remapped stacks are logged with the error and, outside production, sent
+//! in the default 500 response.
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Component, Path, PathBuf};
+use std::sync::{Arc, OnceLock, RwLock};
+
+use base64::Engine;
+use regex::Regex;
+use serde_json::Value;
+
+use super::PROJECT_ROOT;
+use crate::action_management::scan_actions;
+use crate::utils::{blue, gray, green, red};
+
+static MAPS: OnceLock<RwLock<HashMap<String, Arc<SourceMap>>>> = OnceLock::new();
+
+fn maps() -> &'static RwLock<HashMap<String, Arc<SourceMap>>> {
+    MAPS.get_or_init(|| RwLock::new(HashMap::new()))
+}
+
+/// One mapped position: generated column → source, line and column.
+#[derive(Clone, Copy)]
+struct Segment {
+    column: u32,
+    source: u32,
+    line: u32,
+    source_column: u32,
+}
+
+pub struct SourceMap {
+    sources: Vec<String>,
+    /// Segments per generated line (0-based), sorted by column
+    lines: Vec<Vec<Segment>>,
+}
+
+impl SourceMap {
+    /// Parse a Source Map v3 document. `dir` is the map's own directory,
+    /// which relative `sources` are based on.
+    pub fn parse(json: &str, dir: &Path) -> Result<Self, String> {
+        let map: Value = serde_json::from_str(json).map_err(|e| e.to_string())?;
+        if map["version"].as_u64() != Some(3) {
+            return Err("only version 3 source maps are supported".to_string());
+        }
+        if map.get("sections").is_some() {
+            return Err("indexed source maps are not supported".to_string());
+        }
+
+        let root = map["sourceRoot"].as_str().unwrap_or("");
+        let sources = map["sources"]
+            .as_array()
+            .map(|list| {
+                list.iter()
+                    .map(|s| display_path(dir, root, s.as_str().unwrap_or("")))
+                    .collect()
+            })
+            .unwrap_or_default();
+        let lines = decode_mappings(map["mappings"].as_str().unwrap_or(""))?;
+        Ok(Self { sources, lines })
+    }
+
+    /// Original (source, line, column) for a generated 1-based position.
+    pub fn lookup(&self, line: u32, column: u32) -> Option<(&str, u32, u32)> {
+        let segments = self.lines.get(line.checked_sub(1)? as usize)?;
+        let column = column.saturating_sub(1);
+        let index = segments
+            .partition_point(|s| s.column <= column)
+            .checked_sub(1)?;
+        let segment = segments[index];
+        let source = self.sources.get(segment.source as usize)?;
+        Some((source, segment.line + 1, segment.source_column + 1))
+    }
+}
+
+/// Decode the `mappings` field (Base64 VLQ, `;` per line, `,` per segment).
+fn decode_mappings(mappings: &str) -> Result<Vec<Vec<Segment>>, String> {
+    let mut lines = Vec::new();
+    // Everything but the generated column is relative across lines
+    let (mut source, mut line, mut source_column) = (0i64, 0i64, 0i64);
+
+    for text in mappings.split(';') {
+        let mut segments = Vec::new();
+        let mut column = 0i64;
+        for segment in text.split(',').filter(|s| !s.is_empty()) {
+            let fields = decode_vlq(segment)?;
+            column += fields[0];
+            // One-field segments map to nothing
+            if fields.len() < 4 {
+                continue;
+            }
+            source += fields[1];
+            line += fields[2];
+            source_column += fields[3];
+            if column < 0 || source < 0 || line < 0 || source_column < 0 {
+                return Err("negative position in mappings".to_string());
+            }
+            segments.push(Segment {
+                column: column as u32,
+                source: source as u32,
+                line: line as u32,
+                source_column: source_column as u32,
+            });
+        }
+        segments.sort_by_key(|s| s.column);
+        lines.push(segments);
+    }
+    Ok(lines)
+}
+
+fn decode_vlq(segment: &str) -> Result<Vec<i64>, String> {
+    let mut values = Vec::with_capacity(5);
+    let (mut value, mut shift) = (0i64, 0u32);
+    for byte in segment.bytes() {
+        let digit = match byte {
+            b'A'..=b'Z' => byte - b'A',
+            b'a'..=b'z' => byte - b'a' + 26,
+            b'0'..=b'9' => byte - b'0' + 52,
+            b'+' => 62,
+            b'/' => 63,
+            _ => return Err(format!("invalid character '{}' in mappings", byte as char)),
+        } as i64;
+        if shift > 60 {
+            return Err("VLQ value too large".to_string());
+        }
+        value += (digit & 31) << shift;
+        if digit & 32 != 0 {
+            shift += 5;
+            continue;
+        }
+        values.push(if value & 1 == 1 {
+            -(value >> 1)
+        } else {
+            value >> 1
+        });
+        value = 0;
+        shift = 0;
+    }
+    if shift != 0 || values.is_empty() {
+        return Err("truncated VLQ segment".to_string());
+    }
+    Ok(values)
+}
+
+/// Source path as shown in stack traces: relative to the project root when
+/// it lives inside it.
+fn display_path(dir: &Path, root: &str, source: &str) -> String {
+    if source.contains("://") || source.starts_with("webpack:") {
+        return source.to_string();
+    }
+    let path = normalize(&dir.join(root).join(source));
+    PROJECT_ROOT
+        .get()
+        .and_then(|project| project.canonicalize().ok())
+        .and_then(|project| path.strip_prefix(&project).ok().map(Path::to_path_buf))
+        .unwrap_or(path)
+        .to_string_lossy()
+        .into_owned()
+}
+
+/// Lexical `.` / `..` resolution (sources need not exist on this machine).
+fn normalize(path: &Path) -> PathBuf {
+    let mut out = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                if !out.pop() {
+                    out.push("..");
+                }
+            }
+            other => out.push(other),
+        }
+    }
+    out
+}
+
+/// Map for `bundle`: its `sourceMappingURL`, else `<bundle>.map`.
+fn read_map(bundle: &Path) -> Option<Result<SourceMap, String>> {
+    let code = fs::read_to_string(bundle).ok()?;
+    let parent = bundle.parent().filter(|p| !p.as_os_str().is_empty());
+    let dir = fs::canonicalize(parent.unwrap_or(Path::new("."))).ok()?;
+    let url = code
+        .lines()
+        .rev()
+        .take(5)
+        .find_map(|line| line.trim().strip_prefix("//# sourceMappingURL="));
+
+    let (json, map_dir) = match url {
+        Some(url) if url.starts_with("data:") => {
+            let (_, data) = url.split_once(";base64,")?;
+            let bytes = base64::engine::general_purpose::STANDARD
+                .decode(data)
+                .ok()?;
+            (String::from_utf8(bytes).ok()?, dir)
+        }
+        Some(url) => {
+            let path = dir.join(url);
+            let map_dir = path.parent().map_or(dir.clone(), Path::to_path_buf);
+            (fs::read_to_string(&path).ok()?, map_dir)
+        }
+        None => {
+            let mut path = bundle.as_os_str().to_owned();
+            path.push(".map");
+            (fs::read_to_string(PathBuf::from(path)).ok()?, dir)
+        }
+    };
+    Some(SourceMap::parse(&json, &map_dir))
+}
+
+/// Name a bundle is compiled under (its script origin), matched by
+/// `remap_stack`.
+pub fn script_name(bundle: &Path) -> String {
+    bundle
+        .canonicalize()
+        .unwrap_or_else(|_| bundle.to_path_buf())
+        .to_string_lossy()
+        .into_owned()
+}
+
+/// (Re)load the maps of every action bundle.
+pub fn load(root: &PathBuf) {
+    let mut loaded = HashMap::new();
+    for (name, bundle) in scan_actions(root) {
+        match read_map(&bundle) {
+            Some(Ok(map)) => {
+                loaded.insert(script_name(&bundle), Arc::new(map));
+            }
+            Some(Err(e)) => println!(
+                "{} {} {} ({})",
+                blue("[Titan]"),
+                red("Ignoring invalid source map for"),
+                name,
+                e
+            ),
+            None => {}
+        }
+    }
+
+    if !loaded.is_empty() {
+        println!(
+            "{} {} {}",
+            blue("[Titan]"),
+            green("Source maps loaded"),
+            gray(&format!("({} bundle(s))", loaded.len()))
+        );
+    }
+    *maps().write().unwrap() = loaded;
+}
+
+/// Rewrite bundle positions in a V8 stack trace to original sources.
+pub fn remap_stack(stack: &str) -> String {
+    static FRAME: OnceLock<Regex> = OnceLock::new();
+    let maps = maps().read().unwrap();
+    if maps.is_empty() {
+        return stack.to_string();
+    }
+
+    let frame = FRAME.get_or_init(|| Regex::new(r"([^\s()]+):(\d+):(\d+)").unwrap());
+    frame
+        .replace_all(stack, |caps: &regex::Captures| {
+            let file = caps[1].strip_prefix("file://").unwrap_or(&caps[1]);
+            let position = caps[2].parse().ok().zip(caps[3].parse().ok());
+            maps.get(file)
+                .zip(position)
+                .and_then(|(map, (line, column))| map.lookup(line, column))
+                .map(|(source, line, column)| format!("{}:{}:{}", source, line, column))
+                .unwrap_or_else(|| caps[0].to_string())
+        })
+        .into_owned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn decode_vlq_values() {
+        assert_eq!(decode_vlq("A").unwrap(), vec![0]);
+        assert_eq!(decode_vlq("C").unwrap(), vec![1]);
+        assert_eq!(decode_vlq("D").unwrap(), vec![-1]);
+        assert_eq!(decode_vlq("gB").unwrap(), vec![16]);
+        assert_eq!(decode_vlq("2H").unwrap(), vec![123]);
+        assert_eq!(decode_vlq("AACA").unwrap(), vec![0, 0, 1, 0]);
+    }
+
+    #[test]
+    fn decode_vlq_rejects_bad_input() {
+        assert!(decode_vlq("").is_err());
+        assert!(decode_vlq("g").is_err());
+        assert!(decode_vlq("A*").is_err());
+        assert!(decode_vlq("gggggggggggggA").is_err());
+    }
+
+    #[test]
+    fn lookup_maps_generated_positions() {
+        let json = r#"{"version":3,"sources":["src/a.ts"],"mappings":"AAAA,IAAI;AACA"}"#;
+        let map = SourceMap::parse(json, Path::new("/proj")).unwrap();
+        let source = Path::new("/proj/src/a.ts").to_string_lossy().into_owned();
+
+        assert_eq!(map.lookup(1, 1), Some((source.as_str(), 1, 1)));
+        assert_eq!(map.lookup(1, 5), Some((source.as_str(), 1, 5)));
+        // Columns between segments belong to the segment before them
+        assert_eq!(map.lookup(1, 10), Some((source.as_str(), 1, 5)));
+        // Source columns carry over from the previous line
+        assert_eq!(map.lookup(2, 3), Some((source.as_str(), 2, 5)));
+        assert_eq!(map.lookup(3, 1), None);
+        assert_eq!(map.lookup(0, 1), None);
+    }
+
+    #[test]
+    fn parse_rejects_unsupported_maps() {
+        assert!(SourceMap::parse(r#"{"version":2,"mappings":""}"#, Path::new("/")).is_err());
+        assert!(SourceMap::parse(r#"{"version":3,"sections":[]}"#, Path::new("/")).is_err());
+    }
+}
@@ -5,22 +5,295 @@
 //! 2. Bounded channel capacity for pipeline handling.
 //! 3. Batch-ready architecture for HTTP pipelining.
 //! 4. Zero-copy / deferred cloning where possible.
+//! 5. Admission control: when every worker queue is full, requests wait
+//!    asynchronously (bounded count and time) and are shed with 503 beyond that.
+//! 6. Pluggable scheduling (`__config.scheduler`): round-robin, least-loaded
+//!    or power-of-two-choices over per-worker in-flight counts.
+//! 7. Supervision: a worker thread that dies by panic is respawned with a
+//!    fresh `TitanRuntime`; its queued requests move to the new worker.
+//! 8. Request-scoped timers: `setTimeout` / `setInterval` sleep on tokio and
+//!    come back as `WorkerCommand::Timer`; a finished request's timers are
+//!    dropped.
+//! 9. Action-to-action calls (`t.call`): a drift op that dispatches the
+//!    callee through the pool like any request, up to `MAX_CALL_DEPTH` deep.
+//! 10. Diagnostics (`WorkerCommand::Diagnose`): CPU profiles, heap snapshots
+//!     and heap statistics per isolate, for the admin endpoints.
+//! 11. Inspector pinning: while DevTools is attached (`TITAN_INSPECT`), every
+//!     request goes to the inspected worker.
+//! 12. Autoscaling: with `__config.workers_min` < `workers_max` the pool grows
+//!     on queue depth and suspended requests and shrinks when idle. Retired
+//!     workers finish their suspended requests before exiting.
+//! 13. CPU affinity (`__config.pin_workers`, Linux): worker N runs on the
+//!     Nth allowed core (round-robin).
+//! 14. Abandoned requests (client disconnect, `request_timeout_ms`): a script
+//!     still running for one is terminated (`Watchdog`), and a suspended one
+//!     is released without waiting for what it waits on.
 
 use bytes::Bytes;
-use crossbeam::channel::{bounded, Sender, TrySendError};
-use std::sync::atomic::{AtomicUsize, Ordering};
+use crossbeam::channel::{bounded, Receiver, Sender, TrySendError};
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex, OnceLock, RwLock, Weak};
 use std::thread;
+use std::time::{Duration, Instant};
 use tokio::sync::mpsc;
-use tokio::sync::oneshot;
+use tokio::sync::{Notify, oneshot, watch};
 use smallvec::SmallVec;
 
-use crate::extensions::{self, AsyncOpRequest, TitanRuntime, WorkerAsyncResult};
+use crate::extensions::diagnostics::{self, Diagnostic};
+use crate::extensions::{self, builtin, inspector, AsyncOpRequest, TitanAsyncOp, TitanRuntime, WorkerAsyncResult};
+use crate::reload::RouteStore;
+use crate::utils::{blue, gray, red};
+
+const CHANNEL_CAPACITY: usize = 256;
+
+/// How often the supervisor re-evaluates the pool size.
+const SCALE_INTERVAL: Duration = Duration::from_millis(250);
+/// Grow when the worker channels hold this many requests per worker...
+const GROW_QUEUED_PER_WORKER: usize = 2;
+/// ...or the workers hold this many suspended requests each.
+const GROW_SUSPENDED_PER_WORKER: usize = 128;
+/// Shrink after this many consecutive idle ticks (10 s).
+const SHRINK_AFTER_TICKS: u32 = 40;
+
+/// How long an admin diagnostic may wait for its worker (heap snapshots of
+/// big heaps are slow).
+const DIAGNOSE_TIMEOUT: Duration = Duration::from_secs(120);
+
+/// Nested `t.call` limit; deeper chains are almost always a loop.
+pub const MAX_CALL_DEPTH: usize = 8;
+
+/// The pool `t.call` dispatches into (set once by `install`).
+static MANAGER: OnceLock<Weak<RuntimeManager>> = OnceLock::new();
+/// Routes `t.call` resolves a callee's isolation from (set by `install`).
+static ROUTES: OnceLock<Weak<RouteStore>> = OnceLock::new();
+/// Production mode: `t.call` results never carry a callee's stack trace.
+static PRODUCTION: AtomicBool = AtomicBool::new(true);
 
 pub struct RuntimeManager {
-    request_txs: Vec<Sender<WorkerCommand>>,
+    /// One slot per possible worker (`pool.max`); the first `active` run
+    slots: Arc<Vec<WorkerSlot>>,
+    active: Arc<AtomicUsize>,
+    pool: PoolConfig,
+    admission: Arc<Admission>,
+    scheduler: Scheduler,
     round_robin_counter: AtomicUsize,
-    num_workers: usize,
-    _workers: Vec<thread::JoinHandle<()>>,
+    _supervisor: thread::JoinHandle<()>,
+}
+
+/// Worker pool size and placement.
+#[derive(Clone, Copy, Debug)]
+pub struct PoolConfig {
+    /// Workers started at boot; the pool never shrinks below it
+    pub min: usize,
+    /// Upper bound when autoscaling (`min == max` is a fixed pool)
+    pub max: usize,
+    /// Pin worker threads to cores (Linux only)
+    pub pin_cores: bool,
+}
+
+impl PoolConfig {
+    #[inline(always)]
+    fn autoscale(&self) -> bool {
+        self.max > self.min
+    }
+}
+
+/// Async admission queue for requests that find every worker channel full.
+/// Workers signal `space` after dequeuing while anyone is waiting.
+struct Admission {
+    max_queue: usize,
+    max_wait: Duration,
+    waiting: AtomicUsize,
+    space: Notify,
+}
+
+/// Decrements the admission queue length when a waiter leaves (including
+/// when the client disconnects and the request future is dropped).
+struct QueueTicket<'a>(&'a AtomicUsize);
+
+impl Drop for QueueTicket<'_> {
+    fn drop(&mut self) {
+        self.0.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+pub enum ExecuteError {
+    /// Admission queue full or max wait exceeded (→ 503 + Retry-After)
+    Overloaded { retry_after: u64 },
+    Failed(String),
+}
+
+fn worker_gone() -> ExecuteError {
+    ExecuteError::Failed("Worker channel closed".to_string())
+}
+
+impl std::fmt::Display for ExecuteError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ExecuteError::Overloaded { .. } => write!(f, "Server overloaded"),
+            ExecuteError::Failed(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+/// One entry per worker. The sender is swapped when the supervisor respawns
+/// (or re-grows) the worker behind it.
+struct WorkerSlot {
+    tx: RwLock<Sender<WorkerCommand>>,
+    restarts: AtomicU64,
+    /// Requests the worker holds (running or suspended in drift), published
+    /// by the worker after every command
+    pending: Arc<AtomicUsize>,
+    /// A worker thread runs behind the slot (a retired one until it drains)
+    alive: Arc<AtomicBool>,
+    /// The request whose JS the worker is running
+    watchdog: Arc<Watchdog>,
+}
+
+impl WorkerSlot {
+    /// In-flight load: queued in the channel + held by the worker.
+    #[inline(always)]
+    fn load(&self) -> usize {
+        self.tx.read().unwrap().len() + self.pending.load(Ordering::Relaxed)
+    }
+}
+
+/// The request a worker's isolate is running JS for, so an abandoned request
+/// can stop its own script without touching anything else the isolate runs.
+#[derive(Default)]
+struct Watchdog {
+    running: Mutex<Running>,
+}
+
+#[derive(Default)]
+struct Running {
+    /// Handle of the worker's current isolate
+    isolate: Option<v8::IsolateHandle>,
+    /// Cancel token of the request on the isolate's stack
+    request: Option<watch::Receiver<()>>,
+}
+
+impl Watchdog {
+    /// The worker built a new isolate.
+    fn attach(&self, isolate: v8::IsolateHandle) {
+        self.running.lock().unwrap().isolate = Some(isolate);
+    }
+
+    /// The request behind `token` is about to run JS.
+    fn enter(&self, token: Option<&watch::Receiver<()>>) {
+        self.running.lock().unwrap().request = token.cloned();
+    }
+
+    /// The script returned. A termination that arrived too late for it must
+    /// not hit the next one.
+    fn leave(&self) {
+        let mut running = self.running.lock().unwrap();
+        if running.request.take().is_some() {
+            if let Some(isolate) = &running.isolate {
+                isolate.cancel_terminate_execution();
+            }
+        }
+    }
+
+    /// Terminate the running script if it belongs to the request behind `token`.
+    fn terminate(&self, token: &watch::Receiver<()>) {
+        let running = self.running.lock().unwrap();
+        if let (Some(isolate), Some(request)) = (&running.isolate, &running.request) {
+            if request.same_channel(token) {
+                isolate.terminate_execution();
+            }
+        }
+    }
+}
+
+/// Owns the sending half of a request's cancel token for `execute`.
+///
+/// Answered requests send a value first, so their watchers stand down. Dropped
+/// without an answer (client disconnect, request timeout), it terminates the
+/// request's script if a worker is running it; the token closing cancels the
+/// request's drift ops and releases it on its worker.
+struct CancelGuard<'a> {
+    tx: Option<watch::Sender<()>>,
+    slots: &'a [WorkerSlot],
+}
+
+impl CancelGuard<'_> {
+    fn answered(mut self) {
+        if let Some(tx) = self.tx.take() {
+            let _ = tx.send(());
+        }
+    }
+
+    fn abandon(&mut self) {
+        let Some(tx) = self.tx.take() else {
+            return;
+        };
+        let token = tx.subscribe();
+        for slot in self.slots {
+            slot.watchdog.terminate(&token);
+        }
+    }
+}
+
+impl Drop for CancelGuard<'_> {
+    fn drop(&mut self) {
+        self.abandon();
+    }
+}
+
+/// How `execute` picks the first worker to try. Overflow still falls back to
+/// work-stealing across every worker.
+#[derive(Clone, Copy, Debug)]
+pub enum Scheduler {
+    RoundRobin,
+    /// Lowest in-flight count (ties broken round-robin)
+    LeastLoaded,
+    /// Lower load of two pseudo-randomly chosen workers
+    PowerOfTwo,
+}
+
+impl Scheduler {
+    /// Parse `__config.scheduler`. Defaults to round-robin.
+    pub fn from_config(name: Option<&str>) -> Self {
+        match name {
+            None | Some("round_robin") => Scheduler::RoundRobin,
+            Some("least_loaded") => Scheduler::LeastLoaded,
+            Some("p2c") | Some("power_of_two") => Scheduler::PowerOfTwo,
+            Some(other) => {
+                println!(
+                    "{} {} '{}' (expected round_robin, least_loaded or p2c), using round_robin",
+                    blue("[Titan]"),
+                    red("Unknown scheduler"),
+                    other
+                );
+                Scheduler::RoundRobin
+            }
+        }
+    }
+}
+
+/// Where a request's action runs. `Context` trades speed for safety: the
+/// action gets a fresh V8 context, so globals it mutates never reach another
+/// request (including interleaved drift replays).
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum Isolation {
+    /// The worker's long-lived context (fastest)
+    #[default]
+    Shared,
+    /// A pristine context per request, from the startup snapshot when possible
+    Context,
+}
+
+impl Isolation {
+    /// Parse `__config.isolation` or a route's `isolation`.
+    pub fn parse(name: &str) -> Option<Self> {
+        match name {
+            "shared" => Some(Isolation::Shared),
+            "context" => Some(Isolation::Context),
+            _ => None,
+        }
+    }
 }
 
 pub enum WorkerCommand {
@@ -29,6 +302,20 @@ pub enum WorkerCommand {
         drift_id: u32,
         result: WorkerAsyncResult,
     },
+    /// Recompile action bundles from disk (hot reload).
+    Reload,
+    /// The request is gone (client disconnect / timeout): drop its state.
+    Cancel { request_id: u32 },
+    /// A `setTimeout` / `setInterval` came due.
+    Timer { timer_id: u32 },
+    /// Pool shrink: no more requests are coming; exit once the held ones
+    /// are done.
+    Retire,
+    /// Profile or inspect the worker's isolate (admin endpoints).
+    Diagnose {
+        op: Diagnostic,
+        reply: oneshot::Sender<Result<String, String>>,
+    },
 }
 
 #[allow(dead_code)]
@@ -40,19 +327,160 @@ pub struct RequestTask {
     pub headers: SmallVec<[(String, String); 8]>,
     pub params: SmallVec<[(String, String); 4]>,
     pub query: SmallVec<[(String, String); 4]>,
+    /// Extra properties merged into the JS request object (e.g. `req.error`)
+    pub extra: Option<serde_json::Value>,
     pub response_tx: oneshot::Sender<WorkerResult>,
+    /// When `execute` was called (for queue wait reporting)
+    pub enqueued_at: Instant,
+    /// Gets a value once the request is answered; closes without one when it
+    /// is abandoned (see `CancelGuard`)
+    pub cancel: watch::Receiver<()>,
+    pub isolation: Isolation,
 }
 
 pub struct WorkerResult {
     pub json: serde_json::Value,
     pub timings: Vec<(String, f64)>,
+    /// Raw response body for binary responses (copied out of the ArrayBuffer).
+    /// When set, it replaces `json["body"]`.
+    pub body: Option<Bytes>,
+    /// Time spent waiting for a worker (admission queue + worker channel), ms
+    pub queue_ms: f64,
+}
+
+impl WorkerResult {
+    /// A JSON-only result (no binary body).
+    pub fn json(json: serde_json::Value, timings: Vec<(String, f64)>) -> Self {
+        Self {
+            json,
+            timings,
+            body: None,
+            queue_ms: 0.0,
+        }
+    }
+}
+
+/// Make `manager` the target of `t.call`, isolating callees like `routes` do.
+pub fn install(manager: &Arc<RuntimeManager>, routes: &Arc<RouteStore>, production_mode: bool) {
+    let _ = MANAGER.set(Arc::downgrade(manager));
+    let _ = ROUTES.set(Arc::downgrade(routes));
+    PRODUCTION.store(production_mode, Ordering::Relaxed);
+}
+
+/// Run `action` for a `t.call` drift op. `request` is the caller's
+/// request-like object, `chain` the actions already on the call stack and
+/// `depth` the callee's nesting level.
+///
+/// Resolves to the callee's result: plain JSON, its `_isResponse` object
+/// (binary bodies base64-encoded with `bodyEncoding`), or `{ error }` (with
+/// the callee's `stack` outside production only).
+pub async fn call_action(
+    action: String,
+    request: serde_json::Value,
+    mut chain: Vec<String>,
+    depth: usize,
+) -> serde_json::Value {
+    use base64::Engine;
+
+    chain.push(action.clone());
+    if depth > MAX_CALL_DEPTH {
+        return serde_json::json!({
+            "error": format!(
+                "t.call() depth limit ({}) exceeded: {}",
+                MAX_CALL_DEPTH,
+                chain.join(" -> ")
+            )
+        });
+    }
+    let Some(manager) = MANAGER.get().and_then(Weak::upgrade) else {
+        return serde_json::json!({ "error": "t.call() is not available" });
+    };
+
+    // Header names are case-insensitive: lowercase them like the HTTP path does
+    let mut headers: SmallVec<[(String, String); 8]> = string_pairs(&request["headers"])
+        .map(|(k, v)| (k.to_ascii_lowercase(), v))
+        .collect();
+    let body = match &request["body"] {
+        serde_json::Value::Null => None,
+        serde_json::Value::String(s) => Some(Bytes::from(s.clone())),
+        value => {
+            if !headers.iter().any(|(k, _)| k == "content-type") {
+                headers.push(("content-type".to_string(), "application/json".to_string()));
+            }
+            Some(Bytes::from(value.to_string()))
+        }
+    };
+    let params = string_pairs(&request["params"]).collect();
+    let query = string_pairs(&request["query"]).collect();
+
+    let method = request["method"].as_str().unwrap_or("CALL").to_ascii_uppercase();
+    let path = request["path"]
+        .as_str()
+        .map(str::to_string)
+        .unwrap_or_else(|| format!("/__call/{}", action));
+    let extra = serde_json::json!({ "call": { "depth": depth, "chain": chain } });
+    let isolation = ROUTES
+        .get()
+        .and_then(Weak::upgrade)
+        .map_or(Isolation::Shared, |routes| {
+            routes.current().isolation_for(&action)
+        });
+
+    let result = manager
+        .execute(
+            action,
+            method,
+            path,
+            body,
+            headers,
+            params,
+            query,
+            Some(extra),
+            isolation,
+        )
+        .await;
+
+    match result {
+        Ok(WorkerResult { mut json, body, .. }) => {
+            if let serde_json::Value::Object(map) = &mut json {
+                if let Some(bytes) = body {
+                    let encoded = base64::engine::general_purpose::STANDARD.encode(&bytes);
+                    map.insert("body".to_string(), encoded.into());
+                    map.insert("bodyEncoding".to_string(), "base64".into());
+                }
+                // The caller may pass the result on to its client
+                if PRODUCTION.load(Ordering::Relaxed) && map.contains_key("error") {
+                    map.remove("stack");
+                }
+            }
+            json
+        }
+        Err(ExecuteError::Overloaded { .. }) => {
+            serde_json::json!({ "error": "t.call() rejected: worker pool overloaded" })
+        }
+        Err(ExecuteError::Failed(e)) => serde_json::json!({ "error": e }),
+    }
+}
+
+/// `{ key: value }` as string pairs (non-string values JSON-encoded).
+fn string_pairs(value: &serde_json::Value) -> impl Iterator<Item = (String, String)> + '_ {
+    value.as_object().into_iter().flatten().map(|(k, v)| {
+        let v = v.as_str().map(str::to_string).unwrap_or_else(|| v.to_string());
+        (k.clone(), v)
+    })
 }
 
 impl RuntimeManager {
     pub fn new(
         project_root: std::path::PathBuf,
-        num_threads: usize,
+        pool: PoolConfig,
         stack_size: usize,
+        heap_limit: Option<usize>,
+        max_queue: usize,
+        max_queue_wait: Duration,
+        scheduler: Scheduler,
+        drift_timeout: Option<Duration>,
+        drift_concurrency: Option<usize>,
     ) -> Self {
         let (async_tx, mut async_rx) = mpsc::channel::<AsyncOpRequest>(2048);
         let tokio_handle = tokio::runtime::Handle::current();
@@ -62,85 +490,205 @@ impl RuntimeManager {
             while let Some(req) = async_rx.recv().await {
                 let drift_id = req.drift_id;
                 let respond_tx = req.respond_tx;
+                let limit = req.timeout.or(drift_timeout);
+                let concurrency = req.concurrency.or(drift_concurrency);
+                let cancel = req.cancel;
+                let op = req.op;
                 tokio::spawn(async move {
                     let start = std::time::Instant::now();
-                    let result = extensions::builtin::run_async_operation(req.op).await;
+                    let op = async {
+                        match op {
+                            // Per-op timeouts, so one slow op does not sink the batch
+                            TitanAsyncOp::Batch(ops) => builtin::run_batch(ops, limit, concurrency).await,
+                            op => (builtin::run_with_timeout(op, limit).await, Vec::new()),
+                        }
+                    };
+                    // Request gone: abandon the op. Dropping `respond_tx` tells
+                    // the worker to release the request.
+                    let (result, op_timings) = tokio::select! {
+                        result = op => result,
+                        _ = cancelled(cancel) => return,
+                    };
                     let duration_ms = start.elapsed().as_secs_f64() * 1000.0;
                     let _ = respond_tx.send(WorkerAsyncResult {
                         drift_id,
                         result,
                         duration_ms,
+                        op_timings,
                     });
                 });
             }
         });
 
-        // Create worker channels
-        let channel_capacity = 256;
-        let mut workers = Vec::with_capacity(num_threads);
+        let admission = Arc::new(Admission {
+            max_queue,
+            max_wait: max_queue_wait,
+            waiting: AtomicUsize::new(0),
+            space: Notify::new(),
+        });
 
-        let mut channels: Vec<(Sender<WorkerCommand>, crossbeam::channel::Receiver<WorkerCommand>)> =
-            Vec::with_capacity(num_threads);
+        let cores = if pool.pin_cores {
+            allowed_cores()
+        } else {
+            Vec::new()
+        };
+        if pool.pin_cores && cores.is_empty() {
+            println!(
+                "{} {}",
+                blue("[Titan]"),
+                red("pin_workers is only supported on Linux, workers are not pinned")
+            );
+        }
+
+        let (death_tx, death_rx) = crossbeam::channel::unbounded();
+        let spawner = WorkerSpawner {
+            admission: admission.clone(),
+            root: project_root,
+            stack_size,
+            heap_limit,
+            tokio_handle,
+            async_tx,
+            death_tx,
+            cores,
+        };
 
-        for _ in 0..num_threads {
-            let (tx, rx) = bounded(channel_capacity);
-            channels.push((tx, rx));
+        // Create a channel per slot and spawn the `min` first workers. Receiver
+        // clones are kept by the supervisor so a dead worker's queue is never
+        // disconnected.
+        let mut slots = Vec::with_capacity(pool.max);
+        let mut receivers = Vec::with_capacity(pool.max);
+        for i in 0..pool.max {
+            let (tx, rx) = bounded(CHANNEL_CAPACITY);
+            let pending = Arc::new(AtomicUsize::new(0));
+            let alive = Arc::new(AtomicBool::new(i < pool.min));
+            let watchdog = Arc::new(Watchdog::default());
+            if i < pool.min {
+                spawner.spawn(
+                    i,
+                    tx.clone(),
+                    rx.clone(),
+                    pending.clone(),
+                    alive.clone(),
+                    watchdog.clone(),
+                );
+            }
+            slots.push(WorkerSlot {
+                tx: RwLock::new(tx),
+                restarts: AtomicU64::new(0),
+                pending,
+                alive,
+                watchdog,
+            });
+            receivers.push(rx);
         }
+        let slots = Arc::new(slots);
+        let active = Arc::new(AtomicUsize::new(pool.min));
+
+        let supervisor = Supervisor {
+            spawner,
+            slots: slots.clone(),
+            receivers,
+            active: active.clone(),
+            pool,
+            idle_ticks: 0,
+        };
+        let supervisor = thread::Builder::new()
+            .name("titan-supervisor".to_string())
+            .spawn(move || supervisor.run(death_rx))
+            .expect("Failed to spawn worker supervisor");
 
-        let mut final_txs: Vec<Sender<WorkerCommand>> = Vec::with_capacity(num_threads);
-        for (tx, _) in &channels {
-            final_txs.push(tx.clone());
+        Self {
+            slots,
+            active,
+            pool,
+            admission,
+            scheduler,
+            round_robin_counter: AtomicUsize::new(0),
+            _supervisor: supervisor,
         }
+    }
 
-        // Spawn Worker Threads
-        for (i, (tx, rx)) in channels.into_iter().enumerate() {
-            let my_tx = tx.clone();
-            let root = project_root.clone();
-            let handle = tokio_handle.clone();
-            let async_tx = async_tx.clone();
+    /// Workers currently taking requests.
+    #[inline(always)]
+    fn active(&self) -> usize {
+        self.active.load(Ordering::SeqCst)
+    }
 
-            let handle = thread::Builder::new()
-                .name(format!("titan-worker-{}", i))
-                .stack_size(stack_size)
-                .spawn(move || {
-                    let mut rt = extensions::init_runtime_worker(
-                        i,
-                        root,
-                        my_tx,
-                        handle,
-                        async_tx,
-                        stack_size,
-                    );
-                    rt.bind_to_isolate();
-
-                    loop {
-                        match rx.recv() {
-                            Ok(cmd) => match cmd {
-                                WorkerCommand::Request(task) => {
-                                    handle_new_request(task, &mut rt);
-                                }
-                                WorkerCommand::Resume { drift_id, result } => {
-                                    handle_resume(drift_id, result, &mut rt);
-                                }
-                            },
-                            Err(_) => break,
-                        }
-                    }
-                })
-                .expect("Failed to spawn worker");
+    /// Configured pool bounds (min, max).
+    pub fn pool_bounds(&self) -> (usize, usize) {
+        (self.pool.min, self.pool.max)
+    }
 
-            workers.push(handle);
+    /// Ask every worker to recompile its actions. Blocks until each command is
+    /// queued, so call it from a blocking context.
+    pub fn reload_actions(&self) {
+        for slot in &self.slots[..self.active()] {
+            let tx = slot.tx.read().unwrap().clone();
+            let _ = tx.send(WorkerCommand::Reload);
         }
+    }
 
-        Self {
-            request_txs: final_txs,
-            round_robin_counter: AtomicUsize::new(0),
-            num_workers: num_threads,
-            _workers: workers,
+    /// Current in-flight load of each active worker (queued + running +
+    /// suspended).
+    pub fn worker_loads(&self) -> Vec<usize> {
+        self.slots[..self.active()]
+            .iter()
+            .map(|s| s.load())
+            .collect()
+    }
+
+    /// Number of times each active worker has been respawned after a crash.
+    pub fn worker_restarts(&self) -> Vec<u64> {
+        self.slots[..self.active()]
+            .iter()
+            .map(|s| s.restarts.load(Ordering::Relaxed))
+            .collect()
+    }
+
+    /// Run a diagnostic on one worker (`Some(id)`) or all of them. Results
+    /// are in worker order.
+    pub async fn diagnose(
+        &self,
+        worker: Option<usize>,
+        op: Diagnostic,
+    ) -> Result<Vec<(usize, Result<String, String>)>, String> {
+        let active = self.active();
+        let ids: Vec<usize> = match worker {
+            Some(id) if id >= active => {
+                return Err(format!("No worker {} (pool has {})", id, active));
+            }
+            Some(id) => vec![id],
+            None => (0..active).collect(),
+        };
+
+        let mut replies = Vec::with_capacity(ids.len());
+        for id in ids {
+            let (reply, rx) = oneshot::channel();
+            let sent = self.slots[id]
+                .tx
+                .read()
+                .unwrap()
+                .try_send(WorkerCommand::Diagnose { op, reply });
+            replies.push((id, sent.map(|()| rx)));
+        }
+
+        let mut results = Vec::with_capacity(replies.len());
+        for (id, rx) in replies {
+            let result = match rx {
+                Ok(rx) => match tokio::time::timeout(DIAGNOSE_TIMEOUT, rx).await {
+                    Ok(Ok(result)) => result,
+                    Ok(Err(_)) => Err("Worker exited".to_string()),
+                    Err(_) => Err("Worker did not answer in time".to_string()),
+                },
+                Err(_) => Err("Worker queue full".to_string()),
+            };
+            results.push((id, result));
         }
+        Ok(results)
     }
 
-    /// Execute an action on a worker. Uses round-robin with work-stealing fallback.
+    /// Execute an action on a worker. Uses round-robin with work-stealing
+    /// fallback, then the async admission queue when every worker is full.
     pub async fn execute(
         &self,
         action: String,
@@ -150,8 +698,82 @@ impl RuntimeManager {
         headers: SmallVec<[(String, String); 8]>,
         params: SmallVec<[(String, String); 4]>,
         query: SmallVec<[(String, String); 4]>,
-    ) -> Result<(serde_json::Value, Vec<(String, f64)>), String> {
+        extra: Option<serde_json::Value>,
+        isolation: Isolation,
+    ) -> Result<WorkerResult, ExecuteError> {
+        // The guard is dropped with this future (client disconnect, request timeout)
+        let (cmd, rx, cancel_guard) = self.request(
+            action, method, path, body, headers, params, query, extra, isolation,
+        );
+        self.submit(cmd).await?;
+
+        let result = rx.await.map_err(|_| worker_gone());
+        cancel_guard.answered();
+        result
+    }
+
+    /// `execute` with a deadline covering queueing and execution. Past it the
+    /// request is abandoned (its script terminated, a suspended request
+    /// released) and `None` is returned only once the worker has let go of
+    /// it, so nothing the request started is still running.
+    pub async fn execute_within(
+        &self,
+        limit: Option<Duration>,
+        action: String,
+        method: String,
+        path: String,
+        body: Option<Bytes>,
+        headers: SmallVec<[(String, String); 8]>,
+        params: SmallVec<[(String, String); 4]>,
+        query: SmallVec<[(String, String); 4]>,
+        extra: Option<serde_json::Value>,
+        isolation: Isolation,
+    ) -> Option<Result<WorkerResult, ExecuteError>> {
+        let (cmd, mut rx, mut cancel_guard) = self.request(
+            action, method, path, body, headers, params, query, extra, isolation,
+        );
+        let mut dispatched = false;
+        let run = async {
+            self.submit(cmd).await?;
+            dispatched = true;
+            (&mut rx).await.map_err(|_| worker_gone())
+        };
+        let result = match limit {
+            Some(limit) => tokio::time::timeout(limit, run).await.ok(),
+            None => Some(run.await),
+        };
+
+        if result.is_some() {
+            cancel_guard.answered();
+        } else {
+            cancel_guard.abandon();
+            // Answered with an error or released: either way the worker is done
+            if dispatched {
+                let _ = rx.await;
+            }
+        }
+        result
+    }
+
+    /// A request for `execute`, its response channel and cancel guard.
+    fn request(
+        &self,
+        action: String,
+        method: String,
+        path: String,
+        body: Option<Bytes>,
+        headers: SmallVec<[(String, String); 8]>,
+        params: SmallVec<[(String, String); 4]>,
+        query: SmallVec<[(String, String); 4]>,
+        extra: Option<serde_json::Value>,
+        isolation: Isolation,
+    ) -> (
+        WorkerCommand,
+        oneshot::Receiver<WorkerResult>,
+        CancelGuard<'_>,
+    ) {
         let (tx, rx) = oneshot::channel();
+        let (cancel_tx, cancel) = watch::channel(());
         let task = RequestTask {
             action_name: action,
             body,
@@ -160,48 +782,479 @@ impl RuntimeManager {
             headers,
             params,
             query,
+            extra,
             response_tx: tx,
+            enqueued_at: Instant::now(),
+            cancel,
+            isolation,
+        };
+        let cancel_guard = CancelGuard {
+            tx: Some(cancel_tx),
+            slots: &self.slots,
         };
+        (WorkerCommand::Request(task), rx, cancel_guard)
+    }
 
-        // Work-Stealing Distribution
-        let start_idx = self.round_robin_counter.fetch_add(1, Ordering::Relaxed) % self.num_workers;
-        let mut cmd = WorkerCommand::Request(task);
-
-        for attempt in 0..self.num_workers {
-            let idx = (start_idx + attempt) % self.num_workers;
-            match self.request_txs[idx].try_send(cmd) {
-                Ok(()) => {
-                    return match rx.await {
-                        Ok(res) => Ok((res.json, res.timings)),
-                        Err(_) => Err("Worker channel closed".to_string()),
-                    };
-                }
-                Err(TrySendError::Full(returned)) => {
+    /// Hand a request to a worker, waiting in the admission queue if needed.
+    async fn submit(&self, cmd: WorkerCommand) -> Result<(), ExecuteError> {
+        if let Err(cmd) = self.try_dispatch(cmd) {
+            self.wait_for_worker(cmd).await?;
+        }
+        Ok(())
+    }
+
+    /// First choice per the scheduler, then every other worker (work stealing);
+    /// only the inspected worker while DevTools is attached. Hands the command back if all worker channels are full.
+    fn try_dispatch(&self, mut cmd: WorkerCommand) -> Result<(), WorkerCommand> {
+        if let Some(idx) = inspector::pinned() {
+            let sent = self.slots[idx].tx.read().unwrap().try_send(cmd);
+            return sent.map_err(TrySendError::into_inner);
+        }
+        let n = self.active();
+        let start_idx = self.pick_worker(n);
+
+        for attempt in 0..n {
+            let idx = (start_idx + attempt) % n;
+            let tx = self.slots[idx].tx.read().unwrap();
+            // Retired since `n` was read (see `Supervisor::shrink`)
+            if idx >= self.active() {
+                continue;
+            }
+            let sent = tx.try_send(cmd);
+            match sent {
+                Ok(()) => return Ok(()),
+                Err(TrySendError::Full(returned)) | Err(TrySendError::Disconnected(returned)) => {
                     cmd = returned;
                 }
-                Err(TrySendError::Disconnected(_)) => {
-                    return Err("Worker disconnected".to_string());
+            }
+        }
+        Err(cmd)
+    }
+
+    /// Index of the worker to try first among the `n` active ones.
+    #[inline]
+    fn pick_worker(&self, n: usize) -> usize {
+        let tick = self.round_robin_counter.fetch_add(1, Ordering::Relaxed);
+        match self.scheduler {
+            Scheduler::RoundRobin => tick % n,
+            Scheduler::LeastLoaded => (0..n)
+                .map(|k| (tick + k) % n)
+                .min_by_key(|&i| self.slots[i].load())
+                .unwrap_or(0),
+            Scheduler::PowerOfTwo => {
+                let h = splitmix64(tick as u64);
+                let a = (h % n as u64) as usize;
+                let b = ((h >> 32) % n as u64) as usize;
+                if self.slots[b].load() < self.slots[a].load() {
+                    b
+                } else {
+                    a
                 }
             }
         }
+    }
+
+    /// All workers are full: wait (without blocking a tokio thread) until one
+    /// dequeues, bounded by `max_queue` waiters and `max_wait`.
+    async fn wait_for_worker(&self, mut cmd: WorkerCommand) -> Result<(), ExecuteError> {
+        let adm = &self.admission;
+        let retry_after = adm.max_wait.as_secs().max(1);
+
+        if adm.waiting.fetch_add(1, Ordering::SeqCst) >= adm.max_queue {
+            adm.waiting.fetch_sub(1, Ordering::SeqCst);
+            return Err(ExecuteError::Overloaded { retry_after });
+        }
+        let _ticket = QueueTicket(&adm.waiting);
+        let deadline = tokio::time::Instant::now() + adm.max_wait;
+
+        loop {
+            // Register interest before retrying so a dequeue in between is not missed
+            let space = adm.space.notified();
+            match self.try_dispatch(cmd) {
+                Ok(()) => return Ok(()),
+                Err(returned) => cmd = returned,
+            }
+            if tokio::time::timeout_at(deadline, space).await.is_err() {
+                return Err(ExecuteError::Overloaded { retry_after });
+            }
+        }
+    }
+}
+
+/// SplitMix64 — cheap, well-mixed pseudo-random bits for power-of-two choices.
+#[inline(always)]
+fn splitmix64(x: u64) -> u64 {
+    let mut z = x.wrapping_add(0x9E37_79B9_7F4A_7C15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    z ^ (z >> 31)
+}
+
+/// Everything needed to (re)spawn a worker thread.
+struct WorkerSpawner {
+    admission: Arc<Admission>,
+    root: PathBuf,
+    stack_size: usize,
+    heap_limit: Option<usize>,
+    tokio_handle: tokio::runtime::Handle,
+    async_tx: mpsc::Sender<AsyncOpRequest>,
+    death_tx: Sender<usize>,
+    /// Cores to pin workers to (empty: no pinning)
+    cores: Vec<usize>,
+}
+
+/// Tells the supervisor that a worker thread is unwinding from a panic.
+struct DeathNotice {
+    id: usize,
+    tx: Sender<usize>,
+}
+
+impl Drop for DeathNotice {
+    fn drop(&mut self) {
+        if thread::panicking() {
+            let _ = self.tx.send(self.id);
+        }
+    }
+}
+
+impl WorkerSpawner {
+    fn spawn(
+        &self,
+        i: usize,
+        my_tx: Sender<WorkerCommand>,
+        rx: Receiver<WorkerCommand>,
+        pending: Arc<AtomicUsize>,
+        alive: Arc<AtomicBool>,
+        watchdog: Arc<Watchdog>,
+    ) {
+        let root = self.root.clone();
+        let handle = self.tokio_handle.clone();
+        let async_tx = self.async_tx.clone();
+        let stack_size = self.stack_size;
+        let heap_limit = self.heap_limit;
+        let admission = self.admission.clone();
+        let notice = DeathNotice {
+            id: i,
+            tx: self.death_tx.clone(),
+        };
+        let core = (!self.cores.is_empty()).then(|| self.cores[i % self.cores.len()]);
+
+        thread::Builder::new()
+            .name(format!("titan-worker-{}", i))
+            .stack_size(stack_size)
+            .spawn(move || {
+                let _notice = notice;
+                if let Some(core) = core {
+                    if !pin_to_core(core) {
+                        println!(
+                            "{} {}",
+                            blue("[Titan]"),
+                            red(&format!("Failed to pin worker {} to core {}", i, core))
+                        );
+                    }
+                }
+                let build = || {
+                    extensions::init_runtime_worker(
+                        i,
+                        root.clone(),
+                        my_tx.clone(),
+                        handle.clone(),
+                        async_tx.clone(),
+                        stack_size,
+                        heap_limit,
+                    )
+                };
+                let mut rt = build();
+                rt.bind_to_isolate();
+                watchdog.attach(rt.isolate.thread_safe_handle());
+                let inspector_inbox = inspector::inbox(i);
+                let mut retiring = false;
+
+                loop {
+                    let cmd = crossbeam::select! {
+                        recv(rx) -> cmd => Some(cmd),
+                        recv(inspector_inbox) -> msg => {
+                            if let Ok(msg) = msg {
+                                inspector::handle(&mut rt, msg);
+                            }
+                            None
+                        }
+                    };
+                    let Some(cmd) = cmd else {
+                        continue;
+                    };
+                    if admission.waiting.load(Ordering::SeqCst) > 0 {
+                        admission.space.notify_one();
+                    }
+                    match cmd {
+                        Ok(cmd) => match cmd {
+                            WorkerCommand::Request(task) => {
+                                handle_new_request(task, &mut rt, &watchdog);
+                            }
+                            WorkerCommand::Resume { drift_id, result } => {
+                                handle_resume(drift_id, result, &mut rt, &watchdog);
+                            }
+                            WorkerCommand::Reload => {
+                                extensions::reload_actions(&mut rt);
+                            }
+                            WorkerCommand::Cancel { request_id } => {
+                                release_request(&mut rt, request_id);
+                            }
+                            WorkerCommand::Timer { timer_id } => {
+                                handle_timer(timer_id, &mut rt, &watchdog);
+                            }
+                            WorkerCommand::Diagnose { op, reply } => {
+                                let _ = reply.send(diagnostics::run(&mut rt, op));
+                            }
+                            WorkerCommand::Retire => {
+                                retiring = true;
+                            }
+                        },
+                        Err(_) => break,
+                    }
+
+                    // Rebuild the isolate in place after a heap-limit termination.
+                    // The old one is dropped first: V8 isolates on a thread must
+                    // be disposed in reverse creation order.
+                    if rt.heap_limit_hit() {
+                        let counters = abort_runtime(&mut rt);
+                        drop(rt);
+                        rt = build();
+                        (rt.drift_counter, rt.request_counter, rt.timer_counter) = counters;
+                        rt.bind_to_isolate();
+                        watchdog.attach(rt.isolate.thread_safe_handle());
+                    }
+
+                    pending.store(rt.pending_requests.len(), Ordering::Relaxed);
+                    if retiring && rt.pending_requests.is_empty() {
+                        break;
+                    }
+                }
+
+                drop(rt);
+                pending.store(0, Ordering::Relaxed);
+                alive.store(false, Ordering::SeqCst);
+            })
+            .expect("Failed to spawn worker");
+    }
+}
+
+/// Owns worker lifecycles: respawns crashed workers and, when autoscaling,
+/// grows and shrinks the pool.
+struct Supervisor {
+    spawner: WorkerSpawner,
+    slots: Arc<Vec<WorkerSlot>>,
+    receivers: Vec<Receiver<WorkerCommand>>,
+    active: Arc<AtomicUsize>,
+    pool: PoolConfig,
+    /// Consecutive scaling ticks the pool looked oversized
+    idle_ticks: u32,
+}
+
+impl Supervisor {
+    fn run(mut self, death_rx: Receiver<usize>) {
+        let ticks = if self.pool.autoscale() {
+            crossbeam::channel::tick(SCALE_INTERVAL)
+        } else {
+            crossbeam::channel::never()
+        };
+        loop {
+            crossbeam::select! {
+                recv(death_rx) -> id => match id {
+                    Ok(id) => self.respawn(id),
+                    Err(_) => return,
+                },
+                recv(ticks) -> _ => self.scale(),
+            }
+        }
+    }
+
+    /// Respawn a worker that died by panic.
+    ///
+    /// The dead worker gets a fresh channel. Requests still queued on the old
+    /// one are moved over; queued drift resumes are dropped, since the
+    /// requests they belong to died with the old runtime (their callers
+    /// already got an error when the response senders were dropped). A
+    /// retired worker is not respawned: its queued requests go to an active one.
+    fn respawn(&mut self, id: usize) {
+        let slot = &self.slots[id];
+        let active = self.active.load(Ordering::SeqCst);
+        if id >= active {
+            slot.pending.store(0, Ordering::Relaxed);
+            slot.alive.store(false, Ordering::SeqCst);
+            let tx = self.slots[id % active].tx.read().unwrap().clone();
+            let requeued = requeue(&self.receivers[id], &tx);
+            println!(
+                "{} {}",
+                blue("[Titan]"),
+                red(&format!(
+                    "Retired worker {} crashed ({} queued request(s) requeued)",
+                    id, requeued
+                ))
+            );
+            return;
+        }
+
+        let restarts = slot.restarts.fetch_add(1, Ordering::Relaxed) + 1;
+        let (tx, old_rx) = self.renew(id);
+        let requeued = requeue(&old_rx, &tx);
+
+        println!(
+            "{} {}",
+            blue("[Titan]"),
+            red(&format!(
+                "Worker {} crashed, respawned (restart #{}, {} queued request(s) requeued)",
+                id, restarts, requeued
+            ))
+        );
+    }
+
+    /// Spawn a worker on a fresh channel in slot `id`; returns its sender and
+    /// the slot's previous receiver.
+    fn renew(&mut self, id: usize) -> (Sender<WorkerCommand>, Receiver<WorkerCommand>) {
+        let slot = &self.slots[id];
+        let (tx, rx) = bounded(CHANNEL_CAPACITY);
+        *slot.tx.write().unwrap() = tx.clone();
+        let old_rx = std::mem::replace(&mut self.receivers[id], rx.clone());
+        slot.pending.store(0, Ordering::Relaxed);
+        slot.alive.store(true, Ordering::SeqCst);
+        self.spawner.spawn(
+            id,
+            tx.clone(),
+            rx,
+            slot.pending.clone(),
+            slot.alive.clone(),
+            slot.watchdog.clone(),
+        );
+        (tx, old_rx)
+    }
+
+    /// Grow on backlog (queued requests, admission waiters) or many
+    /// suspended requests; shrink after a sustained idle period.
+    fn scale(&mut self) {
+        let n = self.active.load(Ordering::SeqCst);
+        let workers = &self.slots[..n];
+        let queued: usize = workers.iter().map(|s| s.tx.read().unwrap().len()).sum();
+        let suspended: usize = workers
+            .iter()
+            .map(|s| s.pending.load(Ordering::Relaxed))
+            .sum();
+        let waiting = self.spawner.admission.waiting.load(Ordering::SeqCst);
+
+        let backlog = waiting > 0
+            || queued >= n * GROW_QUEUED_PER_WORKER
+            || suspended >= n * GROW_SUSPENDED_PER_WORKER;
+        if backlog {
+            self.idle_ticks = 0;
+            if n < self.pool.max {
+                self.grow(n);
+            }
+            return;
+        }
+
+        // One worker fewer would still be under half the suspended threshold
+        let oversized = queued == 0 && suspended < (n - 1) * GROW_SUSPENDED_PER_WORKER / 2;
+        if !oversized || n <= self.pool.min {
+            self.idle_ticks = 0;
+            return;
+        }
+        self.idle_ticks += 1;
+        if self.idle_ticks >= SHRINK_AFTER_TICKS {
+            self.idle_ticks = 0;
+            self.shrink(n);
+        }
+    }
+
+    /// Start worker `n` (the first inactive slot).
+    fn grow(&mut self, n: usize) {
+        // A retired worker still draining there: try again next tick
+        if self.slots[n].alive.load(Ordering::SeqCst) {
+            return;
+        }
+        self.renew(n);
+        self.active.store(n + 1, Ordering::SeqCst);
+        println!(
+            "{} {}",
+            blue("[Titan]"),
+            gray(&format!("Pool grown to {} worker(s)", n + 1))
+        );
+    }
+
+    /// Retire the last active worker.
+    fn shrink(&mut self, n: usize) {
+        let id = n - 1;
+        self.active.store(id, Ordering::SeqCst);
+        // Wait out dispatches that picked the worker before the store; later
+        // ones see it inactive, so `Retire` is the last command it gets
+        let tx = self.slots[id].tx.write().unwrap().clone();
+        let _ = tx.send(WorkerCommand::Retire);
+        println!(
+            "{} {}",
+            blue("[Titan]"),
+            gray(&format!("Pool shrunk to {} worker(s)", id))
+        );
+    }
+}
 
-        // All workers full — blocking send to the original target as last resort
-        self.request_txs[start_idx]
-            .send(cmd)
-            .map_err(|e| e.to_string())?;
+/// Move the requests queued on `rx` to `tx`; returns how many moved.
+fn requeue(rx: &Receiver<WorkerCommand>, tx: &Sender<WorkerCommand>) -> usize {
+    let mut requeued = 0;
+    for cmd in rx.try_iter() {
+        if let WorkerCommand::Request(_) = cmd {
+            if tx.send(cmd).is_ok() {
+                requeued += 1;
+            }
+        }
+    }
+    requeued
+}
 
-        match rx.await {
-            Ok(res) => Ok((res.json, res.timings)),
-            Err(_) => Err("Worker channel closed".to_string()),
+/// CPUs this process may run on (for `__config.pin_workers`).
+#[cfg(target_os = "linux")]
+fn allowed_cores() -> Vec<usize> {
+    // SAFETY: `cpu_set_t` is plain data; the kernel fills it in
+    unsafe {
+        let mut set: libc::cpu_set_t = std::mem::zeroed();
+        if libc::sched_getaffinity(0, std::mem::size_of::<libc::cpu_set_t>(), &mut set) != 0 {
+            return Vec::new();
         }
+        (0..libc::CPU_SETSIZE as usize)
+            .filter(|&cpu| libc::CPU_ISSET(cpu, &set))
+            .collect()
+    }
+}
+
+#[cfg(not(target_os = "linux"))]
+fn allowed_cores() -> Vec<usize> {
+    Vec::new()
+}
+
+/// Pin the calling thread to `core`.
+#[cfg(target_os = "linux")]
+fn pin_to_core(core: usize) -> bool {
+    // SAFETY: as above; pid 0 is the calling thread
+    unsafe {
+        let mut set: libc::cpu_set_t = std::mem::zeroed();
+        libc::CPU_SET(core, &mut set);
+        libc::sched_setaffinity(0, std::mem::size_of::<libc::cpu_set_t>(), &set) == 0
     }
 }
 
+#[cfg(not(target_os = "linux"))]
+fn pin_to_core(_core: usize) -> bool {
+    false
+}
+
 /// Handle a new incoming request.
 ///
 /// OPTIMIZATION: Deferred cloning.
 /// Only stores data if drift (async suspend) happens.
-fn handle_new_request(task: RequestTask, rt: &mut TitanRuntime) {
+fn handle_new_request(task: RequestTask, rt: &mut TitanRuntime, watchdog: &Watchdog) {
+    // Abandoned while queued (client gone, timed out): nothing to run
+    if task.cancel.has_changed().is_err() {
+        return;
+    }
+
     rt.request_counter += 1;
     let request_id = rt.request_counter;
 
@@ -210,42 +1263,113 @@ fn handle_new_request(task: RequestTask, rt: &mut TitanRuntime) {
 
     let drift_count = rt.drift_counter;
     rt.request_start_counters.insert(request_id, drift_count);
+    rt.request_queue_ms.insert(
+        request_id,
+        task.enqueued_at.elapsed().as_secs_f64() * 1000.0,
+    );
+    rt.request_cancel.insert(request_id, task.cancel.clone());
+    watchdog.enter(Some(&task.cancel));
+
+    // Resolve the action once; a suspended request replays this exact
+    // function even if a hot reload swaps `rt.actions` in the meantime.
+    let action_fn = match task.isolation {
+        Isolation::Shared => rt.actions.get(&task.action_name).cloned(),
+        Isolation::Context => extensions::isolated_context(rt, request_id, &task.action_name),
+    };
 
     // Execute action — pass references, body is O(1) Bytes clone
     extensions::execute_action_optimized(
         rt,
         request_id,
         &task.action_name,
+        action_fn.clone(),
         task.body.clone(), // Bytes::clone() is O(1) refcount bump
         &task.method,
         &task.path,
         &task.headers,
         &task.params,
         &task.query,
+        task.extra.as_ref(),
     );
+    watchdog.leave();
 
     // Deferred cloning decision
     if !rt.pending_requests.contains_key(&request_id) {
         // Completed synchronously — no data needed, minimal cleanup
         rt.request_start_counters.remove(&request_id);
+        rt.request_queue_ms.remove(&request_id);
+        rt.request_cancel.remove(&request_id);
+        rt.release_context(request_id);
+        rt.clear_timers(request_id);
+        // Positions taken without suspending (`t.jobs.enqueue`)
+        if rt.drift_counter != drift_count {
+            release_drifts(rt, request_id);
+        }
     } else {
+        // Released as soon as it is abandoned, whatever it waits on
+        watch_cancel(rt, request_id, task.cancel);
+
+        // An async action awaiting a promise-based drift resumes via its
+        // continuation, nothing to keep for replay
+        if rt.request_objects.contains_key(&request_id) {
+            return;
+        }
         // Suspended via drift — MOVE (not clone) data for resume replay.
         rt.active_requests.insert(
             request_id,
             extensions::RequestData {
                 action_name: task.action_name,
+                action_fn,
                 body: task.body,
                 method: task.method,
                 path: task.path,
                 headers: task.headers.into_vec(),
                 params: task.params.into_vec(),
                 query: task.query.into_vec(),
+                extra: task.extra,
             },
         );
     }
 }
 
-fn handle_resume(drift_id: u32, result: WorkerAsyncResult, rt: &mut TitanRuntime) {
+/// Fail every request still tied to an isolate that hit its heap cap
+/// (including ones suspended in drift); their JS state is gone.
+///
+/// Returns the `(drift_counter, request_counter, timer_counter)` for the
+/// replacement isolate, so drift results and timers still in flight for the
+/// old one can never match a new ID.
+fn abort_runtime(old: &mut TitanRuntime) -> (u32, u32, u32) {
+    println!(
+        "{} {}",
+        blue("[Titan]"),
+        red(&format!(
+            "Worker {} exceeded its heap limit, isolate recycled ({} request(s) aborted)",
+            old.id,
+            old.pending_requests.len()
+        ))
+    );
+
+    for (_, tx) in old.pending_requests.drain() {
+        let _ = tx.send(WorkerResult::json(
+            serde_json::json!({"error": "Heap limit exceeded"}),
+            vec![],
+        ));
+    }
+
+    for (_, timer) in old.timers.drain() {
+        timer.task.abort();
+    }
+
+    let max_drift = old.drift_to_request.keys().copied().max().map_or(0, |d| d + 1);
+    (old.drift_counter.max(max_drift), old.request_counter, old.timer_counter)
+}
+
+fn handle_resume(
+    drift_id: u32,
+    result: WorkerAsyncResult,
+    rt: &mut TitanRuntime,
+    watchdog: &Watchdog,
+) {
     let req_id = rt.drift_to_request.get(&drift_id).copied().unwrap_or(0);
 
     let timing_type = if result.result.get("error").is_some() {
@@ -253,32 +1377,137 @@ fn handle_resume(drift_id: u32, result: WorkerAsyncResult, rt: &mut TitanRuntime
     } else {
         "drift"
     };
-    rt.request_timings
-        .entry(req_id)
-        .or_default()
-        .push((timing_type.to_string(), result.duration_ms));
-
-    rt.completed_drifts.insert(drift_id, result.result);
+    if req_id != 0 {
+        let timings = rt.request_timings.entry(req_id).or_default();
+        timings.push((timing_type.to_string(), result.duration_ms));
+        timings.extend(result.op_timings);
+    }
 
-    if let Some(req_data) = rt.active_requests.get(&req_id).cloned() {
+    watchdog.enter(rt.request_cancel.get(&req_id));
+    if let Some(resolver) = rt.pending_drifts.remove(&drift_id) {
+        // Async action: resolve its Promise and let it continue where it left off
+        rt.drift_to_request.remove(&drift_id);
+        extensions::resolve_drift(rt, req_id, resolver, result.result);
+    } else if let Some(req_data) = rt.active_requests.get(&req_id).cloned() {
+        // Sync action: replay from the start with the completed drift injected
+        rt.completed_drifts.insert(drift_id, result.result);
         let start_counter = rt.request_start_counters.get(&req_id).copied().unwrap_or(0);
+        let high_water = rt.drift_counter;
         rt.drift_counter = start_counter;
+        // The replay sets its timers again
+        rt.clear_timers(req_id);
 
         extensions::execute_action_optimized(
             rt,
             req_id,
             &req_data.action_name,
+            req_data.action_fn,
             req_data.body,
             &req_data.method,
             &req_data.path,
             &req_data.headers,
             &req_data.params,
             &req_data.query,
+            req_data.extra.as_ref(),
         );
+
+        // Never hand out IDs below ones still in flight for other requests
+        rt.drift_counter = rt.drift_counter.max(high_water);
     }
+    watchdog.leave();
 
     if req_id != 0 && !rt.pending_requests.contains_key(&req_id) {
-        rt.active_requests.remove(&req_id);
-        rt.request_start_counters.remove(&req_id);
+        release_request(rt, req_id);
+    }
+}
+
+/// Run a due timer. Intervals are re-armed before the callback runs, so the
+/// callback itself may clear them.
+fn handle_timer(timer_id: u32, rt: &mut TitanRuntime, watchdog: &Watchdog) {
+    // Cleared in the meantime
+    let Some(timer) = rt.timers.get(&timer_id) else {
+        return;
+    };
+    let req_id = timer.request_id;
+    if !rt.pending_requests.contains_key(&req_id) {
+        rt.clear_timers(req_id);
+        return;
+    }
+
+    let (callback, request) = match timer.interval {
+        Some(every) => {
+            let fired = (timer.callback.clone(), timer.request.clone());
+            let task = extensions::arm_timer(rt, timer_id, every);
+            if let Some(timer) = rt.timers.get_mut(&timer_id) {
+                timer.task = task;
+            }
+            fired
+        }
+        None => {
+            let timer = rt.timers.remove(&timer_id).unwrap();
+            (timer.callback, timer.request)
+        }
+    };
+
+    watchdog.enter(rt.request_cancel.get(&req_id));
+    extensions::fire_timer(rt, req_id, callback, request);
+    watchdog.leave();
+
+    if !rt.pending_requests.contains_key(&req_id) {
+        release_request(rt, req_id);
+    }
+}
+
+/// Drop every piece of per-request state a worker holds: replay data,
+/// completed and pending drifts, timers, timings and the response sender (if the
+/// request was cancelled before finishing).
+fn release_request(rt: &mut TitanRuntime, req_id: u32) {
+    rt.pending_requests.remove(&req_id);
+    rt.active_requests.remove(&req_id);
+    rt.request_start_counters.remove(&req_id);
+    rt.request_queue_ms.remove(&req_id);
+    rt.request_objects.remove(&req_id);
+    rt.request_timings.remove(&req_id);
+    rt.request_cancel.remove(&req_id);
+    rt.release_context(req_id);
+    rt.clear_timers(req_id);
+    release_drifts(rt, req_id);
+}
+
+/// Forget the drifts (and drift positions) of a request.
+fn release_drifts(rt: &mut TitanRuntime, req_id: u32) {
+    let drifts: Vec<u32> = rt
+        .drift_to_request
+        .iter()
+        .filter(|(_, r)| **r == req_id)
+        .map(|(d, _)| *d)
+        .collect();
+    for drift_id in drifts {
+        rt.drift_to_request.remove(&drift_id);
+        rt.completed_drifts.remove(&drift_id);
+        rt.pending_drifts.remove(&drift_id);
+    }
+}
+
+/// Release a suspended request as soon as it is abandoned (client
+/// disconnect, request timeout), whatever it is waiting on.
+fn watch_cancel(rt: &TitanRuntime, request_id: u32, mut token: watch::Receiver<()>) {
+    let worker_tx = rt.worker_tx.clone();
+    rt.tokio_handle.spawn(async move {
+        // Answered requests send a value before the token closes
+        if token.changed().await.is_err() {
+            let _ = worker_tx.send(WorkerCommand::Cancel { request_id });
+        }
+    });
+}
+
+/// Resolves once the request is answered or abandoned (see `CancelGuard`);
+/// never without a token.
+async fn cancelled(token: Option<watch::Receiver<()>>) {
+    match token {
+        Some(mut rx) => {
+            let _ = rx.changed().await;
+        }
+        None => std::future::pending().await,
     }
 }
\ No newline at end of file
@@ -139,7 +139,7 @@ export async function bundleFile(options) {
       bundle: true,
       outfile,
       format,
-      globalName,
+      globalName: format === 'iife' ? globalName : undefined,
       platform,
       target,
       banner,
@@ -199,32 +199,21 @@ export async function bundle() {
   for (const file of files) {
     const actionName = path.basename(file, path.extname(file));
     const entryPoint = path.join(actionsDir, file);
-    const outfile = path.join(bundleDir, actionName + ".jsbundle");
+    // Loaded by the server as an ES module. The action is its default export;
+    // an export named after the action is only a fallback for older bundles.
+    const outfile = path.join(bundleDir, actionName + ".mjs");
 
     try {
       await bundleFile({
         entryPoint,
         outfile,
-        format: 'iife',
-        globalName: '__titan_exports',
+        format: 'esm',
         platform: 'node',
-        target: 'es2020',
-        banner: { js: "var Titan = t;" },
-        footer: {
-          js: `
-(function () {
-  const fn =
-    __titan_exports["${actionName}"] ||
-    __titan_exports.default;
-
-  if (typeof fn !== "function") {
-    throw new Error("[Titan] Action '${actionName}' not found or not a function");
-  }
-
-  globalThis["${actionName}"] = globalThis.defineAction(fn);
-})();
-`
-        }
+        // es2022: top-level await
+        target: 'es2022',
+        // <action>.mjs.map: stack traces point at the original sources
+        sourcemap: true,
+        banner: { js: "var Titan = t;" }
       });
 
     } catch (error) {